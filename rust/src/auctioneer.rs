@@ -0,0 +1,50 @@
+use godot::{
+    classes::{Area2D, IArea2D, InputEvent, InputEventKey},
+    global::Key,
+    prelude::*,
+};
+
+use crate::player::Player;
+
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct Auctioneer {
+    is_player_near: bool,
+    base: Base<Area2D>,
+}
+
+#[godot_api]
+impl Auctioneer {
+    #[signal]
+    fn on_toggle_auction(&mut self);
+
+    #[signal]
+    fn on_close_auction(&mut self);
+
+    #[func]
+    fn area2d_entered(&mut self, player_area2d: Gd<Area2D>) {
+        let is_player_near = self.base().overlaps_area(player_area2d);
+
+        if self.is_player_near && !is_player_near {
+            self.base_mut().emit_signal("on_close_auction".into(), &[]);
+        }
+        self.is_player_near = is_player_near;
+    }
+}
+
+#[godot_api]
+impl IArea2D for Auctioneer {
+    fn ready(&mut self) {
+        let mut player_node = self.base_mut().get_node_as::<Player>("../Player");
+        let area2d_entered_callable = self.base().callable("area2d_entered");
+        player_node.connect("on_area2d_entered".into(), area2d_entered_callable);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && e.get_keycode() == Key::E && self.is_player_near {
+                self.base_mut().emit_signal("on_toggle_auction".into(), &[]);
+            }
+        }
+    }
+}
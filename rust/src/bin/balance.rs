@@ -0,0 +1,331 @@
+// Runs the market simulation with a greedy AI trader and the scenario's rival
+// traders for a number of in-game days without Godot, and writes prices, net
+// worth and profit per route.
+//
+//   cargo run --bin balance -- --days 30 --seed 7 --format csv --out balance
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use rust::economy::{
+    clock::GameTime, rivals::travel_time, routes::RouteMap, scenario, simulation::MarketSimulation,
+    trader::Trader,
+};
+
+const USAGE: &str = "usage: balance [--days N] [--seed N] [--funds N] [--capacity N] \
+                     [--format csv|json] [--out DIR]";
+
+#[derive(PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    days: u64,
+    seed: u64,
+    funds: i64,
+    capacity: u32,
+    format: Format,
+    out: PathBuf,
+}
+
+struct PriceRow {
+    day: u64,
+    market: String,
+    item: String,
+    price: u32,
+    stock: u32,
+}
+
+struct NetWorthRow {
+    day: u64,
+    location: String,
+    funds: i64,
+    net_worth: i64,
+}
+
+#[derive(Default)]
+struct RouteStats {
+    trips: u32,
+    units: u32,
+    profit: i64,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        days: 30,
+        seed: 0,
+        funds: 100,
+        capacity: 40,
+        format: Format::Csv,
+        out: PathBuf::from("balance"),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--days" => options.days = value()?.parse().map_err(|e| format!("--days: {e}"))?,
+            "--seed" => options.seed = value()?.parse().map_err(|e| format!("--seed: {e}"))?,
+            "--funds" => options.funds = value()?.parse().map_err(|e| format!("--funds: {e}"))?,
+            "--capacity" => {
+                options.capacity = value()?.parse().map_err(|e| format!("--capacity: {e}"))?
+            }
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format {other}")),
+                }
+            }
+            "--out" => options.out = PathBuf::from(value()?),
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown argument {other}\n{USAGE}")),
+        }
+    }
+
+    Ok(options)
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Tolls and food for a trip between two markets, nothing within a town
+fn road_cost(sim: &MarketSimulation, map: &RouteMap, from: &str, to: &str) -> u32 {
+    let town = |market: &str| sim.market(market).map(|m| m.town.clone());
+    match (town(from), town(to)) {
+        (Some(from), Some(to)) if from != to => map.route(&from, &to).map_or(0, |r| r.cost()),
+        _ => 0,
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut sim = scenario::default_world(options.seed);
+    let start = match sim.markets().first() {
+        Some(market) => market.name.clone(),
+        None => {
+            eprintln!("scenario has no markets");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut trader = Trader::new("Balancer", options.funds, &start, options.capacity);
+    // When the trader reaches `trader.location` and can trade again
+    let mut ready_at = sim.time();
+    // Rivals compete for the same spreads, as they do in the game
+    let map = scenario::default_routes();
+    let mut rivals = scenario::default_rivals();
+
+    let mut prices = vec![];
+    let mut net_worth = vec![];
+    let mut routes: BTreeMap<(String, String), RouteStats> = BTreeMap::new();
+
+    for day in 1..=options.days {
+        sim.advance_with(GameTime::from_days(1), |sim| {
+            rivals.tick(sim, &map);
+            if sim.time() < ready_at {
+                return;
+            }
+
+            for sale in trader.sell_all(sim) {
+                let stats = routes.entry((sale.origin, sale.market)).or_default();
+                stats.trips += 1;
+                stats.units += sale.quantity;
+                stats.profit += sale.profit;
+            }
+
+            // Nothing worth buying, look again in an hour
+            let from = trader.location.clone();
+            // Tolls and food on the way count against the route
+            let costs: BTreeMap<String, i64> = sim
+                .markets()
+                .iter()
+                .map(|m| (m.name.clone(), road_cost(sim, &map, &from, &m.name) as i64))
+                .collect();
+            let trip_cost = |to: &str| costs.get(to).copied().unwrap_or(0);
+            let Some(destination) = trader.buy_best_deal(sim, trip_cost) else {
+                return;
+            };
+            let cost = trip_cost(&destination);
+            trader.funds -= cost;
+            routes
+                .entry((from.clone(), destination.clone()))
+                .or_default()
+                .profit -= cost;
+            ready_at = sim.time() + travel_time(sim, &map, &from, &destination);
+            trader.location = destination;
+        });
+
+        for market in sim.markets() {
+            for good in market.goods.iter() {
+                prices.push(PriceRow {
+                    day,
+                    market: market.name.clone(),
+                    item: good.item.clone(),
+                    price: good.price(),
+                    stock: good.stock,
+                });
+            }
+        }
+        net_worth.push(NetWorthRow {
+            day,
+            location: trader.location.clone(),
+            funds: trader.funds,
+            net_worth: trader.net_worth(&sim),
+        });
+    }
+
+    if let Err(e) = fs::create_dir_all(&options.out) {
+        eprintln!("failed to create {}: {e}", options.out.display());
+        return ExitCode::FAILURE;
+    }
+
+    let files = match options.format {
+        Format::Csv => {
+            let mut prices_csv = String::from("day,market,item,price,stock\n");
+            for r in prices.iter() {
+                prices_csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    r.day,
+                    csv_field(&r.market),
+                    csv_field(&r.item),
+                    r.price,
+                    r.stock
+                ));
+            }
+
+            let mut net_worth_csv = String::from("day,location,funds,net_worth\n");
+            for r in net_worth.iter() {
+                net_worth_csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    r.day,
+                    csv_field(&r.location),
+                    r.funds,
+                    r.net_worth
+                ));
+            }
+
+            let mut routes_csv = String::from("from,to,trips,units,profit\n");
+            for ((from, to), s) in routes.iter() {
+                routes_csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(from),
+                    csv_field(to),
+                    s.trips,
+                    s.units,
+                    s.profit
+                ));
+            }
+
+            vec![
+                ("prices.csv", prices_csv),
+                ("net_worth.csv", net_worth_csv),
+                ("routes.csv", routes_csv),
+            ]
+        }
+        Format::Json => {
+            let prices_json: Vec<String> = prices
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{{\"day\":{},\"market\":{},\"item\":{},\"price\":{},\"stock\":{}}}",
+                        r.day,
+                        json_string(&r.market),
+                        json_string(&r.item),
+                        r.price,
+                        r.stock
+                    )
+                })
+                .collect();
+            let net_worth_json: Vec<String> = net_worth
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{{\"day\":{},\"location\":{},\"funds\":{},\"net_worth\":{}}}",
+                        r.day,
+                        json_string(&r.location),
+                        r.funds,
+                        r.net_worth
+                    )
+                })
+                .collect();
+            let routes_json: Vec<String> = routes
+                .iter()
+                .map(|((from, to), s)| {
+                    format!(
+                        "{{\"from\":{},\"to\":{},\"trips\":{},\"units\":{},\"profit\":{}}}",
+                        json_string(from),
+                        json_string(to),
+                        s.trips,
+                        s.units,
+                        s.profit
+                    )
+                })
+                .collect();
+
+            let report = format!(
+                "{{\"seed\":{},\"days\":{},\"prices\":[{}],\"net_worth\":[{}],\"routes\":[{}]}}\n",
+                options.seed,
+                options.days,
+                prices_json.join(","),
+                net_worth_json.join(","),
+                routes_json.join(",")
+            );
+            vec![("report.json", report)]
+        }
+    };
+
+    for (name, contents) in files {
+        let path = options.out.join(name);
+        if let Err(e) = fs::write(&path, contents) {
+            eprintln!("failed to write {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    // A trader that multiplies its money every day usually means a loop to fix
+    let final_worth = trader.net_worth(&sim);
+    let daily_growth = if options.days > 0 && options.funds > 0 {
+        (final_worth.max(0) as f64 / options.funds as f64).powf(1. / options.days as f64)
+    } else {
+        1.
+    };
+    eprintln!(
+        "net worth {} -> {} over {} days ({:.1}% per day)",
+        options.funds,
+        final_worth,
+        options.days,
+        (daily_growth - 1.) * 100.
+    );
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,43 @@
+use godot::{
+    classes::{Area2D, IArea2D},
+    prelude::*,
+};
+
+use crate::{player::Player, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct GuardCheckpoint {
+    // Name of the checkpoint in the `World`, the guards search whoever walks by
+    #[export]
+    checkpoint: GString,
+    is_player_near: bool,
+    world_node: Option<Gd<World>>,
+    base: Base<Area2D>,
+}
+
+#[godot_api]
+impl GuardCheckpoint {
+    #[func]
+    fn area2d_entered(&mut self, player_area2d: Gd<Area2D>) {
+        let is_player_near = self.base().overlaps_area(player_area2d);
+
+        if !self.is_player_near && is_player_near {
+            if let Some(mut world_gd) = self.world_node.clone() {
+                world_gd.bind_mut().pass_checkpoint(self.checkpoint.clone());
+            }
+        }
+        self.is_player_near = is_player_near;
+    }
+}
+
+#[godot_api]
+impl IArea2D for GuardCheckpoint {
+    fn ready(&mut self) {
+        let mut player_node = self.base_mut().get_node_as::<Player>("../Player");
+        let area2d_entered_callable = self.base().callable("area2d_entered");
+        player_node.connect("on_area2d_entered".into(), area2d_entered_callable);
+
+        self.world_node = Some(self.base_mut().get_node_as::<World>("../World"));
+    }
+}
@@ -0,0 +1,232 @@
+use super::rng::SimRng;
+
+// How far off a merchant who knows nothing can be, as a share of the value
+const MAX_MISPRICE: f32 = 0.6;
+// How far the true value of a single item strays from the market's fair price
+const VALUE_SPREAD: f32 = 0.15;
+// Chance that a merchant who knows nothing sells a fake, and what one is worth
+const MAX_FAKE_CHANCE: f32 = 0.15;
+const FAKE_VALUE_SHARE: f32 = 0.1;
+// Chance that a merchant who spots a fake keeps it instead of handing it back
+const CONFISCATE_CHANCE: f32 = 0.5;
+// Steps of accuracy a free appraisal has to climb before it may look again
+const EYE_LEVELS: f32 = 10.;
+// The item that lets the player appraise with `Appraiser::LOUPE`
+pub const LOUPE_ITEM: &str = "Loupe";
+
+/// What a merchant does when offered a fake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FakeVerdict {
+    Unnoticed,
+    Refused,
+    Confiscated,
+}
+
+/// How well an appraisal method narrows the estimate: 1 reveals the true
+/// value, 0 teaches nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Appraiser {
+    pub accuracy: f32,
+    pub fee: u32,
+}
+
+impl Appraiser {
+    pub const fn skill(level: f32) -> Self {
+        Self {
+            accuracy: level,
+            fee: 0,
+        }
+    }
+
+    pub const LOUPE: Appraiser = Appraiser {
+        accuracy: 0.55,
+        fee: 0,
+    };
+
+    pub const PAID: Appraiser = Appraiser {
+        accuracy: 0.85,
+        fee: 20,
+    };
+
+    // Free appraisals cost nothing but can't be repeated on an item until
+    // the eye got sharper. The level this one looks at, None for paid ones
+    pub fn eye_level(&self) -> Option<u32> {
+        (self.fee == 0).then(|| (self.accuracy * EYE_LEVELS).floor() as u32 + 1)
+    }
+}
+
+/// What the player believes an item is worth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    pub low: u32,
+    pub high: u32,
+}
+
+impl Estimate {
+    // Before any appraisal the player only has the asking price to go on
+    pub fn unknown(price: u32) -> Self {
+        Self {
+            low: price / 4,
+            high: price.max(1) * 3,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.low == self.high
+    }
+
+    // Shrinks the range around the true value, the true value lands at a
+    // random spot inside the new range so its middle gives nothing away
+    pub fn narrow(self, true_value: u32, appraiser: Appraiser, rng: &mut SimRng) -> Self {
+        let low = self.low.min(true_value);
+        let high = self.high.max(true_value);
+
+        let width = ((high - low) as f32 * (1. - appraiser.accuracy.clamp(0., 1.))) as u32;
+        let below = rng.range(0, width);
+
+        Self {
+            low: true_value.saturating_sub(below).max(low),
+            high: (true_value + (width - below)).min(high),
+        }
+    }
+
+    // Once a fake is revealed there is nothing left to guess about its value
+    pub fn reveal(true_value: u32) -> Self {
+        Self {
+            low: true_value,
+            high: true_value,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        if self.is_exact() {
+            format!("Worth {}", self.low)
+        } else {
+            format!("Worth {} - {}", self.low, self.high)
+        }
+    }
+}
+
+// A price factor for a merchant who only roughly knows what things are worth
+pub fn misprice_factor(knowledge: f32, rng: &mut SimRng) -> f32 {
+    1. + rng.spread((1. - knowledge.clamp(0., 1.)) * MAX_MISPRICE)
+}
+
+// The hidden value of one unit bought at a market whose fair price is `fair_price`
+pub fn roll_true_value(fair_price: u32, rng: &mut SimRng) -> u32 {
+    ((fair_price as f32 * (1. + rng.spread(VALUE_SPREAD))).round() as u32).max(1)
+}
+
+// What a merchant offers for an item, a knowing merchant pays for what it
+// really is while others go by their own shelf price
+pub fn merchant_offer(shelf_price: u32, true_value: u32, knowledge: f32) -> u32 {
+    let knowledge = knowledge.clamp(0., 1.);
+    let offer = true_value as f32 * knowledge + shelf_price as f32 * (1. - knowledge);
+    (offer.round() as u32).max(1)
+}
+
+// Whether a merchant slips a fake onto the shelf, careless ones do it more
+pub fn roll_fake(knowledge: f32, rng: &mut SimRng) -> bool {
+    rng.chance((1. - knowledge.clamp(0., 1.)) * MAX_FAKE_CHANCE)
+}
+
+pub fn fake_value(true_value: u32) -> u32 {
+    ((true_value as f32 * FAKE_VALUE_SHARE).round() as u32).max(1)
+}
+
+// What a genuine and a fake unit are each worth in a stack whose units are
+// worth `average` between them, fakes at their usual share of the real thing
+pub fn split_value(average: u32, units: i64, fake_units: i64) -> (u32, u32) {
+    let fake_units = fake_units.clamp(0, units.max(0));
+    if units <= 0 || fake_units == 0 {
+        return (average, fake_value(average));
+    }
+    let weight = (units - fake_units) as f32 + fake_units as f32 * FAKE_VALUE_SHARE;
+    let genuine = ((average as f32 * units as f32 / weight).round() as u32).max(1);
+    (genuine, fake_value(genuine))
+}
+
+// What a unit of `units` is worth on average when `fake_units` of them are
+// fakes of a thing worth `genuine`, the other way around from `split_value`
+pub fn mixed_value(genuine: u32, units: i64, fake_units: i64) -> u32 {
+    let fake_units = fake_units.clamp(0, units.max(0));
+    if units <= 0 || fake_units == 0 {
+        return genuine;
+    }
+    let total = genuine as i64 * (units - fake_units) + fake_value(genuine) as i64 * fake_units;
+    (total as f32 / units as f32).round() as u32
+}
+
+// Whether an appraisal exposes a fake, better appraisers catch more
+pub fn detects_fake(appraiser: Appraiser, rng: &mut SimRng) -> bool {
+    rng.chance(appraiser.accuracy.clamp(0., 1.))
+}
+
+pub fn judge_fake(knowledge: f32, rng: &mut SimRng) -> FakeVerdict {
+    if !rng.chance(knowledge.clamp(0., 1.)) {
+        FakeVerdict::Unnoticed
+    } else if rng.chance(CONFISCATE_CHANCE) {
+        FakeVerdict::Confiscated
+    } else {
+        FakeVerdict::Refused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_mixed_stack_splits_into_genuine_and_fake_values() {
+        // 6 genuine units at 100 and 4 fakes at 10 average 64
+        assert_eq!(split_value(64, 10, 4), (100, 10));
+        assert_eq!(split_value(80, 5, 0), (80, 8));
+        assert_eq!(split_value(10, 3, 3), (100, 10));
+        assert_eq!(mixed_value(100, 10, 4), 64);
+        assert_eq!(mixed_value(100, 2, 0), 100);
+    }
+
+    #[test]
+    fn every_appraisal_narrows_around_the_true_value() {
+        let mut rng = SimRng::new(7);
+        for true_value in [5, 60, 340] {
+            for appraiser in [Appraiser::skill(0.2), Appraiser::LOUPE, Appraiser::PAID] {
+                let mut estimate = Estimate::unknown(100);
+                for _ in 0..5 {
+                    let narrowed = estimate.narrow(true_value, appraiser, &mut rng);
+                    assert!((narrowed.low..=narrowed.high).contains(&true_value));
+                    assert!(
+                        narrowed.high - narrowed.low
+                            <= estimate.high.max(true_value) - estimate.low.min(true_value)
+                    );
+                    estimate = narrowed;
+                }
+            }
+        }
+        let exact = Estimate::unknown(100).narrow(60, Appraiser::skill(1.), &mut rng);
+        assert_eq!(exact, Estimate::reveal(60));
+        assert_eq!(exact.label(), "Worth 60");
+    }
+
+    #[test]
+    fn free_looks_wait_for_a_sharper_eye() {
+        assert_eq!(Appraiser::skill(0.).eye_level(), Some(1));
+        assert_eq!(Appraiser::skill(0.35).eye_level(), Some(4));
+        assert_eq!(Appraiser::LOUPE.eye_level(), Some(6));
+        assert_eq!(Appraiser::PAID.eye_level(), None);
+    }
+
+    #[test]
+    fn only_careless_merchants_mistake_or_miss_fakes() {
+        let mut rng = SimRng::new(3);
+        assert_eq!(merchant_offer(80, 100, 1.), 100);
+        assert_eq!(merchant_offer(80, 100, 0.), 80);
+        assert_eq!(merchant_offer(80, 100, 0.5), 90);
+        for _ in 0..200 {
+            assert!(!roll_fake(1., &mut rng));
+            assert_eq!(judge_fake(0., &mut rng), FakeVerdict::Unnoticed);
+            assert_ne!(judge_fake(1., &mut rng), FakeVerdict::Unnoticed);
+            assert_eq!(misprice_factor(1., &mut rng), 1.);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use std::fmt;
+
+use super::{
+    appraisal::Estimate,
+    clock::GameTime,
+    freshness::{self, FRESH},
+    rng::SimRng,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::{MarketSimulation, TICK},
+};
+
+// Each bid must beat the last one by this share (at least one coin)
+const BID_INCREMENT: f32 = 0.05;
+// Chance per hour that an interested bidder looks at a lot
+const BID_CHANCE: f32 = 0.3;
+// Chance per hour that a bidder puts something of their own up for sale
+const NPC_LISTING_CHANCE: f32 = 0.05;
+const NPC_LISTING_HOURS: u64 = 36;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuctionError {
+    UnknownLot,
+    Closed,
+    BidTooLow,
+    OwnLot,
+}
+
+impl fmt::Display for AuctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuctionError::UnknownLot => write!(f, "no such lot"),
+            AuctionError::Closed => write!(f, "bidding on this lot has closed"),
+            AuctionError::BidTooLow => write!(f, "bid is below the minimum"),
+            AuctionError::OwnLot => write!(f, "can't bid on your own lot"),
+        }
+    }
+}
+
+impl std::error::Error for AuctionError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Party {
+    Player,
+    Npc(String),
+}
+
+impl Party {
+    fn to_field(&self) -> String {
+        match self {
+            Party::Player => "@player".to_string(),
+            Party::Npc(name) => name.clone(),
+        }
+    }
+
+    fn from_field(value: &str) -> Party {
+        match value {
+            "@player" => Party::Player,
+            name => Party::Npc(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Party {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Party::Player => write!(f, "you"),
+            Party::Npc(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bid {
+    pub bidder: Party,
+    pub amount: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lot {
+    pub id: u32,
+    pub item: String,
+    pub quantity: u32,
+    pub seller: Party,
+    // Hidden from bidders, the lot only sells when the best bid reaches it
+    pub reserve: u32,
+    pub ends_at: GameTime,
+    pub best_bid: Option<Bid>,
+    // Bidders pay less for goods that are going off, and they keep going off
+    // while the lot is up
+    pub freshness: f32,
+    pub shelf_life: GameTime,
+    // What the seller's copy really is, handed over with the goods. A true
+    // value of 0 means exactly its price
+    pub true_value: u32,
+    pub estimate: Option<Estimate>,
+    pub fake_units: u32,
+    pub known_fake: bool,
+    // What the seller paid for one unit, for when the goods come back unsold
+    pub unit_cost: u32,
+}
+
+impl Lot {
+    pub fn min_bid(&self) -> u32 {
+        match &self.best_bid {
+            Some(bid) => bid.amount + ((bid.amount as f32 * BID_INCREMENT) as u32).max(1),
+            None => 1,
+        }
+    }
+}
+
+/// An AI bidder, `interests` maps item names to how many times the market
+/// price they are willing to pay.
+#[derive(Debug, Clone)]
+pub struct AiBidder {
+    pub name: String,
+    pub budget: u32,
+    pub daily_income: u32,
+    pub interests: Vec<(String, f32)>,
+}
+
+impl AiBidder {
+    pub fn new(name: &str, budget: u32, daily_income: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            budget,
+            daily_income,
+            interests: vec![],
+        }
+    }
+
+    pub fn with_interest(mut self, item: &str, appetite: f32) -> Self {
+        self.interests.push((item.to_string(), appetite));
+        self
+    }
+
+    pub fn appetite(&self, item: &str) -> f32 {
+        self.interests
+            .iter()
+            .find(|(i, _)| i == item)
+            .map_or(0., |(_, a)| *a)
+    }
+}
+
+/// Money or goods that change hands. Funds taken from a bidder when they bid
+/// are returned with `Refund` when they are outbid.
+#[derive(Debug, Clone)]
+pub enum AuctionEvent {
+    Refund {
+        lot: u32,
+        bidder: Party,
+        amount: u32,
+    },
+    Sold {
+        lot: Lot,
+        buyer: Party,
+        price: u32,
+    },
+    Unsold {
+        lot: Lot,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct AuctionHouse {
+    rng: SimRng,
+    next_id: u32,
+    lots: Vec<Lot>,
+    bidders: Vec<AiBidder>,
+}
+
+impl AuctionHouse {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimRng::new(seed),
+            next_id: 0,
+            lots: vec![],
+            bidders: vec![],
+        }
+    }
+
+    pub fn with_bidder(mut self, bidder: AiBidder) -> Self {
+        self.bidders.push(bidder);
+        self
+    }
+
+    pub fn lots(&self) -> &[Lot] {
+        &self.lots
+    }
+
+    pub fn bidders(&self) -> &[AiBidder] {
+        &self.bidders
+    }
+
+    pub fn list(
+        &mut self,
+        item: &str,
+        quantity: u32,
+        seller: Party,
+        reserve: u32,
+        ends_at: GameTime,
+    ) -> u32 {
+        self.next_id += 1;
+        self.lots.push(Lot {
+            id: self.next_id,
+            item: item.to_string(),
+            quantity,
+            seller,
+            reserve,
+            ends_at,
+            best_bid: None,
+            freshness: FRESH,
+            shelf_life: GameTime::ZERO,
+            true_value: 0,
+            estimate: None,
+            fake_units: 0,
+            known_fake: false,
+            unit_cost: 0,
+        });
+        self.next_id
+    }
+
+    pub fn lot_mut(&mut self, id: u32) -> Option<&mut Lot> {
+        self.lots.iter_mut().find(|l| l.id == id)
+    }
+
+    // Places a bid, the caller takes `amount` from the bidder right away
+    pub fn bid(
+        &mut self,
+        id: u32,
+        bidder: Party,
+        amount: u32,
+        now: GameTime,
+    ) -> Result<Vec<AuctionEvent>, AuctionError> {
+        let lot = self
+            .lots
+            .iter_mut()
+            .find(|l| l.id == id)
+            .ok_or(AuctionError::UnknownLot)?;
+        if now >= lot.ends_at {
+            return Err(AuctionError::Closed);
+        }
+        if lot.seller == bidder {
+            return Err(AuctionError::OwnLot);
+        }
+        if amount < lot.min_bid() {
+            return Err(AuctionError::BidTooLow);
+        }
+
+        let mut events = vec![];
+        if let Some(previous) = lot.best_bid.replace(Bid { bidder, amount }) {
+            if let Party::Npc(name) = &previous.bidder {
+                if let Some(b) = self.bidders.iter_mut().find(|b| b.name == *name) {
+                    b.budget += previous.amount;
+                }
+            }
+            events.push(AuctionEvent::Refund {
+                lot: id,
+                bidder: previous.bidder,
+                amount: previous.amount,
+            });
+        }
+
+        Ok(events)
+    }
+
+    // Lets the AI bidders act for one hour and settles the lots that ended
+    pub fn tick(&mut self, now: GameTime, sim: &MarketSimulation) -> Vec<AuctionEvent> {
+        let mut events = vec![];
+
+        for lot in self.lots.iter_mut() {
+            lot.freshness = freshness::decay(lot.freshness, lot.shelf_life, TICK, 1.);
+        }
+
+        if now.hour_of_day() == 0 {
+            for bidder in self.bidders.iter_mut() {
+                bidder.budget += bidder.daily_income;
+            }
+        }
+
+        for i in 0..self.bidders.len() {
+            self.npc_bids(i, now, sim, &mut events);
+            self.npc_listing(i, now, sim);
+        }
+
+        let (ended, open): (Vec<Lot>, Vec<Lot>) =
+            self.lots.drain(..).partition(|l| now >= l.ends_at);
+        self.lots = open;
+
+        for lot in ended {
+            match lot.best_bid.clone() {
+                Some(bid) if bid.amount >= lot.reserve => events.push(AuctionEvent::Sold {
+                    buyer: bid.bidder,
+                    price: bid.amount,
+                    lot,
+                }),
+                Some(bid) => {
+                    if let Party::Npc(name) = &bid.bidder {
+                        if let Some(b) = self.bidders.iter_mut().find(|b| b.name == *name) {
+                            b.budget += bid.amount;
+                        }
+                    }
+                    events.push(AuctionEvent::Refund {
+                        lot: lot.id,
+                        bidder: bid.bidder,
+                        amount: bid.amount,
+                    });
+                    events.push(AuctionEvent::Unsold { lot });
+                }
+                None => events.push(AuctionEvent::Unsold { lot }),
+            }
+        }
+
+        events
+    }
+
+    fn npc_bids(
+        &mut self,
+        bidder: usize,
+        now: GameTime,
+        sim: &MarketSimulation,
+        events: &mut Vec<AuctionEvent>,
+    ) {
+        let party = Party::Npc(self.bidders[bidder].name.clone());
+
+        let mut bids = vec![];
+        for lot in self.lots.iter() {
+            let is_leading = lot.best_bid.as_ref().is_some_and(|b| b.bidder == party);
+            if lot.seller == party || is_leading || now >= lot.ends_at {
+                continue;
+            }
+
+            let appetite = self.bidders[bidder].appetite(&lot.item);
+            let unit = (market_price(sim, &lot.item) as f32 * appetite) as u32;
+            let valuation = freshness::price(unit, lot.freshness) * lot.quantity;
+            let min_bid = lot.min_bid();
+            if valuation < min_bid || !self.rng.chance(BID_CHANCE) {
+                continue;
+            }
+
+            // Jump a little above the minimum so bidding wars end
+            let jump = self.rng.range(0, (valuation - min_bid) / 4);
+            bids.push((lot.id, min_bid + jump));
+        }
+
+        for (id, amount) in bids {
+            if self.bidders[bidder].budget < amount {
+                continue;
+            }
+            if let Ok(mut refunds) = self.bid(id, party.clone(), amount, now) {
+                self.bidders[bidder].budget -= amount;
+                events.append(&mut refunds);
+            }
+        }
+    }
+
+    fn npc_listing(&mut self, bidder: usize, now: GameTime, sim: &MarketSimulation) {
+        if !self.rng.chance(NPC_LISTING_CHANCE) {
+            return;
+        }
+        let goods: Vec<_> = sim
+            .markets()
+            .iter()
+            .filter(|m| !m.black_market)
+            .flat_map(|m| m.goods.iter())
+            .collect();
+        if goods.is_empty() {
+            return;
+        }
+
+        let good = goods[self.rng.range(0, goods.len() as u32 - 1) as usize];
+        let quantity = self.rng.range(1, (good.max_stock / 4).max(1));
+        let reserve = good.price() * quantity * 3 / 4;
+        let ends_at = now + GameTime::from_hours(NPC_LISTING_HOURS);
+        let seller = Party::Npc(self.bidders[bidder].name.clone());
+
+        self.list(&good.item.clone(), quantity, seller, reserve, ends_at);
+    }
+}
+
+// Cheapest price the item is sold for in any market
+pub fn market_price(sim: &MarketSimulation, item: &str) -> u32 {
+    sim.markets()
+        .iter()
+        .filter_map(|m| m.good(item))
+        .map(|g| g.price())
+        .min()
+        .unwrap_or(0)
+}
+
+// Day and hour an auction ending at `time` closes, for labels
+pub fn closing_label(time: GameTime) -> String {
+    format!("day {} {:02}:00", time.day(), time.hour_of_day())
+}
+
+impl Persist for AuctionHouse {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("auction", &[&self.rng.state(), &self.next_id]);
+        for b in self.bidders.iter() {
+            writer.record("auction_bidder", &[&b.name, &b.budget]);
+        }
+        for l in self.lots.iter() {
+            let (bidder, amount) = match &l.best_bid {
+                Some(bid) => (bid.bidder.to_field(), bid.amount),
+                None => (String::new(), 0),
+            };
+            let (low, high) = l.estimate.map_or((0, 0), |e| (e.low, e.high));
+            writer.record(
+                "auction_lot",
+                &[
+                    &l.id,
+                    &l.item,
+                    &l.quantity,
+                    &l.seller.to_field(),
+                    &l.reserve,
+                    &l.ends_at.minutes(),
+                    &bidder,
+                    &amount,
+                    &l.freshness,
+                    &l.true_value,
+                    &low,
+                    &high,
+                    &l.fake_units,
+                    &l.known_fake,
+                    &l.unit_cost,
+                    &l.shelf_life.minutes(),
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "auction" => {
+                self.rng = SimRng::new(record.get(0)?);
+                self.next_id = record.get(1)?;
+                self.lots.clear();
+            }
+            // Bidders come from the scenario, only their budgets are saved
+            "auction_bidder" => {
+                let name = record.str(0)?;
+                if let Some(b) = self.bidders.iter_mut().find(|b| b.name == name) {
+                    b.budget = record.get(1)?;
+                }
+            }
+            "auction_lot" => {
+                let bidder = record.str(6)?;
+                self.lots.push(Lot {
+                    id: record.get(0)?,
+                    item: record.str(1)?.to_string(),
+                    quantity: record.get(2)?,
+                    seller: Party::from_field(record.str(3)?),
+                    reserve: record.get(4)?,
+                    ends_at: GameTime::from_minutes(record.get(5)?),
+                    best_bid: (!bidder.is_empty()).then(|| Bid {
+                        bidder: Party::from_field(bidder),
+                        amount: record.get(7).unwrap_or(0),
+                    }),
+                    freshness: record.get(8)?,
+                    true_value: record.get(9)?,
+                    estimate: match (record.get(10)?, record.get(11)?) {
+                        (_, 0) => None,
+                        (low, high) => Some(Estimate { low, high }),
+                    },
+                    fake_units: record.get(12)?,
+                    known_fake: record.get(13)?,
+                    unit_cost: record.get(14)?,
+                    shelf_life: GameTime::from_minutes(record.get(15)?),
+                });
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    // Lets the house run for `hours`, with everything that happened written
+    // out for comparing
+    fn run(house: &mut AuctionHouse, sim: &mut MarketSimulation, hours: u64) -> Vec<String> {
+        let mut log = vec![];
+        for _ in 0..hours {
+            sim.advance(TICK);
+            for event in house.tick(sim.time(), sim) {
+                log.push(format!("{event:?}"));
+            }
+        }
+        log
+    }
+
+    // With a few of the player's cups up for a day and a bit
+    fn house_with_cups(seed: u64) -> (AuctionHouse, MarketSimulation) {
+        let mut house = scenario::default_auction_house(seed);
+        let sim = scenario::default_world(seed);
+        let ends_at = sim.time() + GameTime::from_hours(30);
+        house.list("Silver Cup", 3, Party::Player, 100, ends_at);
+        (house, sim)
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_auctions() {
+        let (mut a, mut sim_a) = house_with_cups(11);
+        let (mut b, mut sim_b) = house_with_cups(11);
+        for _ in 0..4 {
+            assert_eq!(run(&mut a, &mut sim_a, 18), run(&mut b, &mut sim_b, 18));
+            assert_eq!(format!("{:?}", a.lots()), format!("{:?}", b.lots()));
+        }
+        let budgets = |h: &AuctionHouse| h.bidders().iter().map(|b| b.budget).collect::<Vec<_>>();
+        assert_eq!(budgets(&a), budgets(&b));
+    }
+
+    #[test]
+    fn a_loaded_auction_settles_like_the_saved_one() {
+        let (mut a, mut sim_a) = house_with_cups(4);
+        let mut sim_b = scenario::default_world(4);
+        run(&mut a, &mut sim_a, 10);
+        sim_b.advance(GameTime::from_hours(10));
+        let now = sim_a.time();
+        let tea = Party::Npc("Old Basho".to_string());
+        let id = a.list("Tea Leaf", 5, tea, 40, now + GameTime::from_hours(12));
+        a.bid(id, Party::Player, 45, now).unwrap();
+
+        let mut writer = SaveWriter::new();
+        a.save(&mut writer);
+        // A different seed, the saved rolls must take over
+        let mut b = scenario::default_auction_house(99);
+        for record in save::parse(&writer.finish()) {
+            assert!(b.load(&record).unwrap());
+        }
+        assert_eq!(format!("{:?}", a.lots()), format!("{:?}", b.lots()));
+
+        let settled = run(&mut a, &mut sim_a, 30);
+        assert_eq!(settled, run(&mut b, &mut sim_b, 30));
+        assert!(settled.iter().any(|e| e.starts_with("Sold")));
+        assert!(a.lots().iter().all(|l| l.id != 1 && l.id != id));
+    }
+}
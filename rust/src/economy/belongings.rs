@@ -0,0 +1,134 @@
+use super::{
+    appraisal::Estimate,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Place {
+    Bags,
+    KeyItems,
+    Stash,
+}
+
+impl Place {
+    pub const ALL: [Place; 3] = [Place::Bags, Place::KeyItems, Place::Stash];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Place::Bags => "Bags",
+            Place::KeyItems => "KeyItems",
+            Place::Stash => "Stash",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+/// One stack the player holds, with everything that sets it apart from a
+/// fresh copy out of the catalogue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedStack {
+    pub place: Place,
+    pub item: String,
+    pub stacks: i64,
+    pub true_value: u32,
+    pub estimate: Option<Estimate>,
+    pub appraised_level: u32,
+    pub unit_cost: u32,
+    pub fake_units: i64,
+    pub known_fake: bool,
+    pub freshness: f32,
+    pub quest: bool,
+    pub bound: bool,
+    pub unsellable: bool,
+    pub undroppable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerState {
+    pub funds: i64,
+    pub appraisal_skill: f32,
+    // Of the rolls for what the player does, see `World`
+    pub rng: u64,
+}
+
+/// What the player owns, in bags, among the key items and in the stash.
+/// Saves from before it was kept have no player record.
+#[derive(Debug, Clone, Default)]
+pub struct Belongings {
+    pub player: Option<PlayerState>,
+    pub stacks: Vec<SavedStack>,
+}
+
+impl Belongings {
+    pub fn in_place(&self, place: Place) -> impl Iterator<Item = &SavedStack> {
+        self.stacks.iter().filter(move |s| s.place == place)
+    }
+}
+
+impl Persist for Belongings {
+    fn save(&self, writer: &mut SaveWriter) {
+        if let Some(p) = self.player {
+            writer.record("player", &[&p.funds, &p.appraisal_skill, &p.rng]);
+        }
+        for s in self.stacks.iter() {
+            let (low, high) = s.estimate.map_or((0, 0), |e| (e.low, e.high));
+            writer.record(
+                "stack",
+                &[
+                    &s.place.name(),
+                    &s.item,
+                    &s.stacks,
+                    &s.true_value,
+                    &low,
+                    &high,
+                    &s.appraised_level,
+                    &s.unit_cost,
+                    &s.fake_units,
+                    &s.known_fake,
+                    &s.freshness,
+                    &s.quest,
+                    &s.bound,
+                    &s.unsellable,
+                    &s.undroppable,
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "player" => {
+                self.player = Some(PlayerState {
+                    funds: record.get(0)?,
+                    appraisal_skill: record.get(1)?,
+                    rng: record.get(2)?,
+                });
+            }
+            "stack" => self.stacks.push(SavedStack {
+                place: Place::from_name(record.str(0)?)
+                    .ok_or_else(|| record.error("unknown place"))?,
+                item: record.str(1)?.to_string(),
+                stacks: record.get(2)?,
+                true_value: record.get(3)?,
+                estimate: match (record.get(4)?, record.get(5)?) {
+                    (_, 0) => None,
+                    (low, high) => Some(Estimate { low, high }),
+                },
+                appraised_level: record.get(6)?,
+                unit_cost: record.get(7)?,
+                fake_units: record.get(8)?,
+                known_fake: record.get(9)?,
+                freshness: record.get(10)?,
+                quest: record.get(11)?,
+                bound: record.get(12)?,
+                unsellable: record.get(13)?,
+                undroppable: record.get(14)?,
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use super::{
+    appraisal::Estimate,
+    clock::GameTime,
+    freshness,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+// Sales every merchant remembers, the oldest is forgotten first
+pub const BUYBACK_SLOTS: usize = 5;
+// How long the player has to change their mind
+pub const BUYBACK_TIME: GameTime = GameTime::from_hours(24);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuybackError {
+    UnknownItem,
+    Expired,
+}
+
+impl fmt::Display for BuybackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuybackError::UnknownItem => write!(f, "the merchant doesn't have it anymore"),
+            BuybackError::Expired => write!(f, "too late to buy it back"),
+        }
+    }
+}
+
+impl std::error::Error for BuybackError {}
+
+/// Something the player sold, kept aside as it was so buying it back
+/// returns the very same goods. `price` is what the merchant paid.
+#[derive(Debug, Clone)]
+pub struct SoldItem {
+    pub id: u32,
+    pub market: String,
+    pub item: String,
+    pub quantity: u32,
+    pub price: u32,
+    pub true_value: u32,
+    pub estimate: Estimate,
+    pub fake_units: u32,
+    pub known_fake: bool,
+    // Still spoiling while the merchant holds on to it
+    pub freshness: f32,
+    pub shelf_life: GameTime,
+    pub expires_at: GameTime,
+}
+
+impl fmt::Display for SoldItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} x{} for {} until day {} {:02}:00",
+            self.item,
+            self.quantity,
+            self.price,
+            self.expires_at.day(),
+            self.expires_at.hour_of_day()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BuybackList {
+    next_id: u32,
+    items: Vec<SoldItem>,
+}
+
+impl BuybackList {
+    // Newest sale last
+    pub fn in_market<'a>(&'a self, market: &'a str) -> impl Iterator<Item = &'a SoldItem> {
+        self.items.iter().filter(move |s| s.market == market)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&SoldItem> {
+        self.items.iter().find(|s| s.id == id)
+    }
+
+    // Remembers a sale until `sold_at + BUYBACK_TIME`, returns its id.
+    // `id` and `expires_at` are filled in here
+    pub fn record(&mut self, mut sold: SoldItem, sold_at: GameTime) -> u32 {
+        sold.id = self.next_id;
+        sold.expires_at = sold_at + BUYBACK_TIME;
+        self.next_id += 1;
+
+        if self.in_market(&sold.market).count() >= BUYBACK_SLOTS {
+            if let Some(oldest) = self.items.iter().position(|s| s.market == sold.market) {
+                self.items.remove(oldest);
+            }
+        }
+        self.items.push(sold);
+        self.next_id - 1
+    }
+
+    // Hands the goods back, the caller charges the price
+    pub fn take(&mut self, id: u32, now: GameTime) -> Result<SoldItem, BuybackError> {
+        let index = self
+            .items
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or(BuybackError::UnknownItem)?;
+        if self.items[index].expires_at <= now {
+            self.items.remove(index);
+            return Err(BuybackError::Expired);
+        }
+        Ok(self.items.remove(index))
+    }
+
+    pub fn age(&mut self, elapsed: GameTime) {
+        for s in self.items.iter_mut() {
+            s.freshness = freshness::decay(s.freshness, s.shelf_life, elapsed, 1.);
+        }
+    }
+
+    pub fn expire(&mut self, now: GameTime) {
+        self.items.retain(|s| s.expires_at > now);
+    }
+}
+
+impl Persist for BuybackList {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("buyback_next_id", &[&self.next_id]);
+        for s in self.items.iter() {
+            writer.record(
+                "buyback",
+                &[
+                    &s.id,
+                    &s.market,
+                    &s.item,
+                    &s.quantity,
+                    &s.price,
+                    &s.true_value,
+                    &s.estimate.low,
+                    &s.estimate.high,
+                    &s.fake_units,
+                    &s.known_fake,
+                    &s.freshness,
+                    &s.expires_at.minutes(),
+                    &s.shelf_life.minutes(),
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "buyback_next_id" => self.next_id = record.get(0)?,
+            "buyback" => self.items.push(SoldItem {
+                id: record.get(0)?,
+                market: record.str(1)?.to_string(),
+                item: record.str(2)?.to_string(),
+                quantity: record.get(3)?,
+                price: record.get(4)?,
+                true_value: record.get(5)?,
+                estimate: Estimate {
+                    low: record.get(6)?,
+                    high: record.get(7)?,
+                },
+                fake_units: record.get(8)?,
+                known_fake: record.get(9)?,
+                freshness: record.get(10)?,
+                expires_at: GameTime::from_minutes(record.get(11)?),
+                shelf_life: GameTime::from_minutes(record.get(12)?),
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,72 @@
+use std::ops::{Add, AddAssign, Sub};
+
+pub const MINUTES_PER_HOUR: u64 = 60;
+pub const HOURS_PER_DAY: u64 = 24;
+pub const MINUTES_PER_DAY: u64 = MINUTES_PER_HOUR * HOURS_PER_DAY;
+
+/// In-game time, counted in minutes since the start of the game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameTime(u64);
+
+impl GameTime {
+    pub const ZERO: GameTime = GameTime(0);
+
+    pub const fn from_minutes(minutes: u64) -> Self {
+        Self(minutes)
+    }
+
+    pub const fn from_hours(hours: u64) -> Self {
+        Self(hours * MINUTES_PER_HOUR)
+    }
+
+    pub const fn from_days(days: u64) -> Self {
+        Self(days * MINUTES_PER_DAY)
+    }
+
+    pub const fn minutes(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn hours(&self) -> u64 {
+        self.0 / MINUTES_PER_HOUR
+    }
+
+    pub const fn days(&self) -> u64 {
+        self.0 / MINUTES_PER_DAY
+    }
+
+    // Calendar day, the first day of the game is day 1
+    pub const fn day(&self) -> u64 {
+        self.days() + 1
+    }
+
+    pub const fn hour_of_day(&self) -> u64 {
+        self.hours() % HOURS_PER_DAY
+    }
+
+    pub const fn saturating_sub(self, other: GameTime) -> GameTime {
+        GameTime(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Add for GameTime {
+    type Output = GameTime;
+
+    fn add(self, other: GameTime) -> GameTime {
+        GameTime(self.0 + other.0)
+    }
+}
+
+impl AddAssign for GameTime {
+    fn add_assign(&mut self, other: GameTime) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for GameTime {
+    type Output = GameTime;
+
+    fn sub(self, other: GameTime) -> GameTime {
+        GameTime(self.0 - other.0)
+    }
+}
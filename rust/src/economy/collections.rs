@@ -0,0 +1,267 @@
+use std::fmt;
+
+use super::{
+    auction,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::MarketSimulation,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollectionError {
+    UnknownCollector,
+    NotInterested,
+    Incomplete,
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionError::UnknownCollector => write!(f, "there is no such collector"),
+            CollectionError::NotInterested => write!(f, "the collector isn't after that set"),
+            CollectionError::Incomplete => write!(f, "the set isn't complete"),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {}
+
+/// Items that are worth more together than apart.
+#[derive(Debug, Clone)]
+pub struct ItemSet {
+    pub name: String,
+    pub pieces: Vec<String>,
+}
+
+impl ItemSet {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pieces: vec![],
+        }
+    }
+
+    pub fn with_piece(mut self, item: &str) -> Self {
+        self.pieces.push(item.to_string());
+        self
+    }
+
+    pub fn has_piece(&self, item: &str) -> bool {
+        self.pieces.iter().any(|p| p == item)
+    }
+
+    // Pieces of the set among what the player `has`
+    pub fn held(&self, have: impl Fn(&str) -> u32) -> usize {
+        self.pieces.iter().filter(|p| have(p) > 0).count()
+    }
+
+    pub fn is_complete(&self, have: impl Fn(&str) -> u32) -> bool {
+        self.held(have) == self.pieces.len()
+    }
+
+    // What the pieces fetch one by one at the cheapest market
+    pub fn value(&self, sim: &MarketSimulation) -> u32 {
+        self.pieces
+            .iter()
+            .map(|p| auction::market_price(sim, p))
+            .sum()
+    }
+}
+
+/// Someone in a town who pays a `premium` on top of the pieces' value for
+/// a complete set.
+#[derive(Debug, Clone)]
+pub struct Collector {
+    pub name: String,
+    pub town: String,
+    pub wants: Vec<(String, f32)>,
+}
+
+impl Collector {
+    pub fn new(name: &str, town: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            town: town.to_string(),
+            wants: vec![],
+        }
+    }
+
+    pub fn with_interest(mut self, set: &str, premium: f32) -> Self {
+        self.wants.push((set.to_string(), premium));
+        self
+    }
+
+    pub fn premium(&self, set: &str) -> Option<f32> {
+        self.wants
+            .iter()
+            .find(|(s, _)| s == set)
+            .map(|(_, premium)| *premium)
+    }
+}
+
+/// Every set and collector, and the pieces the player has come across.
+#[derive(Debug, Clone, Default)]
+pub struct Collections {
+    sets: Vec<ItemSet>,
+    collectors: Vec<Collector>,
+    discovered: Vec<String>,
+}
+
+impl Collections {
+    pub fn with_set(mut self, set: ItemSet) -> Self {
+        self.sets.push(set);
+        self
+    }
+
+    pub fn with_collector(mut self, collector: Collector) -> Self {
+        self.collectors.push(collector);
+        self
+    }
+
+    pub fn sets(&self) -> &[ItemSet] {
+        &self.sets
+    }
+
+    pub fn set(&self, name: &str) -> Option<&ItemSet> {
+        self.sets.iter().find(|s| s.name == name)
+    }
+
+    pub fn collectors(&self) -> &[Collector] {
+        &self.collectors
+    }
+
+    pub fn collector(&self, name: &str) -> Option<&Collector> {
+        self.collectors.iter().find(|c| c.name == name)
+    }
+
+    pub fn is_discovered(&self, item: &str) -> bool {
+        self.discovered.iter().any(|d| d == item)
+    }
+
+    // Logs the set pieces among `items`, returns the ones seen for the first
+    // time along with their set
+    pub fn discover<'a>(&mut self, items: impl Iterator<Item = &'a str>) -> Vec<(String, String)> {
+        let mut found = vec![];
+        for item in items {
+            if self.is_discovered(item) {
+                continue;
+            }
+            if let Some(set) = self.sets.iter().find(|s| s.has_piece(item)) {
+                found.push((item.to_string(), set.name.clone()));
+                self.discovered.push(item.to_string());
+            }
+        }
+        found
+    }
+
+    // What the collector pays for the whole set
+    pub fn offer(
+        &self,
+        collector: &str,
+        set: &str,
+        sim: &MarketSimulation,
+        have: impl Fn(&str) -> u32,
+    ) -> Result<u32, CollectionError> {
+        let collector = self
+            .collector(collector)
+            .ok_or(CollectionError::UnknownCollector)?;
+        let premium = collector
+            .premium(set)
+            .ok_or(CollectionError::NotInterested)?;
+        let set = self.set(set).ok_or(CollectionError::NotInterested)?;
+        if !set.is_complete(have) {
+            return Err(CollectionError::Incomplete);
+        }
+        Ok((set.value(sim) as f32 * premium).round() as u32)
+    }
+}
+
+// Sets and collectors come from the scenario, the save only keeps the log
+impl Persist for Collections {
+    fn save(&self, writer: &mut SaveWriter) {
+        for item in self.discovered.iter() {
+            writer.record("piece", &[item]);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "piece" => self.discovered.push(record.str(0)?.to_string()),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    fn holding<'a>(items: &'a [&'a str]) -> impl Fn(&str) -> u32 + 'a {
+        move |item| items.iter().filter(|i| **i == item).count() as u32
+    }
+
+    const TEA_SET: [&str; 4] = ["Tea Leaf", "Honey", "Silver Cup", "Gold Cup"];
+
+    #[test]
+    fn a_collector_pays_a_premium_for_a_complete_set() {
+        let sim = scenario::default_world(2);
+        let collections = scenario::default_collections();
+        let tea = collections.set("Tea Ceremony").unwrap();
+        assert!(tea.is_complete(holding(&TEA_SET)));
+
+        let offer = collections.offer("Abbess Yue", "Tea Ceremony", &sim, holding(&TEA_SET));
+        assert_eq!(offer, Ok((tea.value(&sim) as f32 * 1.6).round() as u32));
+        assert!(offer.unwrap() > tea.value(&sim));
+    }
+
+    #[test]
+    fn collectors_turn_down_what_they_cannot_take() {
+        let sim = scenario::default_world(2);
+        let collections = scenario::default_collections();
+        let offer = |collector, set, items| collections.offer(collector, set, &sim, holding(items));
+
+        assert_eq!(
+            offer("Abbess Yue", "Samurai Relics", &["Katana"]),
+            Err(CollectionError::NotInterested)
+        );
+        assert_eq!(
+            offer("Old Basho", "Tea Ceremony", &TEA_SET),
+            Err(CollectionError::UnknownCollector)
+        );
+        assert_eq!(
+            offer("Lord Hoshi", "Tea Ceremony", &TEA_SET[1..]),
+            Err(CollectionError::Incomplete)
+        );
+        assert_eq!(
+            collections
+                .set("Tea Ceremony")
+                .unwrap()
+                .held(holding(&TEA_SET[1..])),
+            3
+        );
+    }
+
+    #[test]
+    fn pieces_are_logged_once_and_kept_in_the_save() {
+        let mut collections = scenario::default_collections();
+        let found = collections.discover(["Fish", "Sai", "Honey", "Sai"].into_iter());
+        assert_eq!(
+            found,
+            vec![
+                ("Sai".to_string(), "Samurai Relics".to_string()),
+                ("Honey".to_string(), "Tea Ceremony".to_string()),
+            ]
+        );
+        assert!(collections.discover(["Sai"].into_iter()).is_empty());
+        assert!(!collections.is_discovered("Fish"));
+
+        let mut writer = SaveWriter::new();
+        collections.save(&mut writer);
+        let mut loaded = scenario::default_collections();
+        for record in save::parse(&writer.finish()) {
+            assert!(loaded.load(&record).unwrap());
+        }
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", collections));
+    }
+}
@@ -0,0 +1,339 @@
+use std::fmt;
+
+use super::{
+    clock::GameTime,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::MarketSimulation,
+};
+
+// Contracts posted per town each day, and how generous they are
+const CONTRACTS_PER_DAY: u32 = 2;
+const REWARD_MARKUP: f32 = 1.3;
+const PENALTY_SHARE: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractError {
+    UnknownContract,
+    NotOpen,
+    NotAccepted,
+    NotEnoughItems,
+    Expired,
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::UnknownContract => write!(f, "no such contract"),
+            ContractError::NotOpen => write!(f, "contract was already taken"),
+            ContractError::NotAccepted => write!(f, "contract was not accepted"),
+            ContractError::NotEnoughItems => write!(f, "not enough items to deliver"),
+            ContractError::Expired => write!(f, "contract deadline has passed"),
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractState {
+    Open,
+    Accepted,
+    Completed,
+    Failed,
+}
+
+impl ContractState {
+    fn to_str(self) -> &'static str {
+        match self {
+            ContractState::Open => "Open",
+            ContractState::Accepted => "Accepted",
+            ContractState::Completed => "Completed",
+            ContractState::Failed => "Failed",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<ContractState> {
+        match value {
+            "Open" => Some(ContractState::Open),
+            "Accepted" => Some(ContractState::Accepted),
+            "Completed" => Some(ContractState::Completed),
+            "Failed" => Some(ContractState::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// "Deliver `quantity` `item` to `client` in `town` by `deadline` for `reward`".
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub id: u32,
+    pub town: String,
+    pub client: String,
+    pub item: String,
+    pub quantity: u32,
+    pub deadline: GameTime,
+    pub reward: u32,
+    pub penalty: u32,
+    pub state: ContractState,
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Deliver {} {} to {} by day {} for {} coins",
+            self.quantity,
+            self.item,
+            self.client,
+            self.deadline.day(),
+            self.reward
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContractBoard {
+    next_id: u32,
+    contracts: Vec<Contract>,
+}
+
+impl ContractBoard {
+    pub fn contracts(&self) -> &[Contract] {
+        &self.contracts
+    }
+
+    pub fn contract(&self, id: u32) -> Option<&Contract> {
+        self.contracts.iter().find(|c| c.id == id)
+    }
+
+    // Open and accepted contracts pinned to a town's board
+    pub fn in_town<'a>(&'a self, town: &'a str) -> impl Iterator<Item = &'a Contract> {
+        self.contracts.iter().filter(move |c| {
+            c.town == town && matches!(c.state, ContractState::Open | ContractState::Accepted)
+        })
+    }
+
+    pub fn post(&mut self, mut contract: Contract) -> u32 {
+        self.next_id += 1;
+        contract.id = self.next_id;
+        contract.state = ContractState::Open;
+        self.contracts.push(contract);
+        self.next_id
+    }
+
+    pub fn accept(&mut self, id: u32) -> Result<&Contract, ContractError> {
+        let contract = self.find_mut(id)?;
+        if contract.state != ContractState::Open {
+            return Err(ContractError::NotOpen);
+        }
+        contract.state = ContractState::Accepted;
+        Ok(contract)
+    }
+
+    // Completes the contract when `held` covers it, returns the reward
+    pub fn fulfil(&mut self, id: u32, held: u32, now: GameTime) -> Result<u32, ContractError> {
+        let contract = self.find_mut(id)?;
+        if contract.state != ContractState::Accepted {
+            return Err(ContractError::NotAccepted);
+        }
+        if now > contract.deadline {
+            return Err(ContractError::Expired);
+        }
+        if held < contract.quantity {
+            return Err(ContractError::NotEnoughItems);
+        }
+
+        contract.state = ContractState::Completed;
+        Ok(contract.reward)
+    }
+
+    // Fails accepted contracts past their deadline and drops stale open ones,
+    // returns the failed contracts so their penalties can be charged
+    pub fn expire(&mut self, now: GameTime) -> Vec<Contract> {
+        let mut failed = vec![];
+        for contract in self.contracts.iter_mut() {
+            if now <= contract.deadline {
+                continue;
+            }
+            match contract.state {
+                ContractState::Accepted => {
+                    contract.state = ContractState::Failed;
+                    failed.push(contract.clone());
+                }
+                ContractState::Open => contract.state = ContractState::Failed,
+                _ => {}
+            }
+        }
+
+        // Keep the board from growing forever, only the latest outcomes matter
+        self.contracts
+            .retain(|c| matches!(c.state, ContractState::Open | ContractState::Accepted));
+        failed
+    }
+
+    // Posts new contracts asking for goods that sell in other towns, black
+    // markets stay out of it
+    pub fn generate(&mut self, sim: &mut MarketSimulation) {
+        let now = sim.time();
+        let mut offers = vec![];
+
+        for client in sim.markets().iter().filter(|m| !m.black_market) {
+            let wanted: Vec<_> = sim
+                .markets()
+                .iter()
+                .filter(|m| m.town != client.town && !m.black_market)
+                .flat_map(|m| m.goods.iter())
+                .filter(|g| client.good(&g.item).is_none() && g.max_stock > 0)
+                .map(|g| (g.item.clone(), g.price(), g.max_stock))
+                .collect();
+            if !wanted.is_empty() {
+                offers.push((client.town.clone(), client.name.clone(), wanted));
+            }
+        }
+
+        for (town, client, wanted) in offers {
+            for _ in 0..CONTRACTS_PER_DAY {
+                let rng = sim.rng();
+                let (item, price, max_stock) =
+                    &wanted[rng.range(0, wanted.len() as u32 - 1) as usize];
+                let quantity = rng.range(1, (*max_stock / 2).max(1));
+                let days = rng.range(2, 6) as u64;
+                let value = *price as f32 * quantity as f32;
+
+                self.post(Contract {
+                    id: 0,
+                    town: town.clone(),
+                    client: client.clone(),
+                    item: item.clone(),
+                    quantity,
+                    deadline: now + GameTime::from_days(days),
+                    reward: (value * REWARD_MARKUP).round() as u32,
+                    penalty: (value * PENALTY_SHARE).round() as u32,
+                    state: ContractState::Open,
+                });
+            }
+        }
+    }
+
+    fn find_mut(&mut self, id: u32) -> Result<&mut Contract, ContractError> {
+        self.contracts
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(ContractError::UnknownContract)
+    }
+}
+
+impl Persist for ContractBoard {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("contract_next_id", &[&self.next_id]);
+        for c in self.contracts.iter() {
+            writer.record(
+                "contract",
+                &[
+                    &c.id,
+                    &c.town,
+                    &c.client,
+                    &c.item,
+                    &c.quantity,
+                    &c.deadline.minutes(),
+                    &c.reward,
+                    &c.penalty,
+                    &c.state.to_str(),
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "contract_next_id" => self.next_id = record.get(0)?,
+            "contract" => self.contracts.push(Contract {
+                id: record.get(0)?,
+                town: record.str(1)?.to_string(),
+                client: record.str(2)?.to_string(),
+                item: record.str(3)?.to_string(),
+                quantity: record.get(4)?,
+                deadline: GameTime::from_minutes(record.get(5)?),
+                reward: record.get(6)?,
+                penalty: record.get(7)?,
+                state: ContractState::from_str(record.str(8)?)
+                    .ok_or_else(|| record.error("bad state"))?,
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::scenario;
+
+    fn board_with_order() -> (ContractBoard, u32) {
+        let mut board = ContractBoard::default();
+        let id = board.post(Contract {
+            id: 0,
+            town: "Manor".to_string(),
+            client: "Manor".to_string(),
+            item: "Fish".to_string(),
+            quantity: 10,
+            deadline: GameTime::from_days(3),
+            reward: 80,
+            penalty: 15,
+            state: ContractState::Completed,
+        });
+        (board, id)
+    }
+
+    #[test]
+    fn a_delivery_in_time_pays_the_reward() {
+        let (mut board, id) = board_with_order();
+        assert_eq!(board.contract(id).unwrap().state, ContractState::Open);
+        let now = GameTime::from_days(1);
+        assert_eq!(board.fulfil(id, 10, now), Err(ContractError::NotAccepted));
+
+        board.accept(id).unwrap();
+        assert_eq!(board.accept(id).err(), Some(ContractError::NotOpen));
+        assert_eq!(board.fulfil(id, 9, now), Err(ContractError::NotEnoughItems));
+        assert_eq!(board.fulfil(id, 12, now), Ok(80));
+        assert_eq!(board.contract(id).unwrap().state, ContractState::Completed);
+        assert_eq!(board.in_town("Manor").count(), 0);
+    }
+
+    #[test]
+    fn missing_the_deadline_fails_with_the_penalty() {
+        let (mut board, id) = board_with_order();
+        let (mut open, _) = board_with_order();
+        board.accept(id).unwrap();
+
+        let late = GameTime::from_days(3) + GameTime::from_hours(1);
+        assert_eq!(board.fulfil(id, 10, late), Err(ContractError::Expired));
+        assert!(board.expire(GameTime::from_days(3)).is_empty());
+
+        let failed = board.expire(late);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].penalty, 15);
+        assert_eq!(failed[0].state, ContractState::Failed);
+        assert!(board.contracts().is_empty());
+
+        // Nobody owes anything for an offer they never took
+        assert!(open.expire(late).is_empty());
+        assert!(open.contracts().is_empty());
+    }
+
+    #[test]
+    fn offers_ask_for_goods_the_town_lacks() {
+        let mut sim = scenario::default_world(2);
+        let mut board = ContractBoard::default();
+        board.generate(&mut sim);
+        assert!(!board.contracts().is_empty());
+        for c in board.contracts() {
+            let client = sim.market(&c.client).unwrap();
+            assert!(client.good(&c.item).is_none(), "{c}");
+            assert!(!client.black_market);
+            assert!(c.reward > c.penalty);
+            assert!(c.deadline > sim.time());
+        }
+    }
+}
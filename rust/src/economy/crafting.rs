@@ -0,0 +1,277 @@
+use std::fmt;
+
+use super::{
+    clock::GameTime,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CraftError {
+    UnknownRecipe,
+    Undiscovered,
+    WrongWorkbench,
+    MissingInputs,
+}
+
+impl fmt::Display for CraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraftError::UnknownRecipe => write!(f, "there is no such recipe"),
+            CraftError::Undiscovered => write!(f, "you don't know how to make that yet"),
+            CraftError::WrongWorkbench => write!(f, "that can't be made at this workbench"),
+            CraftError::MissingInputs => write!(f, "you don't have everything it takes"),
+        }
+    }
+}
+
+impl std::error::Error for CraftError {}
+
+/// Turns `inputs` into `quantity` of `output` at a `workbench`, taking
+/// `duration` of the player's time.
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    pub name: String,
+    pub workbench: String,
+    pub inputs: Vec<(String, u32)>,
+    pub output: String,
+    pub quantity: u32,
+    pub duration: GameTime,
+    // Known from the start rather than worked out by holding the inputs
+    pub known: bool,
+}
+
+impl Recipe {
+    pub fn new(name: &str, workbench: &str, output: &str, duration: GameTime) -> Self {
+        Self {
+            name: name.to_string(),
+            workbench: workbench.to_string(),
+            inputs: vec![],
+            output: output.to_string(),
+            quantity: 1,
+            duration,
+            known: false,
+        }
+    }
+
+    pub fn with_input(mut self, item: &str, quantity: u32) -> Self {
+        self.inputs.push((item.to_string(), quantity.max(1)));
+        self
+    }
+
+    pub fn making(mut self, quantity: u32) -> Self {
+        self.quantity = quantity.max(1);
+        self
+    }
+
+    pub fn known(mut self) -> Self {
+        self.known = true;
+        self
+    }
+
+    // How many times over the player can craft it with what they `have`
+    pub fn available(&self, have: impl Fn(&str) -> u32) -> u32 {
+        self.inputs
+            .iter()
+            .map(|(item, quantity)| have(item) / quantity)
+            .min()
+            .unwrap_or(0)
+    }
+
+    // Holding a bit of every input is enough to work out how they go together
+    pub fn is_hinted_by(&self, have: impl Fn(&str) -> u32) -> bool {
+        !self.inputs.is_empty() && self.inputs.iter().all(|(item, _)| have(item) > 0)
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(item, quantity)| format!("{quantity} {item}"))
+            .collect();
+        write!(
+            f,
+            "{}: {} into {} {}, {}h",
+            self.name,
+            inputs.join(" + "),
+            self.quantity,
+            self.output,
+            self.duration.hours()
+        )
+    }
+}
+
+/// Every recipe in the world and which of them the player has discovered.
+#[derive(Debug, Clone, Default)]
+pub struct Cookbook {
+    recipes: Vec<Recipe>,
+    discovered: Vec<String>,
+}
+
+impl Cookbook {
+    pub fn with_recipe(mut self, recipe: Recipe) -> Self {
+        if recipe.known {
+            self.discovered.push(recipe.name.clone());
+        }
+        self.recipes.push(recipe);
+        self
+    }
+
+    pub fn recipes(&self) -> &[Recipe] {
+        &self.recipes
+    }
+
+    pub fn recipe(&self, name: &str) -> Option<&Recipe> {
+        self.recipes.iter().find(|r| r.name == name)
+    }
+
+    pub fn is_discovered(&self, name: &str) -> bool {
+        self.discovered.iter().any(|d| d == name)
+    }
+
+    pub fn at<'a>(&'a self, workbench: &'a str) -> impl Iterator<Item = &'a Recipe> {
+        self.recipes
+            .iter()
+            .filter(move |r| r.workbench == workbench)
+    }
+
+    // Learns every recipe whose inputs the player now holds, returns the
+    // names of the ones that are new
+    pub fn discover(&mut self, have: impl Fn(&str) -> u32) -> Vec<String> {
+        let found: Vec<String> = self
+            .recipes
+            .iter()
+            .filter(|r| !self.is_discovered(&r.name) && r.is_hinted_by(&have))
+            .map(|r| r.name.clone())
+            .collect();
+        self.discovered.extend(found.iter().cloned());
+        found
+    }
+
+    // The recipe, if the player can craft it at `workbench` right now
+    pub fn check(
+        &self,
+        name: &str,
+        workbench: &str,
+        have: impl Fn(&str) -> u32,
+    ) -> Result<&Recipe, CraftError> {
+        let recipe = self.recipe(name).ok_or(CraftError::UnknownRecipe)?;
+        if !self.is_discovered(name) {
+            return Err(CraftError::Undiscovered);
+        }
+        if recipe.workbench != workbench {
+            return Err(CraftError::WrongWorkbench);
+        }
+        if recipe.available(have) == 0 {
+            return Err(CraftError::MissingInputs);
+        }
+        Ok(recipe)
+    }
+}
+
+// Recipes come from the scenario, the save only keeps what was discovered
+impl Persist for Cookbook {
+    fn save(&self, writer: &mut SaveWriter) {
+        for name in self.discovered.iter() {
+            writer.record("recipe", &[name]);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "recipe" => {
+                let name = record.str(0)?;
+                if !self.is_discovered(name) {
+                    self.discovered.push(name.to_string());
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    // Counts an item over every stack of it, like the inventory does
+    fn holding<'a>(stacks: &'a [(&'a str, u32)]) -> impl Fn(&str) -> u32 + 'a {
+        move |item| {
+            stacks
+                .iter()
+                .filter(|(name, _)| *name == item)
+                .map(|(_, count)| count)
+                .sum()
+        }
+    }
+
+    #[test]
+    fn the_scarcest_input_decides_how_many_can_be_made() {
+        let cookbook = scenario::default_recipes();
+        let potion = cookbook.recipe("Brew Life Potion").unwrap();
+
+        let stacks = [("Tea Leaf", 3), ("Honey", 5), ("Tea Leaf", 4)];
+        assert_eq!(potion.available(holding(&stacks)), 3);
+        let stacks = [("Tea Leaf", 9), ("Honey", 2)];
+        assert_eq!(potion.available(holding(&stacks)), 2);
+        let stacks = [("Tea Leaf", 1), ("Honey", 2)];
+        assert_eq!(potion.available(holding(&stacks)), 0);
+        assert_eq!(
+            cookbook
+                .check("Brew Life Potion", "Alchemy Table", holding(&stacks))
+                .unwrap_err(),
+            CraftError::MissingInputs
+        );
+    }
+
+    #[test]
+    fn holding_every_input_teaches_a_recipe() {
+        let mut cookbook = scenario::default_recipes();
+        let stacks = [("Silver Cup", 1), ("Gold", 20)];
+        assert_eq!(
+            cookbook
+                .check("Gild Cup", "Workbench", holding(&stacks))
+                .unwrap_err(),
+            CraftError::Undiscovered
+        );
+        assert!(cookbook.discover(holding(&stacks[..1])).is_empty());
+        assert_eq!(cookbook.discover(holding(&stacks)), vec!["Gild Cup"]);
+        assert!(cookbook.discover(holding(&stacks)).is_empty());
+
+        // Discovered, but a handful of gold won't gild a cup
+        assert_eq!(
+            cookbook
+                .check("Gild Cup", "Workbench", holding(&stacks))
+                .unwrap_err(),
+            CraftError::MissingInputs
+        );
+        let stacks = [("Silver Cup", 1), ("Gold", 60), ("Gold", 30)];
+        assert_eq!(
+            cookbook
+                .check("Gild Cup", "Alchemy Table", holding(&stacks))
+                .unwrap_err(),
+            CraftError::WrongWorkbench
+        );
+        assert!(cookbook
+            .check("Gild Cup", "Workbench", holding(&stacks))
+            .is_ok());
+    }
+
+    #[test]
+    fn a_loaded_cookbook_remembers_what_was_discovered() {
+        let mut cookbook = scenario::default_recipes();
+        cookbook.discover(holding(&[("Silver Cup", 1), ("Gold", 1)]));
+
+        let mut writer = SaveWriter::new();
+        cookbook.save(&mut writer);
+        let mut loaded = scenario::default_recipes();
+        for record in save::parse(&writer.finish()) {
+            assert!(loaded.load(&record).unwrap());
+        }
+        assert!(loaded.is_discovered("Gild Cup"));
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", cookbook));
+    }
+}
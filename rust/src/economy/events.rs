@@ -0,0 +1,439 @@
+use super::{
+    clock::GameTime,
+    rng::SimRng,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::{MarketEvent, MarketSimulation},
+};
+
+// Scheduled events are announced this long before they start
+const NOTICE: GameTime = GameTime::from_days(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventTrigger {
+    // Starts on `first_day` and again every `every_days` (0 for only once)
+    Scheduled { first_day: u64, every_days: u64 },
+    // May start on any day, at most one town at a time
+    Random { chance_per_day: f32 },
+}
+
+/// What an event does to the markets it hits. `towns` and `items` limit it to
+/// some towns and goods, empty means all of them.
+#[derive(Debug, Clone)]
+pub struct EventDef {
+    pub name: String,
+    pub description: String,
+    pub towns: Vec<String>,
+    pub items: Vec<String>,
+    pub price_factor: f32,
+    pub demand_factor: f32,
+    // Applied to the stock once, when the event starts
+    pub stock_factor: f32,
+    pub duration: GameTime,
+    pub trigger: EventTrigger,
+}
+
+impl EventDef {
+    pub fn new(name: &str, description: &str, trigger: EventTrigger) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            towns: vec![],
+            items: vec![],
+            price_factor: 1.,
+            demand_factor: 1.,
+            stock_factor: 1.,
+            duration: GameTime::from_days(1),
+            trigger,
+        }
+    }
+
+    pub fn in_town(mut self, town: &str) -> Self {
+        self.towns.push(town.to_string());
+        self
+    }
+
+    pub fn with_items(mut self, items: &[&str]) -> Self {
+        self.items.extend(items.iter().map(|i| i.to_string()));
+        self
+    }
+
+    pub fn with_prices(mut self, factor: f32) -> Self {
+        self.price_factor = factor;
+        self
+    }
+
+    pub fn with_demand(mut self, factor: f32) -> Self {
+        self.demand_factor = factor;
+        self
+    }
+
+    pub fn with_stock(mut self, factor: f32) -> Self {
+        self.stock_factor = factor;
+        self
+    }
+
+    pub fn lasting(mut self, days: u64) -> Self {
+        self.duration = GameTime::from_days(days);
+        self
+    }
+
+    fn is_scheduled_on(&self, day: u64) -> bool {
+        match self.trigger {
+            EventTrigger::Scheduled {
+                first_day,
+                every_days,
+            } => {
+                day == first_day
+                    || (every_days > 0
+                        && day > first_day
+                        && (day - first_day).is_multiple_of(every_days))
+            }
+            EventTrigger::Random { .. } => false,
+        }
+    }
+}
+
+/// A notice pinned to the boards of `town`, or of every town when `None`.
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub town: Option<String>,
+    pub title: String,
+    pub text: String,
+    pub starts_at: GameTime,
+    pub ends_at: GameTime,
+}
+
+/// Starts events from their definitions as the days go by.
+#[derive(Debug, Clone)]
+pub struct EventCalendar {
+    rng: SimRng,
+    defs: Vec<EventDef>,
+    announcements: Vec<Announcement>,
+}
+
+impl EventCalendar {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimRng::new(seed),
+            defs: vec![],
+            announcements: vec![],
+        }
+    }
+
+    pub fn with_event(mut self, def: EventDef) -> Self {
+        self.defs.push(def);
+        self
+    }
+
+    pub fn defs(&self) -> &[EventDef] {
+        &self.defs
+    }
+
+    pub fn announcements(&self) -> &[Announcement] {
+        &self.announcements
+    }
+
+    // Notices a board in `town` should show
+    pub fn in_town<'a>(&'a self, town: &'a str) -> impl Iterator<Item = &'a Announcement> {
+        self.announcements
+            .iter()
+            .filter(move |a| a.town.as_ref().is_none_or(|t| t == town))
+    }
+
+    // Call once for every in-game day that starts, in order, even when the
+    // clock skipped over several at once. Returns the new announcements
+    pub fn on_new_day(&mut self, today: u64, sim: &mut MarketSimulation) -> Vec<Announcement> {
+        let now = GameTime::from_days(today.saturating_sub(1));
+        self.announcements.retain(|a| a.ends_at > sim.time());
+
+        let mut posted = vec![];
+        for i in 0..self.defs.len() {
+            let def = self.defs[i].clone();
+
+            if def.is_scheduled_on(today + NOTICE.days()) {
+                for town in self.towns_of(&def, sim) {
+                    posted.push(self.announce(&def, Some(town), now + NOTICE, "Coming tomorrow"));
+                }
+            }
+
+            let towns = match def.trigger {
+                EventTrigger::Scheduled { .. } if def.is_scheduled_on(today) => {
+                    self.towns_of(&def, sim)
+                }
+                EventTrigger::Random { chance_per_day } if self.rng.chance(chance_per_day) => {
+                    let towns = self.towns_of(&def, sim);
+                    if towns.is_empty() {
+                        continue;
+                    }
+                    let town = towns[self.rng.range(0, towns.len() as u32 - 1) as usize].clone();
+                    posted.push(self.announce(&def, Some(town.clone()), now, "News"));
+                    vec![town]
+                }
+                _ => continue,
+            };
+
+            for town in towns {
+                start(&def, &town, now, sim);
+            }
+        }
+
+        // Whatever came and went on a skipped day is old news
+        self.announcements.retain(|a| a.ends_at > sim.time());
+        posted.retain(|a| a.ends_at > sim.time());
+        posted
+    }
+
+    // Towns the event can hit, those that trade none of its goods are spared
+    fn towns_of(&self, def: &EventDef, sim: &MarketSimulation) -> Vec<String> {
+        let mut towns: Vec<String> = vec![];
+        for market in sim.markets() {
+            let is_listed = def.towns.is_empty() || def.towns.contains(&market.town);
+            let sells_any =
+                def.items.is_empty() || def.items.iter().any(|i| market.good(i).is_some());
+            if is_listed && sells_any && !towns.contains(&market.town) {
+                towns.push(market.town.clone());
+            }
+        }
+        towns
+    }
+
+    fn announce(
+        &mut self,
+        def: &EventDef,
+        town: Option<String>,
+        starts_at: GameTime,
+        heading: &str,
+    ) -> Announcement {
+        let announcement = Announcement {
+            town,
+            title: format!("{heading}: {}", def.name),
+            text: def.description.clone(),
+            starts_at,
+            ends_at: starts_at + def.duration,
+        };
+        self.announcements.push(announcement.clone());
+        announcement
+    }
+}
+
+// Definitions come from the scenario, only the rolls and the notices up are
+// saved
+impl Persist for EventCalendar {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("calendar", &[&self.rng.state()]);
+        for a in self.announcements.iter() {
+            let town = a.town.clone().unwrap_or_default();
+            writer.record(
+                "announcement",
+                &[
+                    &town,
+                    &a.title,
+                    &a.text,
+                    &a.starts_at.minutes(),
+                    &a.ends_at.minutes(),
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "calendar" => {
+                self.rng = SimRng::new(record.get(0)?);
+                self.announcements.clear();
+            }
+            "announcement" => self.announcements.push(Announcement {
+                town: Some(record.str(0)?)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string),
+                title: record.str(1)?.to_string(),
+                text: record.str(2)?.to_string(),
+                starts_at: GameTime::from_minutes(record.get(3)?),
+                ends_at: GameTime::from_minutes(record.get(4)?),
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+// Applies an event started at `now` to every market of a town for its
+// duration, unless that is already over
+fn start(def: &EventDef, town: &str, now: GameTime, sim: &mut MarketSimulation) {
+    if now + def.duration <= sim.time() {
+        return;
+    }
+    let markets: Vec<String> = sim
+        .markets()
+        .iter()
+        .filter(|m| m.town == town)
+        .map(|m| m.name.clone())
+        .collect();
+
+    for name in markets {
+        let event = MarketEvent {
+            name: def.name.clone(),
+            market: name.clone(),
+            items: def.items.clone(),
+            price_factor: def.price_factor,
+            demand_factor: def.demand_factor,
+            ends_at: now + def.duration,
+        };
+
+        if let Some(market) = sim.market_mut(&name) {
+            for good in market.goods.iter_mut() {
+                if event.affects(&name, &good.item) {
+                    good.stock = (good.stock as f32 * def.stock_factor).round() as u32;
+                }
+            }
+        }
+        sim.add_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    fn fair(first_day: u64, every_days: u64) -> EventDef {
+        EventDef::new(
+            "Fish Fair",
+            "Fishmongers come from all over",
+            EventTrigger::Scheduled {
+                first_day,
+                every_days,
+            },
+        )
+        .in_town("Harbor")
+        .with_items(&["Fish"])
+        .with_prices(0.8)
+    }
+
+    fn has_event(sim: &MarketSimulation, name: &str) -> bool {
+        sim.events().iter().any(|e| e.name == name)
+    }
+
+    #[test]
+    fn scheduled_events_come_back_every_so_many_days() {
+        let fortnightly = fair(5, 14);
+        for day in [5, 19, 33, 47] {
+            assert!(fortnightly.is_scheduled_on(day), "day {day}");
+        }
+        for day in [1, 4, 6, 12, 18, 20] {
+            assert!(!fortnightly.is_scheduled_on(day), "day {day}");
+        }
+
+        let once = fair(5, 0);
+        assert!(once.is_scheduled_on(5));
+        assert!(!once.is_scheduled_on(19));
+    }
+
+    #[test]
+    fn a_notice_goes_up_the_day_before() {
+        let mut calendar = EventCalendar::new(1).with_event(fair(3, 0));
+        let mut sim = scenario::default_world(1);
+
+        sim.advance(GameTime::from_days(1));
+        let posted = calendar.on_new_day(2, &mut sim);
+        assert_eq!(posted.len(), 1);
+        assert_eq!(posted[0].title, "Coming tomorrow: Fish Fair");
+        assert_eq!(posted[0].town.as_deref(), Some("Harbor"));
+        assert_eq!(posted[0].starts_at, GameTime::from_days(2));
+        assert_eq!(calendar.in_town("Harbor").count(), 1);
+        assert_eq!(calendar.in_town("Village").count(), 0);
+        assert!(!has_event(&sim, "Fish Fair"));
+
+        sim.advance(GameTime::from_days(1));
+        calendar.on_new_day(3, &mut sim);
+        assert!(has_event(&sim, "Fish Fair"));
+    }
+
+    #[test]
+    fn skipped_days_only_leave_what_is_still_going_on() {
+        let mut calendar = EventCalendar::new(1)
+            .with_event(fair(4, 0).lasting(2))
+            .with_event(
+                EventDef::new(
+                    "Tea Glut",
+                    "",
+                    EventTrigger::Scheduled {
+                        first_day: 9,
+                        every_days: 0,
+                    },
+                )
+                .with_items(&["Tea Leaf"])
+                .lasting(3),
+            );
+        let mut sim = scenario::default_world(1);
+
+        // The player slept through days 2 to 10
+        sim.advance(GameTime::from_days(9));
+        for day in 2..=10 {
+            calendar.on_new_day(day, &mut sim);
+        }
+        assert!(!has_event(&sim, "Fish Fair"));
+        assert!(calendar
+            .announcements()
+            .iter()
+            .all(|a| !a.title.ends_with("Fish Fair")));
+        let glut: Vec<_> = sim
+            .events()
+            .iter()
+            .filter(|e| e.name == "Tea Glut")
+            .collect();
+        assert!(!glut.is_empty());
+        assert!(glut.iter().all(|e| e.ends_at == GameTime::from_days(8 + 3)));
+    }
+
+    #[test]
+    fn stock_is_cut_once_when_the_event_starts() {
+        let mut calendar = EventCalendar::new(1).with_event(fair(1, 0).with_stock(0.5).lasting(3));
+        let mut sim = scenario::default_world(1);
+        let stock =
+            |sim: &MarketSimulation| sim.market("Harbor").unwrap().good("Fish").unwrap().stock;
+        let before = stock(&sim);
+
+        calendar.on_new_day(1, &mut sim);
+        let cut = (before as f32 * 0.5).round() as u32;
+        assert_eq!(stock(&sim), cut);
+        for day in 2..=3 {
+            calendar.on_new_day(day, &mut sim);
+        }
+        assert_eq!(stock(&sim), cut);
+    }
+
+    #[test]
+    fn a_loaded_calendar_rolls_like_the_saved_one() {
+        let mut a = scenario::default_events(8);
+        let mut sim_a = scenario::default_world(8);
+        for day in 2..=12 {
+            sim_a.advance(GameTime::from_days(1));
+            a.on_new_day(day, &mut sim_a);
+        }
+        let mut writer = SaveWriter::new();
+        a.save(&mut writer);
+
+        let mut b = scenario::default_events(99);
+        for record in save::parse(&writer.finish()) {
+            assert!(b.load(&record).unwrap());
+        }
+        assert_eq!(
+            format!("{:?}", a.announcements()),
+            format!("{:?}", b.announcements())
+        );
+
+        let mut sim_b = sim_a.clone();
+        for day in 13..=60 {
+            sim_a.advance(GameTime::from_days(1));
+            sim_b.advance(GameTime::from_days(1));
+            let posted_a = a.on_new_day(day, &mut sim_a);
+            let posted_b = b.on_new_day(day, &mut sim_b);
+            assert_eq!(format!("{posted_a:?}"), format!("{posted_b:?}"));
+        }
+        assert_eq!(
+            format!("{:?}", a.announcements()),
+            format!("{:?}", b.announcements())
+        );
+    }
+}
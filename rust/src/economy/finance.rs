@@ -0,0 +1,391 @@
+use std::fmt;
+
+use super::{
+    clock::GameTime,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+// Days between repayments
+pub const INSTALLMENT_DAYS: u64 = 7;
+// A missed installment adds this share of it to the balance
+const LATE_FEE_SHARE: f64 = 0.1;
+// Missed installments in a row before the lender seizes goods
+const MISSES_BEFORE_DEFAULT: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoanError {
+    UnknownLender,
+    UnknownLoan,
+    InvalidAmount,
+    OverLimit,
+}
+
+impl fmt::Display for LoanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoanError::UnknownLender => write!(f, "no such lender"),
+            LoanError::UnknownLoan => write!(f, "no such loan"),
+            LoanError::InvalidAmount => write!(f, "amount must be above zero"),
+            LoanError::OverLimit => write!(f, "lender won't lend that much"),
+        }
+    }
+}
+
+impl std::error::Error for LoanError {}
+
+/// A moneylender's terms, `daily_rate` is compounded once per in-game day.
+#[derive(Debug, Clone)]
+pub struct Lender {
+    pub name: String,
+    pub daily_rate: f64,
+    // Most the lender lets the player owe them at once
+    pub max_principal: u64,
+    pub installments: u32,
+}
+
+impl Lender {
+    pub fn new(name: &str, daily_rate: f64, max_principal: u64, installments: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            daily_rate,
+            max_principal,
+            installments,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Loan {
+    pub id: u32,
+    pub lender: String,
+    pub principal: u64,
+    pub balance: u64,
+    pub daily_rate: f64,
+    pub installment: u64,
+    pub next_due: GameTime,
+    // Repaid since the last installment fell due
+    pub paid_since_due: u64,
+    pub missed: u32,
+}
+
+impl Loan {
+    // What still has to be paid before `next_due` to stay on schedule
+    pub fn due_now(&self) -> u64 {
+        self.installment
+            .saturating_sub(self.paid_since_due)
+            .min(self.balance)
+    }
+}
+
+impl fmt::Display for Loan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} coins owed to {}, {} due by day {}",
+            self.balance,
+            self.lender,
+            self.due_now(),
+            self.next_due.day()
+        )
+    }
+}
+
+/// What happened to a loan when a day passed.
+#[derive(Debug, Clone)]
+pub enum LoanEvent {
+    Late { loan: u32, penalty: u64 },
+    Defaulted { loan: Loan },
+}
+
+// Interest for one day, lenders round up to the next coin
+pub fn daily_interest(balance: u64, daily_rate: f64) -> u64 {
+    (balance as f64 * daily_rate).ceil() as u64
+}
+
+// Balance after `days` of daily compounding, the same as ticking day by day
+pub fn compound(balance: u64, daily_rate: f64, days: u64) -> u64 {
+    (0..days).fold(balance, |b, _| b + daily_interest(b, daily_rate))
+}
+
+// Fixed payment every `INSTALLMENT_DAYS` that clears `principal` in
+// `installments` payments
+pub fn installment(principal: u64, daily_rate: f64, installments: u32) -> u64 {
+    let n = installments.max(1);
+    let rate = (1. + daily_rate).powi(INSTALLMENT_DAYS as i32) - 1.;
+    if rate <= 0. {
+        return principal.div_ceil(n as u64);
+    }
+    let payment = principal as f64 * rate / (1. - (1. + rate).powi(-(n as i32)));
+    payment.ceil() as u64
+}
+
+pub fn late_fee(installment: u64) -> u64 {
+    (installment as f64 * LATE_FEE_SHARE).ceil() as u64
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LoanBook {
+    next_id: u32,
+    loans: Vec<Loan>,
+}
+
+impl LoanBook {
+    pub fn loans(&self) -> &[Loan] {
+        &self.loans
+    }
+
+    pub fn loan(&self, id: u32) -> Option<&Loan> {
+        self.loans.iter().find(|l| l.id == id)
+    }
+
+    pub fn total_debt(&self) -> u64 {
+        self.loans.iter().map(|l| l.balance).sum()
+    }
+
+    pub fn owed_to(&self, lender: &str) -> u64 {
+        self.loans
+            .iter()
+            .filter(|l| l.lender == lender)
+            .map(|l| l.balance)
+            .sum()
+    }
+
+    pub fn take(&mut self, lender: &Lender, amount: u64, now: GameTime) -> Result<u32, LoanError> {
+        if amount == 0 {
+            return Err(LoanError::InvalidAmount);
+        }
+        if self.owed_to(&lender.name) + amount > lender.max_principal {
+            return Err(LoanError::OverLimit);
+        }
+
+        self.next_id += 1;
+        self.loans.push(Loan {
+            id: self.next_id,
+            lender: lender.name.clone(),
+            principal: amount,
+            balance: amount,
+            daily_rate: lender.daily_rate,
+            installment: installment(amount, lender.daily_rate, lender.installments),
+            next_due: now + GameTime::from_days(INSTALLMENT_DAYS),
+            paid_since_due: 0,
+            missed: 0,
+        });
+        Ok(self.next_id)
+    }
+
+    // Pays up to `amount` off a loan, returns what was actually taken. A loan
+    // paid off in full is closed
+    pub fn repay(&mut self, id: u32, amount: u64) -> Result<u64, LoanError> {
+        if amount == 0 {
+            return Err(LoanError::InvalidAmount);
+        }
+        let loan = self
+            .loans
+            .iter_mut()
+            .find(|l| l.id == id)
+            .ok_or(LoanError::UnknownLoan)?;
+
+        let paid = amount.min(loan.balance);
+        loan.balance -= paid;
+        loan.paid_since_due += paid;
+
+        self.loans.retain(|l| l.balance > 0);
+        Ok(paid)
+    }
+
+    // Adds a day of interest to every loan and settles installments that
+    // fell due, loans in default are closed and handed back
+    pub fn tick_day(&mut self, now: GameTime) -> Vec<LoanEvent> {
+        let mut events = vec![];
+
+        for loan in self.loans.iter_mut() {
+            loan.balance += daily_interest(loan.balance, loan.daily_rate);
+            if now < loan.next_due {
+                continue;
+            }
+
+            if loan.paid_since_due < loan.installment.min(loan.balance) {
+                let penalty = late_fee(loan.installment);
+                loan.balance += penalty;
+                loan.missed += 1;
+                events.push(LoanEvent::Late {
+                    loan: loan.id,
+                    penalty,
+                });
+            } else {
+                loan.missed = 0;
+            }
+            loan.paid_since_due = 0;
+            loan.next_due += GameTime::from_days(INSTALLMENT_DAYS);
+        }
+
+        let (defaulted, active): (Vec<Loan>, Vec<Loan>) = self
+            .loans
+            .drain(..)
+            .partition(|l| l.missed >= MISSES_BEFORE_DEFAULT);
+        self.loans = active;
+        events.extend(
+            defaulted
+                .into_iter()
+                .map(|loan| LoanEvent::Defaulted { loan }),
+        );
+
+        events
+    }
+}
+
+impl Persist for LoanBook {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("loan_next_id", &[&self.next_id]);
+        for l in self.loans.iter() {
+            writer.record(
+                "loan",
+                &[
+                    &l.id,
+                    &l.lender,
+                    &l.principal,
+                    &l.balance,
+                    &l.daily_rate,
+                    &l.installment,
+                    &l.next_due.minutes(),
+                    &l.paid_since_due,
+                    &l.missed,
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "loan_next_id" => self.next_id = record.get(0)?,
+            "loan" => self.loans.push(Loan {
+                id: record.get(0)?,
+                lender: record.str(1)?.to_string(),
+                principal: record.get(2)?,
+                balance: record.get(3)?,
+                daily_rate: record.get(4)?,
+                installment: record.get(5)?,
+                next_due: GameTime::from_minutes(record.get(6)?),
+                paid_since_due: record.get(7)?,
+                missed: record.get(8)?,
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::save;
+
+    fn lender() -> Lender {
+        Lender::new("Shylock", 0.01, 1000, 4)
+    }
+
+    #[test]
+    fn compound_rounds_interest_up_each_day() {
+        assert_eq!(compound(100, 0.01, 0), 100);
+        assert_eq!(compound(100, 0.01, 1), 101);
+        // 101 * 0.01 = 1.01 rounds up to 2
+        assert_eq!(compound(100, 0.01, 2), 103);
+        assert_eq!(compound(500, 0., 30), 500);
+    }
+
+    #[test]
+    fn installment_without_interest_splits_evenly() {
+        assert_eq!(installment(100, 0., 4), 25);
+        assert_eq!(installment(101, 0., 4), 26);
+    }
+
+    #[test]
+    fn installments_clear_the_loan_on_schedule() {
+        let mut book = LoanBook::default();
+        let id = book.take(&lender(), 400, GameTime::ZERO).unwrap();
+        let payment = book.loan(id).unwrap().installment;
+        assert!(payment > 100);
+
+        for day in 1..=INSTALLMENT_DAYS * 4 {
+            let now = GameTime::from_days(day);
+            if day % INSTALLMENT_DAYS == 0 {
+                book.repay(id, payment).unwrap();
+            }
+            let events = book.tick_day(now);
+            assert!(events.is_empty(), "day {day}: {events:?}");
+        }
+
+        // Rounding up every day may leave up to a coin a day
+        let left = book.loan(id).map_or(0, |l| l.balance);
+        assert!(left <= INSTALLMENT_DAYS * 4, "{left} left");
+    }
+
+    #[test]
+    fn missed_installments_charge_a_fee_then_default() {
+        let mut book = LoanBook::default();
+        let id = book.take(&lender(), 400, GameTime::ZERO).unwrap();
+        let payment = book.loan(id).unwrap().installment;
+
+        let mut late = 0;
+        let mut defaulted = None;
+        for day in 1..=INSTALLMENT_DAYS * 3 {
+            for event in book.tick_day(GameTime::from_days(day)) {
+                match event {
+                    LoanEvent::Late { penalty, .. } => {
+                        assert_eq!(penalty, late_fee(payment));
+                        late += 1;
+                    }
+                    LoanEvent::Defaulted { loan } => defaulted = Some(loan),
+                }
+            }
+        }
+
+        assert_eq!(late, 3);
+        let loan = defaulted.expect("loan should default after three misses");
+        assert!(loan.balance > compound(400, 0.01, INSTALLMENT_DAYS * 3));
+        assert!(book.loans().is_empty());
+    }
+
+    #[test]
+    fn repaying_in_full_closes_the_loan() {
+        let mut book = LoanBook::default();
+        let id = book.take(&lender(), 200, GameTime::ZERO).unwrap();
+        assert_eq!(book.repay(id, 500), Ok(200));
+        assert_eq!(book.total_debt(), 0);
+        assert_eq!(book.repay(id, 1), Err(LoanError::UnknownLoan));
+    }
+
+    #[test]
+    fn lender_refuses_more_than_the_limit() {
+        let mut book = LoanBook::default();
+        book.take(&lender(), 800, GameTime::ZERO).unwrap();
+        assert_eq!(
+            book.take(&lender(), 300, GameTime::ZERO),
+            Err(LoanError::OverLimit)
+        );
+        assert_eq!(
+            book.take(&lender(), 0, GameTime::ZERO),
+            Err(LoanError::InvalidAmount)
+        );
+    }
+
+    #[test]
+    fn loans_survive_a_save() {
+        let mut book = LoanBook::default();
+        let id = book.take(&lender(), 300, GameTime::ZERO).unwrap();
+        book.repay(id, 50).unwrap();
+        book.tick_day(GameTime::from_days(1));
+
+        let mut writer = SaveWriter::new();
+        book.save(&mut writer);
+        let mut loaded = LoanBook::default();
+        save::load_all(&writer.finish(), &mut [&mut loaded]).unwrap();
+
+        let (a, b) = (book.loan(id).unwrap(), loaded.loan(id).unwrap());
+        assert_eq!(a.balance, b.balance);
+        assert_eq!(a.daily_rate, b.daily_rate);
+        assert_eq!(a.next_due, b.next_due);
+        assert_eq!(a.paid_since_due, b.paid_since_due);
+        assert_eq!(loaded.take(&lender(), 1, GameTime::ZERO), Ok(id + 1));
+    }
+}
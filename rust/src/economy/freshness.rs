@@ -0,0 +1,125 @@
+use super::clock::GameTime;
+
+// How fast goods spoil in a cold stash compared to the player's bags
+pub const COLD_SPOIL_RATE: f32 = 0.25;
+// Stacks only merge within the same bucket, so a fresh catch isn't averaged
+// into a rotting one
+const BUCKETS: f32 = 4.;
+// What a merchant still pays for goods on the verge of spoiling
+const STALE_PRICE_SHARE: f32 = 0.3;
+
+// Freshness runs from 1 for fresh goods down to 0 for spoiled ones
+pub const FRESH: f32 = 1.;
+
+// Freshness left after `elapsed` of spoiling at `rate`, goods that keep for
+// `shelf_life` go from fresh to spoiled in exactly that time at rate 1
+pub fn decay(freshness: f32, shelf_life: GameTime, elapsed: GameTime, rate: f32) -> f32 {
+    if shelf_life == GameTime::ZERO {
+        return freshness;
+    }
+    let lost = elapsed.minutes() as f32 * rate.max(0.) / shelf_life.minutes() as f32;
+    (freshness - lost).clamp(0., FRESH)
+}
+
+pub fn is_spoiled(freshness: f32) -> bool {
+    freshness <= 0.
+}
+
+// 0 for spoiled goods, up to BUCKETS for fresh ones
+pub fn bucket(freshness: f32) -> u32 {
+    (freshness.clamp(0., FRESH) * BUCKETS).ceil() as u32
+}
+
+pub fn price_factor(freshness: f32) -> f32 {
+    STALE_PRICE_SHARE + (1. - STALE_PRICE_SHARE) * freshness.clamp(0., FRESH)
+}
+
+pub fn price(unit: u32, freshness: f32) -> u32 {
+    (unit as f32 * price_factor(freshness)).round() as u32
+}
+
+pub fn label(freshness: f32) -> &'static str {
+    match bucket(freshness) {
+        0 => "Spoiled",
+        1 => "Rotting",
+        2 => "Stale",
+        3 => "Good",
+        _ => "Fresh",
+    }
+}
+
+// Units weighted average of two stacks being merged
+pub fn merge(a: f32, a_units: u32, b: f32, b_units: u32) -> f32 {
+    let units = (a_units + b_units).max(1) as f32;
+    (a * a_units as f32 + b * b_units as f32) / units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::simulation::{MarketSimulation, TICK};
+
+    // Ages goods the way the world does, by the whole ticks each advance ran
+    fn age_over(steps: &[u64]) -> f32 {
+        let mut sim = MarketSimulation::new(1);
+        let shelf_life = GameTime::from_hours(48);
+        let mut freshness = FRESH;
+        for minutes in steps {
+            let ticks = sim.advance(GameTime::from_minutes(*minutes));
+            let elapsed = GameTime::from_minutes(ticks * TICK.minutes());
+            freshness = decay(freshness, shelf_life, elapsed, 1.);
+        }
+        freshness
+    }
+
+    #[test]
+    fn many_small_advances_age_goods_like_one_large_one() {
+        // A frame at a time, a minute or two each
+        let frames: Vec<u64> = (0..1200).map(|i| 1 + i % 2).collect();
+        let total = frames.iter().sum();
+
+        let small = age_over(&frames);
+        let large = age_over(&[total]);
+        assert!((small - large).abs() < 1e-4, "{small} vs {large}");
+        assert!(large < FRESH);
+    }
+
+    #[test]
+    fn a_cold_stash_keeps_goods_longer() {
+        let shelf_life = GameTime::from_hours(48);
+        let day = GameTime::from_hours(24);
+        assert_eq!(decay(FRESH, shelf_life, day, 1.), 0.5);
+        assert_eq!(decay(FRESH, shelf_life, day, COLD_SPOIL_RATE), 0.875);
+        assert_eq!(decay(0.5, shelf_life, GameTime::from_hours(72), 1.), 0.);
+        assert!(is_spoiled(decay(
+            0.5,
+            shelf_life,
+            GameTime::from_hours(72),
+            1.
+        )));
+
+        // Goods without a shelf life never go off
+        assert_eq!(decay(0.7, GameTime::ZERO, GameTime::from_days(30), 1.), 0.7);
+    }
+
+    #[test]
+    fn staler_goods_fetch_less_but_never_nothing() {
+        assert_eq!(price(100, FRESH), 100);
+        assert_eq!(price(100, 0.5), 65);
+        assert_eq!(price(100, 0.), 30);
+        assert_eq!(
+            [1., 0.6, 0.4, 0.1, 0.].map(label),
+            ["Fresh", "Good", "Stale", "Rotting", "Spoiled"]
+        );
+    }
+
+    #[test]
+    fn merged_stacks_average_by_units() {
+        assert_eq!(merge(1., 3, 0.6, 1), 0.9);
+        assert_eq!(merge(0.4, 0, 0.8, 5), 0.8);
+        assert_eq!(merge(0.4, 0, 0.8, 0), 0.);
+        // A fresh catch stays apart from yesterday's
+        assert_ne!(bucket(FRESH), bucket(0.7));
+        assert_eq!(bucket(0.8), bucket(0.76));
+    }
+}
@@ -0,0 +1,534 @@
+use std::fmt;
+
+use super::{
+    clock::GameTime,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Buy,
+    Sell,
+    Fee,
+    LoanPayment,
+    // Goods that left without being paid for, seized or confiscated
+    Loss,
+    // Money that came in without goods going out, like a shop's takings
+    Income,
+    // Goods made from others, `amount` is what the inputs had cost
+    Craft,
+}
+
+impl EntryKind {
+    pub const ALL: [EntryKind; 7] = [
+        EntryKind::Buy,
+        EntryKind::Sell,
+        EntryKind::Fee,
+        EntryKind::LoanPayment,
+        EntryKind::Loss,
+        EntryKind::Income,
+        EntryKind::Craft,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryKind::Buy => "Buy",
+            EntryKind::Sell => "Sell",
+            EntryKind::Fee => "Fee",
+            EntryKind::LoanPayment => "LoanPayment",
+            EntryKind::Loss => "Loss",
+            EntryKind::Income => "Income",
+            EntryKind::Craft => "Craft",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
+
+/// One line of the ledger. `item` names what a fee was for when no goods
+/// changed hands, `profit` is only set for sales and losses.
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub kind: EntryKind,
+    pub item: String,
+    pub quantity: u32,
+    pub amount: u32,
+    pub location: String,
+    pub at: GameTime,
+    pub profit: Option<i64>,
+}
+
+impl LedgerEntry {
+    fn new(
+        kind: EntryKind,
+        item: &str,
+        quantity: u32,
+        amount: u32,
+        location: &str,
+        at: GameTime,
+    ) -> Self {
+        Self {
+            kind,
+            item: item.to_string(),
+            quantity,
+            amount,
+            location: location.to_string(),
+            at,
+            profit: None,
+        }
+    }
+}
+
+impl fmt::Display for LedgerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {} {:02}:00 {} ",
+            self.at.day(),
+            self.at.hour_of_day(),
+            self.location
+        )?;
+        match self.kind {
+            EntryKind::Buy => write!(
+                f,
+                "bought {} {} for {}",
+                self.quantity, self.item, self.amount
+            )?,
+            EntryKind::Sell => write!(
+                f,
+                "sold {} {} for {}",
+                self.quantity, self.item, self.amount
+            )?,
+            EntryKind::Fee => write!(f, "paid {} for {}", self.amount, self.item)?,
+            EntryKind::LoanPayment => write!(f, "repaid {}", self.amount)?,
+            EntryKind::Loss => write!(f, "lost {} {}", self.quantity, self.item)?,
+            EntryKind::Income => write!(f, "took in {} from {}", self.amount, self.item)?,
+            EntryKind::Craft => write!(f, "made {} {}", self.quantity, self.item)?,
+        }
+        match self.profit {
+            Some(profit) => write!(f, " ({profit:+})"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Units of an item bought together, sold off oldest first.
+#[derive(Debug, Clone)]
+pub struct Lot {
+    pub item: String,
+    pub quantity: u32,
+    pub unit_cost: u32,
+}
+
+/// What went in and out over one day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayTotals {
+    pub day: u64,
+    pub bought: u64,
+    pub sold: u64,
+    pub fees: u64,
+    pub loan_payments: u64,
+    pub profit: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemTotals {
+    pub item: String,
+    pub bought: u32,
+    pub spent: u64,
+    pub sold: u32,
+    pub earned: u64,
+    pub profit: i64,
+}
+
+/// Every coin the player moved, with the cost of the goods they still hold.
+/// Goods the player came by for free have no lots and sell at pure profit.
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+    lots: Vec<Lot>,
+}
+
+impl Ledger {
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn lots(&self) -> &[Lot] {
+        &self.lots
+    }
+
+    pub fn buy(&mut self, item: &str, quantity: u32, total: u32, location: &str, at: GameTime) {
+        if quantity == 0 {
+            return;
+        }
+        self.add_lots(item, quantity, total as u64);
+        self.entries.push(LedgerEntry::new(
+            EntryKind::Buy,
+            item,
+            quantity,
+            total,
+            location,
+            at,
+        ));
+    }
+
+    // Returns the profit made over what the units cost
+    pub fn sell(
+        &mut self,
+        item: &str,
+        quantity: u32,
+        total: u32,
+        location: &str,
+        at: GameTime,
+    ) -> i64 {
+        let profit = total as i64 - self.take_lots(item, quantity) as i64;
+        self.entries.push(LedgerEntry {
+            profit: Some(profit),
+            ..LedgerEntry::new(EntryKind::Sell, item, quantity, total, location, at)
+        });
+        profit
+    }
+
+    pub fn fee(&mut self, what: &str, amount: u32, location: &str, at: GameTime) {
+        if amount > 0 {
+            self.entries.push(LedgerEntry::new(
+                EntryKind::Fee,
+                what,
+                0,
+                amount,
+                location,
+                at,
+            ));
+        }
+    }
+
+    pub fn loan_payment(&mut self, lender: &str, amount: u32, at: GameTime) {
+        if amount > 0 {
+            self.entries.push(LedgerEntry::new(
+                EntryKind::LoanPayment,
+                "",
+                0,
+                amount,
+                lender,
+                at,
+            ));
+        }
+    }
+
+    pub fn income(&mut self, source: &str, amount: u32, location: &str, at: GameTime) {
+        if amount > 0 {
+            self.entries.push(LedgerEntry {
+                profit: Some(amount as i64),
+                ..LedgerEntry::new(EntryKind::Income, source, 0, amount, location, at)
+            });
+        }
+    }
+
+    // The made goods cost what went into them, so selling them later shows
+    // the profit over the inputs. Returns that cost
+    pub fn craft(
+        &mut self,
+        inputs: &[(String, u32)],
+        output: &str,
+        quantity: u32,
+        location: &str,
+        at: GameTime,
+    ) -> u64 {
+        let cost: u64 = inputs
+            .iter()
+            .map(|(item, used)| self.take_lots(item, *used))
+            .sum();
+        if quantity > 0 && cost > 0 {
+            self.add_lots(output, quantity, cost);
+        }
+        self.entries.push(LedgerEntry::new(
+            EntryKind::Craft,
+            output,
+            quantity,
+            cost as u32,
+            location,
+            at,
+        ));
+        cost
+    }
+
+    // Writes off goods that left the inventory unpaid, returns their cost
+    pub fn lose(&mut self, item: &str, quantity: u32, location: &str, at: GameTime) -> u64 {
+        let cost = self.take_lots(item, quantity);
+        self.entries.push(LedgerEntry {
+            profit: Some(-(cost as i64)),
+            ..LedgerEntry::new(EntryKind::Loss, item, quantity, 0, location, at)
+        });
+        cost
+    }
+
+    pub fn held(&self, item: &str) -> u32 {
+        self.lots
+            .iter()
+            .filter(|l| l.item == item)
+            .map(|l| l.quantity)
+            .sum()
+    }
+
+    // Average cost of the units still held, None when none were bought
+    pub fn cost_basis(&self, item: &str) -> Option<u32> {
+        let held = self.held(item);
+        if held == 0 {
+            return None;
+        }
+        let cost: u64 = self
+            .lots
+            .iter()
+            .filter(|l| l.item == item)
+            .map(|l| l.unit_cost as u64 * l.quantity as u64)
+            .sum();
+        Some((cost as f64 / held as f64).round() as u32)
+    }
+
+    // Profit from sales and losses, less every fee paid
+    pub fn realised(&self) -> i64 {
+        self.entries
+            .iter()
+            .map(|e| match e.kind {
+                EntryKind::Fee => -(e.amount as i64),
+                _ => e.profit.unwrap_or(0),
+            })
+            .sum()
+    }
+
+    // What the held goods would make at `price_of` per unit over their cost
+    pub fn unrealised(&self, price_of: impl Fn(&str) -> u32) -> i64 {
+        self.lots
+            .iter()
+            .map(|l| (price_of(&l.item) as i64 - l.unit_cost as i64) * l.quantity as i64)
+            .sum()
+    }
+
+    // Oldest day first
+    pub fn by_day(&self) -> Vec<DayTotals> {
+        let mut days: Vec<DayTotals> = vec![];
+        for e in self.entries.iter() {
+            let day = e.at.day();
+            let totals = match days.iter_mut().position(|d| d.day == day) {
+                Some(i) => &mut days[i],
+                None => {
+                    days.push(DayTotals {
+                        day,
+                        ..Default::default()
+                    });
+                    days.last_mut().unwrap()
+                }
+            };
+            match e.kind {
+                EntryKind::Buy => totals.bought += e.amount as u64,
+                EntryKind::Sell => totals.sold += e.amount as u64,
+                EntryKind::Fee => {
+                    totals.fees += e.amount as u64;
+                    totals.profit -= e.amount as i64;
+                }
+                EntryKind::LoanPayment => totals.loan_payments += e.amount as u64,
+                EntryKind::Loss | EntryKind::Income | EntryKind::Craft => {}
+            }
+            totals.profit += e.profit.unwrap_or(0);
+        }
+        days
+    }
+
+    // Traded goods in the order they first show up, fees are left out
+    pub fn by_item(&self) -> Vec<ItemTotals> {
+        let mut items: Vec<ItemTotals> = vec![];
+        let traded = self
+            .entries
+            .iter()
+            .filter(|e| matches!(e.kind, EntryKind::Buy | EntryKind::Sell | EntryKind::Loss));
+        for e in traded {
+            let totals = match items.iter_mut().position(|t| t.item == e.item) {
+                Some(i) => &mut items[i],
+                None => {
+                    items.push(ItemTotals {
+                        item: e.item.clone(),
+                        ..Default::default()
+                    });
+                    items.last_mut().unwrap()
+                }
+            };
+            match e.kind {
+                EntryKind::Buy => {
+                    totals.bought += e.quantity;
+                    totals.spent += e.amount as u64;
+                }
+                EntryKind::Sell => {
+                    totals.sold += e.quantity;
+                    totals.earned += e.amount as u64;
+                }
+                _ => {}
+            }
+            totals.profit += e.profit.unwrap_or(0);
+        }
+        items
+    }
+
+    // The odd coin of an uneven split goes to the first units sold
+    fn add_lots(&mut self, item: &str, quantity: u32, total: u64) {
+        let unit_cost = (total / quantity as u64) as u32;
+        let extra = (total % quantity as u64) as u32;
+        if extra > 0 {
+            self.lots.push(Lot {
+                item: item.to_string(),
+                quantity: extra,
+                unit_cost: unit_cost + 1,
+            });
+        }
+        if quantity > extra {
+            self.lots.push(Lot {
+                item: item.to_string(),
+                quantity: quantity - extra,
+                unit_cost,
+            });
+        }
+    }
+
+    // Takes `quantity` units off the oldest lots, returns what they cost
+    fn take_lots(&mut self, item: &str, quantity: u32) -> u64 {
+        let mut remaining = quantity;
+        let mut cost = 0;
+        for lot in self.lots.iter_mut().filter(|l| l.item == item) {
+            if remaining == 0 {
+                break;
+            }
+            let taken = lot.quantity.min(remaining);
+            lot.quantity -= taken;
+            remaining -= taken;
+            cost += lot.unit_cost as u64 * taken as u64;
+        }
+        self.lots.retain(|l| l.quantity > 0);
+        cost
+    }
+}
+
+impl Persist for Ledger {
+    fn save(&self, writer: &mut SaveWriter) {
+        for e in self.entries.iter() {
+            // Empty when there is no profit to speak of
+            let profit = e.profit.map_or(String::new(), |p| p.to_string());
+            writer.record(
+                "ledger",
+                &[
+                    &e.kind.name(),
+                    &e.item,
+                    &e.quantity,
+                    &e.amount,
+                    &e.location,
+                    &e.at.minutes(),
+                    &profit,
+                ],
+            );
+        }
+        for l in self.lots.iter() {
+            writer.record("ledger_lot", &[&l.item, &l.quantity, &l.unit_cost]);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "ledger" => {
+                let kind = EntryKind::from_name(record.str(0)?)
+                    .ok_or_else(|| record.error("unknown entry kind"))?;
+                let profit = match record.str(6)? {
+                    "" => None,
+                    _ => Some(record.get(6)?),
+                };
+                self.entries.push(LedgerEntry {
+                    kind,
+                    item: record.str(1)?.to_string(),
+                    quantity: record.get(2)?,
+                    amount: record.get(3)?,
+                    location: record.str(4)?.to_string(),
+                    at: GameTime::from_minutes(record.get(5)?),
+                    profit,
+                });
+            }
+            "ledger_lot" => self.lots.push(Lot {
+                item: record.str(0)?.to_string(),
+                quantity: record.get(1)?,
+                unit_cost: record.get(2)?,
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hours: u64) -> GameTime {
+        GameTime::from_hours(hours)
+    }
+
+    #[test]
+    fn partial_sells_take_the_oldest_lots_first() {
+        let mut ledger = Ledger::default();
+        ledger.buy("Fish", 10, 50, "Harbor", at(0));
+        ledger.buy("Fish", 10, 80, "Harbor", at(1));
+
+        assert_eq!(ledger.sell("Fish", 4, 40, "Village", at(2)), 20);
+        assert_eq!(ledger.held("Fish"), 16);
+        // 6 left at 5 and 10 at 8
+        assert_eq!(ledger.cost_basis("Fish"), Some(7));
+        assert_eq!(ledger.sell("Fish", 8, 80, "Village", at(3)), 80 - 30 - 16);
+        assert_eq!(ledger.sell("Fish", 8, 40, "Village", at(4)), 40 - 64);
+        assert_eq!(ledger.cost_basis("Fish"), None);
+    }
+
+    #[test]
+    fn uneven_splits_keep_every_coin() {
+        let mut ledger = Ledger::default();
+        ledger.buy("Salt", 3, 10, "Harbor", at(0));
+
+        assert_eq!(ledger.sell("Salt", 1, 4, "Village", at(1)), 0);
+        assert_eq!(ledger.sell("Salt", 2, 6, "Village", at(1)), 0);
+    }
+
+    #[test]
+    fn losses_and_fees_count_against_profit() {
+        let mut ledger = Ledger::default();
+        ledger.buy("Wine", 5, 100, "Harbor", at(0));
+        ledger.fee("Toll", 7, "Harbor", at(1));
+        assert_eq!(ledger.lose("Wine", 2, "Road to Manor", at(2)), 40);
+        ledger.sell("Wine", 3, 90, "Manor", at(3));
+
+        assert_eq!(ledger.realised(), -40 + 30 - 7);
+        let wine = &ledger.by_item()[0];
+        assert_eq!((wine.bought, wine.sold, wine.profit), (5, 3, -10));
+    }
+
+    #[test]
+    fn goods_come_by_for_free_sell_at_pure_profit() {
+        let mut ledger = Ledger::default();
+        assert_eq!(ledger.sell("Shell", 2, 6, "Harbor", at(0)), 6);
+        assert_eq!(ledger.unrealised(|_| 10), 0);
+    }
+
+    #[test]
+    fn crafted_goods_cost_what_went_into_them() {
+        let mut ledger = Ledger::default();
+        ledger.buy("Flour", 4, 20, "Village", at(0));
+        ledger.buy("Berries", 2, 16, "Village", at(0));
+        let inputs = [("Flour".to_string(), 2), ("Berries".to_string(), 2)];
+
+        assert_eq!(ledger.craft(&inputs, "Pie", 2, "Village", at(1)), 26);
+        assert_eq!(ledger.held("Flour"), 2);
+        assert_eq!(ledger.held("Berries"), 0);
+        assert_eq!(ledger.cost_basis("Pie"), Some(13));
+        assert_eq!(ledger.sell("Pie", 2, 40, "Village", at(2)), 14);
+        assert_eq!(
+            ledger.unrealised(|item| if item == "Flour" { 7 } else { 0 }),
+            4
+        );
+    }
+}
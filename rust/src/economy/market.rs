@@ -0,0 +1,336 @@
+use std::fmt;
+
+use super::{
+    appraisal,
+    clock::GameTime,
+    reputation::{self, StockTier},
+    rng::SimRng,
+    smuggling::BLACK_MARKET_PREMIUM,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeError {
+    UnknownItem,
+    OutOfStock,
+    InvalidQuantity,
+    Locked,
+    Unlicensed,
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeError::UnknownItem => write!(f, "item is not traded in this market"),
+            TradeError::OutOfStock => write!(f, "not enough stock"),
+            TradeError::InvalidQuantity => write!(f, "quantity must be at least 1"),
+            TradeError::Locked => write!(f, "the merchant doesn't trust you enough"),
+            TradeError::Unlicensed => write!(f, "trading that here takes a licence"),
+        }
+    }
+}
+
+impl std::error::Error for TradeError {}
+
+/// One item line in a market, keyed by the `Item` name.
+#[derive(Debug, Clone)]
+pub struct MarketGood {
+    pub item: String,
+    pub base_price: u32,
+    pub price_factor: f32,
+    pub stock: u32,
+    pub max_stock: u32,
+    // Units bought by NPCs per in-game day
+    pub demand: f32,
+    pub tier: StockTier,
+    // How wrong the merchant is about this good right now, 1 is spot on
+    pub misprice: f32,
+}
+
+impl MarketGood {
+    pub fn new(item: &str, base_price: u32, max_stock: u32) -> Self {
+        Self {
+            item: item.to_string(),
+            base_price,
+            price_factor: 1.,
+            stock: max_stock,
+            max_stock,
+            demand: max_stock as f32 / 3.,
+            tier: StockTier::Common,
+            misprice: 1.,
+        }
+    }
+
+    pub fn with_tier(mut self, tier: StockTier) -> Self {
+        self.tier = tier;
+        self
+    }
+
+    pub fn with_demand(mut self, demand: f32) -> Self {
+        self.demand = demand;
+        self
+    }
+
+    // The shelf price, including the merchant's mistakes
+    pub fn price(&self) -> u32 {
+        ((self.fair_price() as f32 * self.misprice).round() as u32).max(1)
+    }
+
+    // What the good is really going for
+    pub fn fair_price(&self) -> u32 {
+        ((self.base_price as f32 * self.price_factor).round() as u32).max(1)
+    }
+
+    // Goods get more expensive as they sell out and cheaper when the shelf is full
+    pub fn scarcity_factor(&self) -> f32 {
+        self.scarcity_at(self.stock)
+    }
+
+    fn scarcity_at(&self, stock: u32) -> f32 {
+        if self.max_stock == 0 {
+            return 1.;
+        }
+        let fill = stock as f32 / self.max_stock as f32;
+        1.5 - fill.min(1.)
+    }
+
+    // Shelf price of the unit after `taken` others were bought in the same
+    // order, big orders push the price up as they empty the shelf
+    pub fn impact_price(&self, taken: u32) -> u32 {
+        let impact = self.scarcity_at(self.stock.saturating_sub(taken)) / self.scarcity_factor();
+        ((self.price() as f32 * impact).round() as u32).max(1)
+    }
+
+    // What a unit offered at `unit` fetches after `added` others were sold
+    // in the same order, the price falls as the player fills the shelf
+    pub fn glut_price(&self, unit: u32, added: u32) -> u32 {
+        let impact = self.scarcity_at(self.stock + added + 1) / self.scarcity_factor();
+        ((unit as f32 * impact).floor() as u32).max(1)
+    }
+}
+
+/// Delivers `quantity` (plus or minus `variance`) units of an item every
+/// `interval`, never filling the shelf above `max_stock`.
+#[derive(Debug, Clone)]
+pub struct RestockRule {
+    pub item: String,
+    pub quantity: u32,
+    pub variance: u32,
+    pub interval: GameTime,
+    pub next_at: GameTime,
+}
+
+impl RestockRule {
+    pub fn new(item: &str, quantity: u32, interval: GameTime) -> Self {
+        Self {
+            item: item.to_string(),
+            quantity,
+            variance: 0,
+            interval,
+            next_at: interval,
+        }
+    }
+
+    pub fn with_variance(mut self, variance: u32) -> Self {
+        self.variance = variance;
+        self
+    }
+}
+
+/// A merchant's shop. Several markets can share the same town.
+#[derive(Debug, Clone)]
+pub struct Market {
+    pub name: String,
+    pub town: String,
+    // How well the merchant knows what goods are worth, from 0 to 1
+    pub knowledge: f32,
+    // Deals in contraband and pays a premium for it
+    pub black_market: bool,
+    pub goods: Vec<MarketGood>,
+    pub restock_rules: Vec<RestockRule>,
+}
+
+impl Market {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            town: name.to_string(),
+            knowledge: 0.8,
+            black_market: false,
+            goods: vec![],
+            restock_rules: vec![],
+        }
+    }
+
+    pub fn in_town(mut self, town: &str) -> Self {
+        self.town = town.to_string();
+        self
+    }
+
+    pub fn with_knowledge(mut self, knowledge: f32) -> Self {
+        self.knowledge = knowledge;
+        self
+    }
+
+    pub fn as_black_market(mut self) -> Self {
+        self.black_market = true;
+        self
+    }
+
+    pub fn with_good(mut self, good: MarketGood) -> Self {
+        self.goods.push(good);
+        self
+    }
+
+    pub fn with_restock(mut self, rule: RestockRule) -> Self {
+        self.restock_rules.push(rule);
+        self
+    }
+
+    pub fn good(&self, item: &str) -> Option<&MarketGood> {
+        self.goods.iter().find(|g| g.item == item)
+    }
+
+    pub fn good_mut(&mut self, item: &str) -> Option<&mut MarketGood> {
+        self.goods.iter_mut().find(|g| g.item == item)
+    }
+
+    // What buying `quantity` would cost the player, price impact included
+    pub fn quote(&self, item: &str, quantity: u32, standing: i32) -> Result<u32, TradeError> {
+        if quantity == 0 {
+            return Err(TradeError::InvalidQuantity);
+        }
+        let good = self.good(item).ok_or(TradeError::UnknownItem)?;
+        if good.tier > StockTier::unlocked_at(standing) {
+            return Err(TradeError::Locked);
+        }
+        if good.stock < quantity {
+            return Err(TradeError::OutOfStock);
+        }
+
+        Ok((0..quantity)
+            .map(|taken| reputation::buy_price(good.impact_price(taken), standing))
+            .sum())
+    }
+
+    // Most units of an item `funds` can pay for in one order
+    pub fn affordable(&self, item: &str, standing: i32, funds: u32) -> u32 {
+        let Some(good) = self.good(item) else {
+            return 0;
+        };
+        let mut total = 0;
+        for taken in 0..good.stock {
+            total += reputation::buy_price(good.impact_price(taken), standing);
+            if total > funds {
+                return taken;
+            }
+        }
+        good.stock
+    }
+
+    // The player buys from the market, returns the total cost. The price
+    // stays where the order left it until the market drifts back
+    pub fn buy(&mut self, item: &str, quantity: u32, standing: i32) -> Result<u32, TradeError> {
+        let total = self.quote(item, quantity, standing)?;
+        let good = self.good_mut(item).ok_or(TradeError::UnknownItem)?;
+
+        let before = good.scarcity_factor();
+        good.stock -= quantity;
+        good.price_factor *= good.scarcity_factor() / before;
+
+        Ok(total)
+    }
+
+    // What the merchant pays for one unit, judging `true_value` by their
+    // knowledge. Black markets only pay their premium for contraband
+    pub fn offer(&self, item: &str, true_value: Option<u32>, contraband: bool) -> Option<u32> {
+        let good = self.good(item)?;
+        let offer = match true_value {
+            Some(value) => appraisal::merchant_offer(good.price(), value, self.knowledge),
+            None => good.price(),
+        };
+        if self.black_market && contraband {
+            return Some((offer as f32 * BLACK_MARKET_PREMIUM).round() as u32);
+        }
+        Some(offer)
+    }
+
+    // The player sells to the market, returns the total paid out
+    pub fn sell(
+        &mut self,
+        item: &str,
+        quantity: u32,
+        standing: i32,
+        true_value: Option<u32>,
+        contraband: bool,
+    ) -> Result<u32, TradeError> {
+        if quantity == 0 {
+            return Err(TradeError::InvalidQuantity);
+        }
+        let unit = self
+            .offer(item, true_value, contraband)
+            .ok_or(TradeError::UnknownItem)?;
+        let good = self.good_mut(item).ok_or(TradeError::UnknownItem)?;
+
+        let total = (0..quantity)
+            .map(|added| reputation::sell_price(good.glut_price(unit, added), standing))
+            .sum();
+
+        let before = good.scarcity_factor();
+        good.stock += quantity;
+        good.price_factor *= good.scarcity_factor() / before;
+
+        Ok(total)
+    }
+
+    // Applies every restock rule that is due at `now`
+    pub fn restock(&mut self, now: GameTime, rng: &mut SimRng) {
+        for rule in self.restock_rules.iter_mut() {
+            while rule.next_at <= now && rule.interval > GameTime::ZERO {
+                rule.next_at += rule.interval;
+
+                let low = rule.quantity.saturating_sub(rule.variance);
+                let quantity = rng.range(low, rule.quantity + rule.variance);
+
+                if let Some(good) = self.goods.iter_mut().find(|g| g.item == rule.item) {
+                    good.stock = (good.stock + quantity).min(good.max_stock.max(good.stock));
+                    good.misprice = appraisal::misprice_factor(self.knowledge, rng);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(stock: u32) -> Market {
+        let mut good = MarketGood::new("Tea", 37, 20);
+        good.stock = stock;
+        Market::new("Harbor").with_good(good)
+    }
+
+    #[test]
+    fn selling_pushes_the_price_down() {
+        let mut market = market(10);
+        let before = market.good("Tea").unwrap().price();
+        let total = market.sell("Tea", 5, 0, None, false).unwrap();
+        assert!(total < before * 5);
+        assert!(market.good("Tea").unwrap().price() < before);
+    }
+
+    #[test]
+    fn buying_and_selling_straight_back_never_profits() {
+        for stock in 1..=20 {
+            for quantity in 1..=stock {
+                let mut market = market(stock);
+                let paid = market.buy("Tea", quantity, 0).unwrap();
+                let got = market.sell("Tea", quantity, 0, None, false).unwrap();
+                assert!(
+                    got <= paid,
+                    "stock {stock}, {quantity} units: paid {paid}, got {got}"
+                );
+            }
+        }
+    }
+}
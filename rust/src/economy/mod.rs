@@ -0,0 +1,27 @@
+pub mod appraisal;
+pub mod auction;
+pub mod belongings;
+pub mod buyback;
+pub mod clock;
+pub mod collections;
+pub mod contract;
+pub mod crafting;
+pub mod events;
+pub mod finance;
+pub mod freshness;
+pub mod ledger;
+pub mod market;
+pub mod property;
+pub mod reputation;
+pub mod rivals;
+pub mod rng;
+pub mod routes;
+pub mod save;
+pub mod scenario;
+pub mod scoring;
+pub mod simulation;
+pub mod smuggling;
+pub mod stacking;
+pub mod stall;
+pub mod taxes;
+pub mod trader;
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use super::save::{Persist, SaveError, SaveRecord, SaveWriter};
+
+// Share of what went into a property a buyer would pay for it, which is what
+// it counts for in net worth
+const RESALE_SHARE: f32 = 0.8;
+// Lost for every day it is owned, down to the floor
+const DEPRECIATION_PER_DAY: f32 = 0.002;
+const MIN_RESALE_SHARE: f32 = 0.4;
+// Lost on top for every day its upkeep went unpaid
+const NEGLECT_LOSS: f32 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyError {
+    UnknownProperty,
+    NotOwned,
+    FullyUpgraded,
+}
+
+impl fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyError::UnknownProperty => write!(f, "there is no such property"),
+            PropertyError::NotOwned => write!(f, "you don't own that"),
+            PropertyError::FullyUpgraded => write!(f, "it can't be improved any further"),
+        }
+    }
+}
+
+impl std::error::Error for PropertyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    // Room for `capacity` more stacks in the stash for every level
+    Warehouse { capacity: u32 },
+    // Takes in `revenue` a day for every level
+    Shop { revenue: u32 },
+}
+
+/// A building the player can buy and improve level by level. Every level
+/// adds to what it does for the player and to its daily upkeep.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub town: String,
+    pub kind: PropertyKind,
+    pub price: u32,
+    // Going from level n to n + 1 costs n times this
+    pub upgrade_cost: u32,
+    pub max_level: u32,
+    pub upkeep_per_level: u32,
+    // 0 while the player doesn't own it
+    pub level: u32,
+    pub days_owned: u32,
+    // Days its upkeep went unpaid since it was last improved
+    pub neglected: u32,
+}
+
+impl Property {
+    pub fn new(name: &str, town: &str, kind: PropertyKind, price: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            town: town.to_string(),
+            kind,
+            price,
+            upgrade_cost: price / 2,
+            max_level: 1,
+            upkeep_per_level: 0,
+            level: 0,
+            days_owned: 0,
+            neglected: 0,
+        }
+    }
+
+    pub fn with_upgrades(mut self, max_level: u32, upgrade_cost: u32) -> Self {
+        self.max_level = max_level.max(1);
+        self.upgrade_cost = upgrade_cost;
+        self
+    }
+
+    pub fn with_upkeep(mut self, upkeep_per_level: u32) -> Self {
+        self.upkeep_per_level = upkeep_per_level;
+        self
+    }
+
+    pub fn is_owned(&self) -> bool {
+        self.level > 0
+    }
+
+    // What buying it or taking it up a level costs, None at the top level
+    pub fn next_cost(&self) -> Option<u32> {
+        match self.level {
+            0 => Some(self.price),
+            level if level < self.max_level => Some(self.upgrade_cost * level),
+            _ => None,
+        }
+    }
+
+    // Everything paid for the levels it has now
+    pub fn invested(&self) -> u32 {
+        if !self.is_owned() {
+            return 0;
+        }
+        self.price + (1..self.level).map(|l| self.upgrade_cost * l).sum::<u32>()
+    }
+
+    // What a buyer would pay, less the older and the worse kept it is
+    pub fn resale_value(&self) -> u32 {
+        let share = RESALE_SHARE
+            - DEPRECIATION_PER_DAY * self.days_owned as f32
+            - NEGLECT_LOSS * self.neglected as f32;
+        (self.invested() as f32 * share.max(MIN_RESALE_SHARE)).round() as u32
+    }
+
+    pub fn upkeep(&self) -> u32 {
+        self.upkeep_per_level * self.level
+    }
+
+    pub fn revenue(&self) -> u32 {
+        match self.kind {
+            PropertyKind::Shop { revenue } => revenue * self.level,
+            PropertyKind::Warehouse { .. } => 0,
+        }
+    }
+
+    pub fn stash_capacity(&self) -> u32 {
+        match self.kind {
+            PropertyKind::Warehouse { capacity } => capacity * self.level,
+            PropertyKind::Shop { .. } => 0,
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}", self.name, self.town)?;
+        if self.is_owned() {
+            write!(f, ", level {}/{}", self.level, self.max_level)?;
+        }
+        let level = self.level.max(1);
+        match self.kind {
+            PropertyKind::Warehouse { capacity } => {
+                write!(f, ", {} stash slots", capacity * level)?
+            }
+            PropertyKind::Shop { revenue } => write!(f, ", takes {}/day", revenue * level)?,
+        }
+        write!(f, ", upkeep {}/day", self.upkeep_per_level * level)
+    }
+}
+
+/// What a property made or cost over one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyEvent {
+    Revenue { property: String, amount: u32 },
+    Upkeep { property: String, amount: u32 },
+}
+
+/// Every property on the market and how far the player has built each up.
+#[derive(Debug, Clone, Default)]
+pub struct Estate {
+    properties: Vec<Property>,
+}
+
+impl Estate {
+    pub fn with_property(mut self, property: Property) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    pub fn properties(&self) -> &[Property] {
+        &self.properties
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    pub fn owned(&self) -> impl Iterator<Item = &Property> {
+        self.properties.iter().filter(|p| p.is_owned())
+    }
+
+    pub fn value(&self) -> u32 {
+        self.owned().map(|p| p.resale_value()).sum()
+    }
+
+    pub fn stash_capacity(&self) -> u32 {
+        self.owned().map(|p| p.stash_capacity()).sum()
+    }
+
+    // Buys the property or takes it up a level, returns what that cost.
+    // Improving it puts right whatever neglect did
+    pub fn improve(&mut self, name: &str) -> Result<u32, PropertyError> {
+        let property = self
+            .properties
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or(PropertyError::UnknownProperty)?;
+        let cost = property.next_cost().ok_or(PropertyError::FullyUpgraded)?;
+        if !property.is_owned() {
+            property.days_owned = 0;
+        }
+        property.level += 1;
+        property.neglected = 0;
+        Ok(cost)
+    }
+
+    // Upkeep left unpaid costs the property a level, down to losing it
+    pub fn neglect(&mut self, name: &str) -> Result<u32, PropertyError> {
+        let property = self
+            .properties
+            .iter_mut()
+            .find(|p| p.name == name && p.is_owned())
+            .ok_or(PropertyError::NotOwned)?;
+        property.level -= 1;
+        property.neglected += 1;
+        Ok(property.level)
+    }
+
+    // Takings first, so a shop pays for its own upkeep
+    pub fn tick_day(&mut self) -> Vec<PropertyEvent> {
+        for property in self.properties.iter_mut().filter(|p| p.is_owned()) {
+            property.days_owned += 1;
+        }
+        let mut events = vec![];
+        for property in self.owned() {
+            if property.revenue() > 0 {
+                events.push(PropertyEvent::Revenue {
+                    property: property.name.clone(),
+                    amount: property.revenue(),
+                });
+            }
+        }
+        for property in self.owned() {
+            if property.upkeep() > 0 {
+                events.push(PropertyEvent::Upkeep {
+                    property: property.name.clone(),
+                    amount: property.upkeep(),
+                });
+            }
+        }
+        events
+    }
+}
+
+// Properties come from the scenario, the save only keeps how far the player
+// got with them
+impl Persist for Estate {
+    fn save(&self, writer: &mut SaveWriter) {
+        for property in self.owned() {
+            writer.record(
+                "property",
+                &[
+                    &property.name,
+                    &property.level,
+                    &property.days_owned,
+                    &property.neglected,
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "property" => {
+                let name = record.str(0)?;
+                let property = self
+                    .properties
+                    .iter_mut()
+                    .find(|p| p.name == name)
+                    .ok_or_else(|| record.error("unknown property"))?;
+                property.level = record.get::<u32>(1)?.min(property.max_level);
+                property.days_owned = record.get(2)?;
+                property.neglected = record.get(3)?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shop() -> Property {
+        Property::new("Shop", "Harbor", PropertyKind::Shop { revenue: 10 }, 1000)
+            .with_upgrades(3, 500)
+            .with_upkeep(5)
+    }
+
+    #[test]
+    fn property_is_worth_less_than_was_paid_and_loses_value_with_age() {
+        let mut estate = Estate::default().with_property(shop());
+        estate.improve("Shop").unwrap();
+        let bought = estate.value();
+        assert!(bought < 1000);
+
+        estate.tick_day();
+        assert!(estate.value() < bought);
+        for _ in 0..10_000 {
+            estate.tick_day();
+        }
+        assert_eq!(estate.value(), (1000. * MIN_RESALE_SHARE) as u32);
+    }
+
+    #[test]
+    fn neglect_costs_value_until_the_property_is_improved() {
+        let mut estate = Estate::default().with_property(shop());
+        estate.improve("Shop").unwrap();
+        estate.improve("Shop").unwrap();
+        let kept = estate.property("Shop").unwrap().resale_value();
+
+        estate.neglect("Shop").unwrap();
+        let neglected = estate.property("Shop").unwrap();
+        assert!(neglected.resale_value() < Property { level: 1, ..shop() }.resale_value());
+
+        estate.improve("Shop").unwrap();
+        assert_eq!(estate.property("Shop").unwrap().resale_value(), kept);
+    }
+}
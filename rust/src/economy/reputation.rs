@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use super::{
+    market::Market,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+pub const MIN_REPUTATION: i32 = -100;
+pub const MAX_REPUTATION: i32 = 100;
+
+// Smaller trades than this don't count towards a merchant's good opinion
+pub const FAIR_TRADE_MIN_VALUE: u32 = 50;
+
+// Standing needed before a merchant shows each stock tier
+const FINE_TIER_STANDING: i32 = 25;
+const BACK_ROOM_STANDING: i32 = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StockTier {
+    #[default]
+    Common,
+    Fine,
+    BackRoom,
+}
+
+impl StockTier {
+    pub fn unlocked_at(standing: i32) -> StockTier {
+        if standing >= BACK_ROOM_STANDING {
+            StockTier::BackRoom
+        } else if standing >= FINE_TIER_STANDING {
+            StockTier::Fine
+        } else {
+            StockTier::Common
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    FairTrade,
+    CompletedOrder,
+    FailedOrder,
+    FailedHaggle,
+    SoldFake,
+    // A fake the player didn't know about, merchants are more forgiving
+    PassedFake,
+}
+
+impl ReputationEvent {
+    pub fn delta(&self) -> i32 {
+        match self {
+            ReputationEvent::FairTrade => 1,
+            ReputationEvent::CompletedOrder => 8,
+            ReputationEvent::FailedOrder => -10,
+            ReputationEvent::FailedHaggle => -3,
+            ReputationEvent::SoldFake => -25,
+            ReputationEvent::PassedFake => -8,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ReputationEvent> {
+        match name {
+            "FairTrade" => Some(ReputationEvent::FairTrade),
+            "CompletedOrder" => Some(ReputationEvent::CompletedOrder),
+            "FailedOrder" => Some(ReputationEvent::FailedOrder),
+            "FailedHaggle" => Some(ReputationEvent::FailedHaggle),
+            "SoldFake" => Some(ReputationEvent::SoldFake),
+            "PassedFake" => Some(ReputationEvent::PassedFake),
+            _ => None,
+        }
+    }
+}
+
+/// The player's reputation with each merchant (market) and each town.
+#[derive(Debug, Clone, Default)]
+pub struct Reputation {
+    merchants: BTreeMap<String, i32>,
+    towns: BTreeMap<String, i32>,
+}
+
+impl Reputation {
+    pub fn merchant(&self, market: &str) -> i32 {
+        self.merchants.get(market).copied().unwrap_or(0)
+    }
+
+    pub fn town(&self, town: &str) -> i32 {
+        self.towns.get(town).copied().unwrap_or(0)
+    }
+
+    // What a merchant thinks of the player, word from the town counts for half
+    pub fn standing(&self, market: &Market) -> i32 {
+        (self.merchant(&market.name) + self.town(&market.town) / 2)
+            .clamp(MIN_REPUTATION, MAX_REPUTATION)
+    }
+
+    pub fn record(&mut self, market: &Market, event: ReputationEvent) {
+        let delta = event.delta();
+        adjust(
+            self.merchants.entry(market.name.clone()).or_default(),
+            delta,
+        );
+        adjust(
+            self.towns.entry(market.town.clone()).or_default(),
+            delta / 2,
+        );
+    }
+}
+
+// Merchants sell above the shelf price and buy below it. The gap narrows
+// as a merchant warms to the player and closes completely at the top, so
+// selling back never pays more than buying did
+const MERCHANT_MARGIN: f32 = 0.05;
+
+fn margin(standing: i32) -> f32 {
+    MERCHANT_MARGIN * (MAX_REPUTATION - standing) as f32 / MAX_REPUTATION as f32
+}
+
+// From 10% over the shelf price for a merchant who loathes the player down
+// to the shelf price itself for one who trusts them completely
+pub fn buy_price(price: u32, standing: i32) -> u32 {
+    ((price as f32 * (1. + margin(standing))).round() as u32).max(1)
+}
+
+// From 10% under the shelf price up to the shelf price itself
+pub fn sell_price(price: u32, standing: i32) -> u32 {
+    ((price as f32 * (1. - margin(standing))).round() as u32).max(1)
+}
+
+fn adjust(value: &mut i32, delta: i32) {
+    *value = (*value + delta).clamp(MIN_REPUTATION, MAX_REPUTATION);
+}
+
+impl Persist for Reputation {
+    fn save(&self, writer: &mut SaveWriter) {
+        for (name, value) in self.merchants.iter() {
+            writer.record("reputation_merchant", &[name, value]);
+        }
+        for (name, value) in self.towns.iter() {
+            writer.record("reputation_town", &[name, value]);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        let map = match record.kind.as_str() {
+            "reputation_merchant" => &mut self.merchants,
+            "reputation_town" => &mut self.towns,
+            _ => return Ok(false),
+        };
+        map.insert(record.str(0)?.to_string(), record.get(1)?);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selling_never_pays_more_than_buying() {
+        for standing in MIN_REPUTATION..=MAX_REPUTATION {
+            for price in [1, 2, 7, 12, 60, 240, 1999] {
+                assert!(
+                    sell_price(price, standing) <= buy_price(price, standing),
+                    "price {price} at standing {standing}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn standing_improves_both_sides() {
+        assert!(buy_price(100, MAX_REPUTATION) < buy_price(100, 0));
+        assert!(buy_price(100, 0) < buy_price(100, MIN_REPUTATION));
+        assert!(sell_price(100, MAX_REPUTATION) > sell_price(100, 0));
+        assert!(sell_price(100, 0) > sell_price(100, MIN_REPUTATION));
+    }
+}
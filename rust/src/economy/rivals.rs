@@ -0,0 +1,310 @@
+use std::{collections::VecDeque, fmt};
+
+use super::{
+    clock::GameTime,
+    routes::RouteMap,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::MarketSimulation,
+    trader::{Cargo, Trader},
+};
+
+// How much gossip the towns remember, the oldest is forgotten first
+const MAX_RUMOURS: usize = 30;
+// Time a rival needs to walk between two markets of the same town, and how
+// long they wait around when nothing is worth buying
+const ACROSS_TOWN: GameTime = GameTime::from_hours(1);
+const IDLE_TIME: GameTime = GameTime::from_hours(6);
+// For markets no road connects
+const OFF_ROAD: GameTime = GameTime::from_days(1);
+
+/// Word of what a rival trader did, told in the town it happened in.
+#[derive(Debug, Clone)]
+pub struct Rumour {
+    pub town: String,
+    pub at: GameTime,
+    pub text: String,
+}
+
+impl fmt::Display for Rumour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {} {:02}:00: {}",
+            self.at.day(),
+            self.at.hour_of_day(),
+            self.text
+        )
+    }
+}
+
+/// An AI flipper who trades greedily between markets like the balancing
+/// trader, but takes time to get from one to the next.
+#[derive(Debug, Clone)]
+pub struct Rival {
+    pub trader: Trader,
+    // When they reach `trader.location` and can trade again
+    pub ready_at: GameTime,
+}
+
+impl Rival {
+    pub fn new(name: &str, funds: i64, market: &str, capacity: u32) -> Self {
+        Self {
+            trader: Trader::new(name, funds, market, capacity),
+            ready_at: GameTime::ZERO,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.trader.name
+    }
+
+    pub fn is_travelling(&self, now: GameTime) -> bool {
+        self.ready_at > now
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rivals {
+    rivals: Vec<Rival>,
+    rumours: VecDeque<Rumour>,
+}
+
+impl Rivals {
+    pub fn with_rival(mut self, rival: Rival) -> Self {
+        self.rivals.push(rival);
+        self
+    }
+
+    pub fn rivals(&self) -> &[Rival] {
+        &self.rivals
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rival> {
+        self.rivals.iter().find(|r| r.name() == name)
+    }
+
+    fn rival_mut(&mut self, name: &str) -> Option<&mut Rival> {
+        self.rivals.iter_mut().find(|r| r.name() == name)
+    }
+
+    // Town the rival is in, None while they are on the road
+    pub fn town_of(&self, name: &str, sim: &MarketSimulation) -> Option<String> {
+        let rival = self.get(name)?;
+        if rival.is_travelling(sim.time()) {
+            return None;
+        }
+        sim.market(&rival.trader.location).map(|m| m.town.clone())
+    }
+
+    // Newest first
+    pub fn rumours(&self) -> impl Iterator<Item = &Rumour> {
+        self.rumours.iter().rev()
+    }
+
+    pub fn rumours_in<'a>(&'a self, town: &'a str) -> impl Iterator<Item = &'a Rumour> {
+        self.rumours().filter(move |r| r.town == town)
+    }
+
+    // Lets every rival who has arrived sell what they carry, buy the next
+    // load and set off, returns the rumours that spread
+    pub fn tick(&mut self, sim: &mut MarketSimulation, map: &RouteMap) -> Vec<Rumour> {
+        let now = sim.time();
+        let mut rumours = vec![];
+
+        for rival in self.rivals.iter_mut().filter(|r| !r.is_travelling(now)) {
+            let Some(town) = sim.market(&rival.trader.location).map(|m| m.town.clone()) else {
+                continue;
+            };
+            let mut tell = |text: String| {
+                rumours.push(Rumour {
+                    town: town.clone(),
+                    at: now,
+                    text,
+                })
+            };
+
+            for sale in rival.trader.sell_all(sim) {
+                tell(format!(
+                    "{} sold {} {} at {} for {:+}",
+                    rival.trader.name, sale.quantity, sale.item, sale.market, sale.profit
+                ));
+            }
+
+            let from = rival.trader.location.clone();
+            match rival.trader.buy_best_deal(sim, |_| 0) {
+                Some(destination) => {
+                    if let Some(Cargo { item, quantity, .. }) = rival.trader.cargo.last() {
+                        tell(format!(
+                            "{} bought up {quantity} {item} at {from}, heading for {destination}",
+                            rival.trader.name
+                        ));
+                    }
+                    rival.ready_at = now + travel_time(sim, map, &from, &destination);
+                    rival.trader.location = destination;
+                }
+                None => rival.ready_at = now + IDLE_TIME,
+            }
+        }
+
+        for rumour in rumours.iter() {
+            if self.rumours.len() >= MAX_RUMOURS {
+                self.rumours.pop_front();
+            }
+            self.rumours.push_back(rumour.clone());
+        }
+        rumours
+    }
+}
+
+// How long a trader takes to get from one market to another
+pub fn travel_time(sim: &MarketSimulation, map: &RouteMap, from: &str, to: &str) -> GameTime {
+    let (Some(from), Some(to)) = (sim.market(from), sim.market(to)) else {
+        return OFF_ROAD;
+    };
+    if from.town == to.town {
+        return ACROSS_TOWN;
+    }
+    map.route(&from.town, &to.town)
+        .map_or(OFF_ROAD, |r| r.duration)
+}
+
+impl Persist for Rivals {
+    fn save(&self, writer: &mut SaveWriter) {
+        for r in self.rivals.iter() {
+            let t = &r.trader;
+            writer.record(
+                "rival",
+                &[&t.name, &t.funds, &t.location, &r.ready_at.minutes()],
+            );
+            for c in t.cargo.iter() {
+                writer.record(
+                    "rival_cargo",
+                    &[&t.name, &c.item, &c.quantity, &c.unit_cost, &c.origin],
+                );
+            }
+        }
+        for r in self.rumours.iter() {
+            writer.record("rumour", &[&r.town, &r.at.minutes(), &r.text]);
+        }
+    }
+
+    // Rivals come from the scenario, the save only moves them along
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "rival" => {
+                if let Some(r) = self.rival_mut(record.str(0)?) {
+                    r.trader.funds = record.get(1)?;
+                    r.trader.location = record.str(2)?.to_string();
+                    r.ready_at = GameTime::from_minutes(record.get(3)?);
+                    r.trader.cargo.clear();
+                }
+            }
+            "rival_cargo" => {
+                if let Some(r) = self.rival_mut(record.str(0)?) {
+                    r.trader.cargo.push(Cargo {
+                        item: record.str(1)?.to_string(),
+                        quantity: record.get(2)?,
+                        unit_cost: record.get(3)?,
+                        origin: record.str(4)?.to_string(),
+                    });
+                }
+            }
+            "rumour" => self.rumours.push_back(Rumour {
+                town: record.str(0)?.to_string(),
+                at: GameTime::from_minutes(record.get(1)?),
+                text: record.str(2)?.to_string(),
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    // Ticks rivals every hour for so many days, like the world does
+    fn run(sim: &mut MarketSimulation, rivals: &mut Rivals, days: u32) {
+        let map = scenario::default_routes();
+        for _ in 0..days * 24 {
+            sim.advance(GameTime::from_hours(1));
+            rivals.tick(sim, &map);
+        }
+    }
+
+    #[test]
+    fn a_rival_is_on_the_road_for_as_long_as_the_route_takes() {
+        let mut sim = scenario::default_world(3);
+        let mut rivals = scenario::default_rivals();
+        let map = scenario::default_routes();
+        let now = sim.time();
+        let before = rivals.clone();
+        rivals.tick(&mut sim, &map);
+
+        for (rival, was) in rivals.rivals().iter().zip(before.rivals()) {
+            let from = &was.trader.location;
+            let to = &rival.trader.location;
+            let took = rival.ready_at - now;
+            if rival.trader.cargo.is_empty() {
+                assert_eq!(took, IDLE_TIME);
+            } else {
+                assert_eq!(took, travel_time(&sim, &map, from, to));
+            }
+            assert!(rival.is_travelling(now));
+            assert_eq!(rivals.town_of(rival.name(), &sim), None);
+        }
+
+        // Nobody trades again before they get there
+        let moved = format!("{:?}", rivals.rivals());
+        assert!(rivals.tick(&mut sim, &map).is_empty());
+        assert_eq!(format!("{:?}", rivals.rivals()), moved);
+    }
+
+    #[test]
+    fn towns_remember_only_the_latest_gossip() {
+        let mut sim = scenario::default_world(3);
+        let mut rivals = scenario::default_rivals();
+        let mut writer = SaveWriter::new();
+        for i in 0..MAX_RUMOURS {
+            writer.record("rumour", &[&"Manor", &0, &format!("old news {i}")]);
+        }
+        for record in save::parse(&writer.finish()) {
+            assert!(rivals.load(&record).unwrap());
+        }
+
+        sim.advance(GameTime::from_hours(1));
+        let told = rivals.tick(&mut sim, &scenario::default_routes());
+        assert!(!told.is_empty());
+
+        let heard: Vec<_> = rivals.rumours().collect();
+        assert_eq!(heard.len(), MAX_RUMOURS);
+        assert_eq!(heard[0].text, told.last().unwrap().text);
+        assert_eq!(
+            heard.last().unwrap().text,
+            format!("old news {}", told.len())
+        );
+        assert!(rivals.rumours_in("Manor").all(|r| r.town == "Manor"));
+    }
+
+    #[test]
+    fn loaded_rivals_trade_like_the_saved_ones() {
+        let mut sim = scenario::default_world(5);
+        let mut rivals = scenario::default_rivals();
+        run(&mut sim, &mut rivals, 4);
+
+        let mut writer = SaveWriter::new();
+        rivals.save(&mut writer);
+        let mut loaded = scenario::default_rivals();
+        for record in save::parse(&writer.finish()) {
+            assert!(loaded.load(&record).unwrap());
+        }
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", rivals));
+
+        let mut other = sim.clone();
+        run(&mut sim, &mut rivals, 3);
+        run(&mut other, &mut loaded, 3);
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", rivals));
+    }
+}
@@ -0,0 +1,528 @@
+use std::fmt;
+
+use super::{
+    clock::{GameTime, MINUTES_PER_DAY},
+    reputation::{self, StockTier},
+    rng::SimRng,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::MarketSimulation,
+    smuggling::BLACK_MARKET_PREMIUM,
+    taxes::TaxOffice,
+};
+
+// Share of each stack bandits make off with or the weather ruins
+const ROBBED_SHARE: (u32, u32) = (20, 50);
+const DAMAGED_SHARE: (u32, u32) = (10, 30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TravelError {
+    NoRoute,
+    CantAfford,
+}
+
+impl fmt::Display for TravelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TravelError::NoRoute => write!(f, "no road leads there from here"),
+            TravelError::CantAfford => write!(f, "not enough coins for the journey"),
+        }
+    }
+}
+
+impl std::error::Error for TravelError {}
+
+/// Something that befell the player's cargo on the road. `share` is the
+/// part of every stack that is lost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encounter {
+    Robbed { share: f32 },
+    Damaged { share: f32 },
+}
+
+impl Encounter {
+    pub fn units_lost(&self, stacks: u32) -> u32 {
+        let share = match self {
+            Encounter::Robbed { share } | Encounter::Damaged { share } => *share,
+        };
+        ((stacks as f32 * share).round() as u32).min(stacks)
+    }
+}
+
+impl fmt::Display for Encounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encounter::Robbed { .. } => write!(f, "Bandits make off with part of your cargo"),
+            Encounter::Damaged { .. } => write!(f, "A storm ruins part of your cargo"),
+        }
+    }
+}
+
+// Share of the cargo a road with `danger` costs on an average trip
+fn expected_loss(danger: f32) -> f32 {
+    let robbed = (ROBBED_SHARE.0 + ROBBED_SHARE.1) as f32 / 200.;
+    let damaged = (DAMAGED_SHARE.0 + DAMAGED_SHARE.1) as f32 / 200.;
+    danger * (robbed + damaged) / 2.
+}
+
+/// A road between two towns, travelled either way.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub towns: (String, String),
+    pub duration: GameTime,
+    pub toll: u32,
+    pub food_per_day: u32,
+    // Chance of running into trouble on a single trip
+    pub danger: f32,
+    // Guards the player passes on the way
+    pub checkpoint: Option<String>,
+}
+
+impl Route {
+    pub fn new(from: &str, to: &str, duration: GameTime) -> Self {
+        Self {
+            towns: (from.to_string(), to.to_string()),
+            duration,
+            toll: 0,
+            food_per_day: 5,
+            danger: 0.,
+            checkpoint: None,
+        }
+    }
+
+    pub fn with_toll(mut self, toll: u32) -> Self {
+        self.toll = toll;
+        self
+    }
+
+    pub fn with_food(mut self, food_per_day: u32) -> Self {
+        self.food_per_day = food_per_day;
+        self
+    }
+
+    pub fn with_danger(mut self, danger: f32) -> Self {
+        self.danger = danger;
+        self
+    }
+
+    pub fn through(mut self, checkpoint: &str) -> Self {
+        self.checkpoint = Some(checkpoint.to_string());
+        self
+    }
+
+    pub fn connects(&self, a: &str, b: &str) -> bool {
+        (self.towns.0 == a && self.towns.1 == b) || (self.towns.0 == b && self.towns.1 == a)
+    }
+
+    // The town at the far end when setting out from `town`
+    pub fn other_end(&self, town: &str) -> Option<&str> {
+        if self.towns.0 == town {
+            Some(&self.towns.1)
+        } else if self.towns.1 == town {
+            Some(&self.towns.0)
+        } else {
+            None
+        }
+    }
+
+    // Food is bought for every day begun on the road
+    pub fn food_cost(&self) -> u32 {
+        let days = self.duration.minutes().div_ceil(MINUTES_PER_DAY).max(1);
+        self.food_per_day * days as u32
+    }
+
+    pub fn cost(&self) -> u32 {
+        self.toll + self.food_cost()
+    }
+
+    pub fn encounter(&self, rng: &mut SimRng) -> Option<Encounter> {
+        if !rng.chance(self.danger) {
+            return None;
+        }
+        if rng.chance(0.5) {
+            let share = rng.range(ROBBED_SHARE.0, ROBBED_SHARE.1) as f32 / 100.;
+            Some(Encounter::Robbed { share })
+        } else {
+            let share = rng.range(DAMAGED_SHARE.0, DAMAGED_SHARE.1) as f32 / 100.;
+            Some(Encounter::Damaged { share })
+        }
+    }
+}
+
+/// Every road in the world and the town the player is in.
+#[derive(Debug, Clone)]
+pub struct RouteMap {
+    pub location: String,
+    routes: Vec<Route>,
+}
+
+impl RouteMap {
+    pub fn new(location: &str) -> Self {
+        Self {
+            location: location.to_string(),
+            routes: vec![],
+        }
+    }
+
+    pub fn with_route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    pub fn route(&self, from: &str, to: &str) -> Option<&Route> {
+        self.routes.iter().find(|r| r.connects(from, to))
+    }
+
+    pub fn routes_from<'a>(&'a self, town: &'a str) -> impl Iterator<Item = &'a Route> {
+        self.routes
+            .iter()
+            .filter(move |r| r.other_end(town).is_some())
+    }
+
+    // The road out of the player's town to `to`, if there is one
+    pub fn departure(&self, to: &str) -> Result<Route, TravelError> {
+        self.route(&self.location, to)
+            .cloned()
+            .ok_or(TravelError::NoRoute)
+    }
+}
+
+impl Persist for RouteMap {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record("location", &[&self.location]);
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "location" => self.location = record.str(0)?.to_string(),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// The best single load to carry along a route, as far as the prices the
+/// player knows right now go.
+#[derive(Debug, Clone)]
+pub struct Deal {
+    pub item: String,
+    pub buy_market: String,
+    pub sell_market: String,
+    pub quantity: u32,
+    pub cost: u32,
+    pub revenue: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutePlan {
+    pub to: String,
+    pub duration: GameTime,
+    pub trip_cost: u32,
+    pub danger: f32,
+    pub deal: Option<Deal>,
+}
+
+impl RoutePlan {
+    // Expected profit of the trip, cargo lost on the road and the trip's
+    // costs taken off
+    pub fn profit(&self) -> i64 {
+        let trade = self.deal.as_ref().map_or(0., |d| {
+            d.revenue as f32 * (1. - expected_loss(self.danger)) - d.cost as f32
+        });
+        trade.round() as i64 - self.trip_cost as i64
+    }
+}
+
+impl fmt::Display for RoutePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}h, {} coins, {:.0}% danger",
+            self.to,
+            self.duration.hours(),
+            self.trip_cost,
+            self.danger * 100.
+        )?;
+        match &self.deal {
+            Some(d) => write!(
+                f,
+                ", buy {} {} at {} for {}, sell at {} for {}, about {:+}",
+                d.quantity,
+                d.item,
+                d.buy_market,
+                d.cost,
+                d.sell_market,
+                d.revenue,
+                self.profit()
+            ),
+            None => write!(f, ", nothing worth carrying"),
+        }
+    }
+}
+
+/// A price the player saw on a market's shelf, and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeenPrice {
+    pub market: String,
+    pub item: String,
+    pub price: u32,
+    pub at: GameTime,
+}
+
+/// What the player last saw every market ask. The player only learns about
+/// a town's markets by being there, so plans go by these rather than by
+/// what the markets are doing right now.
+#[derive(Debug, Clone, Default)]
+pub struct KnownPrices {
+    seen: Vec<SeenPrice>,
+}
+
+impl KnownPrices {
+    pub fn seen(&self) -> &[SeenPrice] {
+        &self.seen
+    }
+
+    pub fn get(&self, market: &str, item: &str) -> Option<&SeenPrice> {
+        self.seen
+            .iter()
+            .find(|s| s.market == market && s.item == item)
+    }
+
+    // Notes the shelf price of every good in every market of `town`
+    pub fn observe(&mut self, sim: &MarketSimulation, town: &str, at: GameTime) {
+        for market in sim.markets().iter().filter(|m| m.town == town) {
+            for good in market.goods.iter() {
+                let price = good.price();
+                match self
+                    .seen
+                    .iter_mut()
+                    .find(|s| s.market == market.name && s.item == good.item)
+                {
+                    Some(seen) => {
+                        seen.price = price;
+                        seen.at = at;
+                    }
+                    None => self.seen.push(SeenPrice {
+                        market: market.name.clone(),
+                        item: good.item.clone(),
+                        price,
+                        at,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+impl Persist for KnownPrices {
+    fn save(&self, writer: &mut SaveWriter) {
+        for s in self.seen.iter() {
+            writer.record(
+                "seen_price",
+                &[&s.market, &s.item, &s.price, &s.at.minutes()],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "seen_price" => self.seen.push(SeenPrice {
+                market: record.str(0)?.to_string(),
+                item: record.str(1)?.to_string(),
+                price: record.get(2)?,
+                at: GameTime::from_minutes(record.get(3)?),
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// What the planner needs to know about an item from the player's catalogue.
+#[derive(Debug, Clone, Default)]
+pub struct GoodInfo {
+    // The licence category, see `TaxOffice::may_trade`
+    pub category: String,
+    pub contraband: bool,
+}
+
+// Estimates every trip out of `town`, most profitable first. Goods are
+// bought at the prices on the shelves here and sold at those the player
+// last saw at the other end, after the town's sales tax. Buys are capped by
+// `funds` and by the `room` the player has for each item. Goods the player
+// has no licence for are left out, and contraband only goes to black markets
+#[allow(clippy::too_many_arguments)]
+pub fn plan(
+    sim: &MarketSimulation,
+    known: &KnownPrices,
+    taxes: &TaxOffice,
+    map: &RouteMap,
+    town: &str,
+    funds: u32,
+    room: impl Fn(&str) -> u32,
+    standing: impl Fn(&str) -> i32,
+    info: impl Fn(&str) -> GoodInfo,
+) -> Vec<RoutePlan> {
+    let mut plans: Vec<RoutePlan> = map
+        .routes_from(town)
+        .filter_map(|route| {
+            let to = route.other_end(town)?;
+            let trip_cost = route.cost();
+            let budget = funds.saturating_sub(trip_cost);
+
+            let mut best: Option<Deal> = None;
+            let sources = sim
+                .markets()
+                .iter()
+                .filter(|m| m.town == town && !m.black_market);
+            for source in sources {
+                let unlocked = StockTier::unlocked_at(standing(&source.name));
+                for good in source.goods.iter().filter(|g| g.tier <= unlocked) {
+                    let GoodInfo {
+                        category,
+                        contraband,
+                    } = info(&good.item);
+                    if !taxes.may_trade(town, &category) {
+                        continue;
+                    }
+                    let quantity = source
+                        .affordable(&good.item, standing(&source.name), budget)
+                        .min(room(&good.item));
+                    let Ok(cost) = source.quote(&good.item, quantity, standing(&source.name))
+                    else {
+                        continue;
+                    };
+
+                    for buyer in sim.markets().iter().filter(|m| m.town == to) {
+                        // Black markets ask for neither licences nor taxes
+                        let is_open = !buyer.black_market;
+                        if is_open && (contraband || !taxes.may_trade(to, &category)) {
+                            continue;
+                        }
+                        if buyer.good(&good.item).is_none() {
+                            continue;
+                        }
+                        let Some(seen) = known.get(&buyer.name, &good.item) else {
+                            continue;
+                        };
+                        let unit = if buyer.black_market && contraband {
+                            (seen.price as f32 * BLACK_MARKET_PREMIUM).round() as u32
+                        } else {
+                            seen.price
+                        };
+                        let paid = reputation::sell_price(unit, standing(&buyer.name)) * quantity;
+                        let revenue = if is_open {
+                            paid - taxes.sales_tax(to, paid)
+                        } else {
+                            paid
+                        };
+                        let margin = revenue as i64 - cost as i64;
+                        if margin
+                            > best
+                                .as_ref()
+                                .map_or(0, |b| b.revenue as i64 - b.cost as i64)
+                        {
+                            best = Some(Deal {
+                                item: good.item.clone(),
+                                buy_market: source.name.clone(),
+                                sell_market: buyer.name.clone(),
+                                quantity,
+                                cost,
+                                revenue,
+                            });
+                        }
+                    }
+                }
+            }
+
+            Some(RoutePlan {
+                to: to.to_string(),
+                duration: route.duration,
+                trip_cost,
+                danger: route.danger,
+                deal: best,
+            })
+        })
+        .collect();
+
+    plans.sort_by_key(|p| std::cmp::Reverse(p.profit()));
+    plans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::scenario;
+
+    fn plan_from(sim: &MarketSimulation, known: &KnownPrices, town: &str) -> Vec<RoutePlan> {
+        plan(
+            sim,
+            known,
+            &scenario::default_taxes(),
+            &scenario::default_routes(),
+            town,
+            500,
+            |_| 1000,
+            |_| 0,
+            |_| GoodInfo::default(),
+        )
+    }
+
+    #[test]
+    fn a_trip_earns_the_margin_less_losses_and_costs() {
+        let mut trip = RoutePlan {
+            to: "Village".to_string(),
+            duration: GameTime::from_hours(6),
+            trip_cost: 10,
+            danger: 0.,
+            deal: Some(Deal {
+                item: "Fish".to_string(),
+                buy_market: "Harbor".to_string(),
+                sell_market: "Village".to_string(),
+                quantity: 20,
+                cost: 100,
+                revenue: 200,
+            }),
+        };
+        assert_eq!(trip.profit(), 90);
+
+        // Bandits and storms take 11% of the cargo on an average trip here
+        trip.danger = 0.4;
+        assert_eq!(trip.profit(), 68);
+
+        trip.deal = None;
+        assert_eq!(trip.profit(), -10);
+    }
+
+    #[test]
+    fn plans_go_by_the_prices_the_player_saw() {
+        let mut sim = scenario::default_world(1);
+        let mut known = KnownPrices::default();
+        assert!(plan_from(&sim, &known, "Harbor")
+            .iter()
+            .all(|p| p.deal.is_none() && p.profit() == -(p.trip_cost as i64)));
+
+        known.observe(&sim, "Village", sim.time());
+        // Prices move on after the player left
+        sim.advance(GameTime::from_days(4));
+        let plans = plan_from(&sim, &known, "Harbor");
+        let to_village = plans.iter().find(|p| p.to == "Village").unwrap();
+        let deal = to_village.deal.as_ref().unwrap();
+        let seen = known.get(&deal.sell_market, &deal.item).unwrap();
+        let paid = reputation::sell_price(seen.price, 0) * deal.quantity;
+        let taxes = scenario::default_taxes();
+        assert_eq!(deal.revenue, paid - taxes.sales_tax("Village", paid));
+        assert!(deal.revenue > deal.cost);
+        assert!(plans.windows(2).all(|w| w[0].profit() >= w[1].profit()));
+    }
+
+    #[test]
+    fn an_encounter_never_takes_more_than_the_stack() {
+        assert_eq!(Encounter::Robbed { share: 0.25 }.units_lost(10), 3);
+        assert_eq!(Encounter::Damaged { share: 1.2 }.units_lost(10), 10);
+        assert_eq!(Encounter::Damaged { share: 0.1 }.units_lost(0), 0);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "save line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// One line of a save file: a record kind followed by its fields.
+#[derive(Debug, Clone)]
+pub struct SaveRecord {
+    pub line: usize,
+    pub kind: String,
+    pub fields: Vec<String>,
+}
+
+impl SaveRecord {
+    pub fn error(&self, message: &str) -> SaveError {
+        SaveError {
+            line: self.line,
+            message: format!("{}: {message}", self.kind),
+        }
+    }
+
+    pub fn str(&self, index: usize) -> Result<&str, SaveError> {
+        self.fields
+            .get(index)
+            .map(|f| f.as_str())
+            .ok_or_else(|| self.error(&format!("missing field {index}")))
+    }
+
+    pub fn get<T: FromStr>(&self, index: usize) -> Result<T, SaveError> {
+        self.str(index)?
+            .parse()
+            .map_err(|_| self.error(&format!("bad field {index}")))
+    }
+}
+
+/// Anything that writes itself into the save file and picks its own records
+/// back out of it.
+pub trait Persist {
+    fn save(&self, writer: &mut SaveWriter);
+
+    // Returns false when the record belongs to someone else
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError>;
+}
+
+#[derive(Debug, Default)]
+pub struct SaveWriter {
+    out: String,
+}
+
+impl SaveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, kind: &str, fields: &[&dyn fmt::Display]) {
+        self.out.push_str(&escape(kind));
+        for field in fields {
+            self.out.push('\t');
+            self.out.push_str(&escape(&field.to_string()));
+        }
+        self.out.push('\n');
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+pub fn parse(text: &str) -> Vec<SaveRecord> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(i, l)| {
+            let mut fields = l.split('\t').map(unescape);
+            SaveRecord {
+                line: i + 1,
+                kind: fields.next().unwrap_or_default(),
+                fields: fields.collect(),
+            }
+        })
+        .collect()
+}
+
+// Feeds every record to the first part that claims it
+pub fn load_all(text: &str, parts: &mut [&mut dyn Persist]) -> Result<(), SaveError> {
+    for record in parse(text) {
+        let mut claimed = false;
+        for part in parts.iter_mut() {
+            if part.load(&record)? {
+                claimed = true;
+                break;
+            }
+        }
+        if !claimed {
+            return Err(record.error("unknown record"));
+        }
+    }
+    Ok(())
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -0,0 +1,348 @@
+use super::{
+    auction::{AiBidder, AuctionHouse},
+    clock::GameTime,
+    collections::{Collections, Collector, ItemSet},
+    crafting::{Cookbook, Recipe},
+    events::{EventCalendar, EventDef, EventTrigger},
+    finance::Lender,
+    market::{Market, MarketGood, RestockRule},
+    property::{Estate, Property, PropertyKind},
+    reputation::StockTier,
+    rivals::{Rival, Rivals},
+    routes::{Route, RouteMap},
+    scoring::{Campaign, Goal},
+    simulation::MarketSimulation,
+    smuggling::Checkpoint,
+    stall::{Customer, Stall},
+    taxes::{TaxOffice, TownDues},
+};
+
+// The towns and goods used by the balancing binary and as the starting world
+pub fn default_world(seed: u64) -> MarketSimulation {
+    let mut sim = MarketSimulation::new(seed);
+
+    sim.add_market(
+        Market::new("Harbor")
+            .with_good(MarketGood::new("Fish", 4, 80).with_demand(40.))
+            .with_good(MarketGood::new("Tea Leaf", 12, 30))
+            .with_good(MarketGood::new("Silver Cup", 60, 8))
+            .with_good(MarketGood::new("Gold", 1, 500).with_demand(100.))
+            .with_good(MarketGood::new("Sai", 100, 1).with_tier(StockTier::BackRoom))
+            .with_good(MarketGood::new("Loupe", 80, 2))
+            .with_restock(RestockRule::new("Fish", 30, GameTime::from_hours(12)).with_variance(10))
+            .with_restock(RestockRule::new("Tea Leaf", 10, GameTime::from_days(1)).with_variance(3))
+            .with_restock(
+                RestockRule::new("Silver Cup", 2, GameTime::from_days(2)).with_variance(1),
+            )
+            .with_restock(RestockRule::new("Gold", 100, GameTime::from_days(1))),
+    );
+    sim.add_market(
+        Market::new("Village")
+            .with_knowledge(0.4)
+            .with_good(MarketGood::new("Fish", 7, 20))
+            .with_good(MarketGood::new("Honey", 9, 40).with_demand(10.))
+            .with_good(MarketGood::new("Life Potion", 35, 12))
+            .with_good(MarketGood::new("Gold", 1, 300))
+            .with_good(MarketGood::new("Yumi Bow", 120, 1).with_tier(StockTier::Fine))
+            .with_restock(RestockRule::new("Fish", 5, GameTime::from_days(1)).with_variance(2))
+            .with_restock(RestockRule::new("Honey", 15, GameTime::from_days(1)).with_variance(5))
+            .with_restock(
+                RestockRule::new("Life Potion", 4, GameTime::from_days(2)).with_variance(2),
+            )
+            .with_restock(RestockRule::new("Gold", 100, GameTime::from_days(1))),
+    );
+    sim.add_market(
+        Market::new("Manor")
+            .with_knowledge(0.95)
+            .with_good(MarketGood::new("Tea Leaf", 20, 10).with_demand(6.))
+            .with_good(MarketGood::new("Honey", 15, 10))
+            .with_good(MarketGood::new("Silver Cup", 85, 4).with_demand(2.))
+            .with_good(
+                MarketGood::new("Gold Cup", 240, 3)
+                    .with_demand(1.)
+                    .with_tier(StockTier::Fine),
+            )
+            .with_good(MarketGood::new("Katana", 180, 2).with_tier(StockTier::BackRoom))
+            .with_good(MarketGood::new("Ninjaku", 150, 1).with_tier(StockTier::BackRoom))
+            .with_good(MarketGood::new("Gold", 1, 800))
+            .with_restock(RestockRule::new("Tea Leaf", 3, GameTime::from_days(1)))
+            .with_restock(RestockRule::new("Honey", 3, GameTime::from_days(1)).with_variance(1))
+            .with_restock(RestockRule::new("Gold Cup", 1, GameTime::from_days(4)))
+            .with_restock(RestockRule::new("Katana", 1, GameTime::from_days(3)).with_variance(1))
+            .with_restock(RestockRule::new("Gold", 200, GameTime::from_days(1))),
+    );
+    sim.add_market(
+        Market::new("Smuggler's Den")
+            .in_town("Harbor")
+            .with_knowledge(0.7)
+            .as_black_market()
+            .with_good(MarketGood::new("Fire Scroll", 90, 6).with_demand(2.))
+            .with_good(MarketGood::new("Katana", 200, 2).with_demand(0.5))
+            .with_good(MarketGood::new("Yari Lance", 170, 1).with_demand(0.5))
+            .with_restock(
+                RestockRule::new("Fire Scroll", 2, GameTime::from_days(2)).with_variance(1),
+            ),
+    );
+
+    sim
+}
+
+// Guards on the roads between the towns of the default world
+pub fn default_checkpoints() -> Vec<Checkpoint> {
+    vec![
+        Checkpoint::new("Harbor Gate", "Harbor", "Village"),
+        Checkpoint::new("Manor Road", "Village", "Manor")
+            .with_strictness(0.08, 0.7)
+            .with_fine(35),
+    ]
+}
+
+// Roads between the towns, the player starts out in Harbor
+pub fn default_routes() -> RouteMap {
+    RouteMap::new("Harbor")
+        .with_route(
+            Route::new("Harbor", "Village", GameTime::from_hours(6))
+                .with_toll(5)
+                .with_danger(0.1)
+                .through("Harbor Gate"),
+        )
+        .with_route(
+            Route::new("Village", "Manor", GameTime::from_hours(10))
+                .with_toll(15)
+                .with_danger(0.05)
+                .through("Manor Road"),
+        )
+        // The coast road avoids the guards but is long and lawless
+        .with_route(
+            Route::new(
+                "Harbor",
+                "Manor",
+                GameTime::from_days(1) + GameTime::from_hours(6),
+            )
+            .with_food(8)
+            .with_danger(0.3),
+        )
+}
+
+// Sales taxes, listing fees and licences of the default towns
+pub fn default_taxes() -> TaxOffice {
+    TaxOffice::new(0.05, 2)
+        .with_town(
+            TownDues::new("Harbor", 0.05)
+                .with_listing_fee(2)
+                .with_licence("Weapons", 100),
+        )
+        .with_town(TownDues::new("Village", 0.03).with_listing_fee(1))
+        .with_town(
+            TownDues::new("Manor", 0.08)
+                .with_listing_fee(5)
+                .with_licence("Weapons", 150)
+                .with_licence("Potions", 60),
+        )
+}
+
+// What the player can make at the default workbenches
+pub fn default_recipes() -> Cookbook {
+    Cookbook::default()
+        .with_recipe(
+            Recipe::new(
+                "Brew Life Potion",
+                "Alchemy Table",
+                "Life Potion",
+                GameTime::from_hours(4),
+            )
+            .with_input("Tea Leaf", 2)
+            .with_input("Honey", 1)
+            .known(),
+        )
+        .with_recipe(
+            Recipe::new("Gild Cup", "Workbench", "Gold Cup", GameTime::from_hours(8))
+                .with_input("Silver Cup", 1)
+                .with_input("Gold", 90),
+        )
+}
+
+// Sets worth more together, and who pays for them
+pub fn default_collections() -> Collections {
+    Collections::default()
+        .with_set(
+            ItemSet::new("Samurai Relics")
+                .with_piece("Katana")
+                .with_piece("Ninjaku")
+                .with_piece("Sai")
+                .with_piece("Yumi Bow")
+                .with_piece("Yari Lance"),
+        )
+        .with_set(
+            ItemSet::new("Tea Ceremony")
+                .with_piece("Tea Leaf")
+                .with_piece("Honey")
+                .with_piece("Silver Cup")
+                .with_piece("Gold Cup"),
+        )
+        .with_collector(
+            Collector::new("Lord Hoshi", "Manor")
+                .with_interest("Samurai Relics", 2.)
+                .with_interest("Tea Ceremony", 1.3),
+        )
+        .with_collector(Collector::new("Abbess Yue", "Village").with_interest("Tea Ceremony", 1.6))
+}
+
+// Late game money sinks, some of which pay for themselves in time
+pub fn default_estate() -> Estate {
+    Estate::default()
+        .with_property(
+            Property::new(
+                "Harbor Warehouse",
+                "Harbor",
+                PropertyKind::Warehouse { capacity: 4 },
+                400,
+            )
+            .with_upgrades(3, 250)
+            .with_upkeep(4),
+        )
+        .with_property(
+            Property::new(
+                "Village Tea House",
+                "Village",
+                PropertyKind::Shop { revenue: 30 },
+                900,
+            )
+            .with_upgrades(3, 600)
+            .with_upkeep(8),
+        )
+        .with_property(
+            Property::new(
+                "Manor Curio Shop",
+                "Manor",
+                PropertyKind::Shop { revenue: 90 },
+                3000,
+            )
+            .with_upgrades(3, 2000)
+            .with_upkeep(25),
+        )
+}
+
+// What the player plays the default world for
+pub fn default_campaign() -> Campaign {
+    Campaign::new("Harbor Trader")
+        .with_goal(Goal::new(1000, 10))
+        .with_goal(Goal::new(5000, 20))
+        .with_goal(Goal::new(10000, 30))
+}
+
+// Flippers who chase the same spreads as the player
+pub fn default_rivals() -> Rivals {
+    Rivals::default()
+        .with_rival(Rival::new("Kiro", 300, "Harbor", 30))
+        .with_rival(Rival::new("Widow Mabel", 200, "Village", 25))
+        .with_rival(Rival::new("Sultan Azim", 800, "Manor", 20))
+}
+
+pub fn default_auction_house(seed: u64) -> AuctionHouse {
+    AuctionHouse::new(seed)
+        .with_bidder(
+            AiBidder::new("Lady Whitmore", 800, 150)
+                .with_interest("Gold Cup", 1.4)
+                .with_interest("Silver Cup", 1.3)
+                .with_interest("Tea Leaf", 1.1),
+        )
+        .with_bidder(
+            AiBidder::new("Captain Rook", 400, 80)
+                .with_interest("Katana", 1.5)
+                .with_interest("Life Potion", 1.2)
+                .with_interest("Fish", 0.9),
+        )
+        .with_bidder(
+            AiBidder::new("Old Basho", 250, 40)
+                .with_interest("Tea Leaf", 1.3)
+                .with_interest("Honey", 1.2)
+                .with_interest("Silver Cup", 1.1),
+        )
+}
+
+// Moneylenders the player can borrow from, by name
+pub fn default_lenders() -> Vec<Lender> {
+    vec![
+        Lender::new("Harbor Moneylender", 0.02, 1000, 4),
+        Lender::new("Manor Bank", 0.008, 3000, 8),
+    ]
+}
+
+// Stalls the player can rent, one per town at most, with the townsfolk who
+// shop at them
+pub fn default_stalls(seed: u64) -> Vec<Stall> {
+    vec![
+        Stall::new("Harbor", 15, 4, seed ^ 0x5747)
+            .with_customer(Customer::new("a fisherman", 30).with_fancy("Tea Leaf"))
+            .with_customer(
+                Customer::new("a sailor", 60)
+                    .with_fancy("Life Potion")
+                    .with_fancy("Fish"),
+            )
+            .with_customer(
+                Customer::new("a cook", 40)
+                    .with_fancy("Fish")
+                    .with_fancy("Honey"),
+            )
+            .with_customer(Customer::new("a guard", 200).with_fancy("Katana"))
+            .with_customer(
+                Customer::new("a scholar", 120)
+                    .with_fancy("Silver Cup")
+                    .with_fancy("Tea Leaf"),
+            ),
+        Stall::new("Village", 8, 3, seed ^ 0x5748)
+            .with_customer(Customer::new("a farmer", 25).with_fancy("Fish"))
+            .with_customer(
+                Customer::new("a housewife", 50)
+                    .with_fancy("Honey")
+                    .with_fancy("Tea Leaf"),
+            )
+            .with_customer(Customer::new("a traveller", 80).with_fancy("Life Potion")),
+    ]
+}
+
+// Festivals, shortages and crashes that come and go
+pub fn default_events(seed: u64) -> EventCalendar {
+    EventCalendar::new(seed ^ 0xE7E7)
+        .with_event(
+            EventDef::new(
+                "Harvest Festival",
+                "The village celebrates the harvest, food sells fast and dear",
+                EventTrigger::Scheduled {
+                    first_day: 5,
+                    every_days: 14,
+                },
+            )
+            .in_town("Village")
+            .with_items(&["Fish", "Honey", "Tea Leaf"])
+            .with_demand(2.5)
+            .with_prices(1.3)
+            .lasting(3),
+        )
+        .with_event(
+            EventDef::new(
+                "Bandit Raid",
+                "Bandits raided the roads, weapons are hard to come by",
+                EventTrigger::Random {
+                    chance_per_day: 0.06,
+                },
+            )
+            .with_items(&["Katana"])
+            .with_stock(0.2)
+            .with_prices(1.8)
+            .lasting(4),
+        )
+        .with_event(
+            EventDef::new(
+                "Flood",
+                "The river burst its banks, merchants sell off what they can",
+                EventTrigger::Random {
+                    chance_per_day: 0.04,
+                },
+            )
+            .with_demand(0.5)
+            .with_prices(0.6)
+            .lasting(2),
+        )
+}
@@ -0,0 +1,364 @@
+use std::fmt;
+
+use super::save::{Persist, SaveError, SaveRecord, SaveWriter};
+
+// Points for every goal reached, on top of the final net worth
+const GOAL_BONUS: i64 = 1000;
+// Scores kept per scenario, the lowest drops off
+const HIGH_SCORES_KEPT: usize = 10;
+
+/// Everything the player owns, less what they owe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetWorth {
+    pub funds: i64,
+    // Carried goods at what the player believes they are worth
+    pub inventory: i64,
+    pub property: i64,
+    pub debt: i64,
+}
+
+impl NetWorth {
+    pub fn total(&self) -> i64 {
+        self.funds + self.inventory + self.property - self.debt
+    }
+}
+
+impl fmt::Display for NetWorth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} coins + {} in goods + {} in property - {} debt = {}",
+            self.funds,
+            self.inventory,
+            self.property,
+            self.debt,
+            self.total()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalState {
+    Open,
+    Reached { day: u64 },
+    Failed,
+}
+
+impl GoalState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GoalState::Open => "open",
+            GoalState::Reached { .. } => "reached",
+            GoalState::Failed => "failed",
+        }
+    }
+}
+
+/// Reach `target` net worth by the end of day `deadline`.
+#[derive(Debug, Clone)]
+pub struct Goal {
+    pub target: i64,
+    pub deadline: u64,
+    pub state: GoalState,
+}
+
+impl Goal {
+    pub fn new(target: i64, deadline: u64) -> Self {
+        Self {
+            target,
+            deadline,
+            state: GoalState::Open,
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Reach {} net worth by day {}",
+            self.target, self.deadline
+        )?;
+        match self.state {
+            GoalState::Open => Ok(()),
+            GoalState::Reached { day } => write!(f, " (reached on day {day})"),
+            GoalState::Failed => write!(f, " (failed)"),
+        }
+    }
+}
+
+/// How a campaign ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Results {
+    pub scenario: String,
+    pub day: u64,
+    pub net_worth: i64,
+    pub goals_reached: usize,
+    pub goals: usize,
+    pub score: i64,
+}
+
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} of {} goals on day {}, net worth {}, score {}",
+            self.scenario, self.goals_reached, self.goals, self.day, self.net_worth, self.score
+        )
+    }
+}
+
+/// The goals of one scenario, played out in any order. It is over once
+/// every goal has been reached or missed.
+#[derive(Debug, Clone)]
+pub struct Campaign {
+    pub scenario: String,
+    goals: Vec<Goal>,
+}
+
+impl Campaign {
+    pub fn new(scenario: &str) -> Self {
+        Self {
+            scenario: scenario.to_string(),
+            goals: vec![],
+        }
+    }
+
+    pub fn with_goal(mut self, goal: Goal) -> Self {
+        self.goals.push(goal);
+        self
+    }
+
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.goals.iter().all(|g| g.state != GoalState::Open)
+    }
+
+    // Settles open goals against the player's net worth on `day`, returns
+    // the goals that changed
+    pub fn check(&mut self, net_worth: i64, day: u64) -> Vec<Goal> {
+        let mut changed = vec![];
+        for goal in self.goals.iter_mut().filter(|g| g.state == GoalState::Open) {
+            // A goal reached only after its deadline still counts as failed
+            if day > goal.deadline {
+                goal.state = GoalState::Failed;
+            } else if net_worth >= goal.target {
+                goal.state = GoalState::Reached { day };
+            } else {
+                continue;
+            }
+            changed.push(goal.clone());
+        }
+        changed
+    }
+
+    pub fn results(&self, net_worth: i64, day: u64) -> Results {
+        let goals_reached = self
+            .goals
+            .iter()
+            .filter(|g| matches!(g.state, GoalState::Reached { .. }))
+            .count();
+        Results {
+            scenario: self.scenario.clone(),
+            day,
+            net_worth,
+            goals_reached,
+            goals: self.goals.len(),
+            score: net_worth.max(0) + GOAL_BONUS * goals_reached as i64,
+        }
+    }
+}
+
+impl Persist for Campaign {
+    fn save(&self, writer: &mut SaveWriter) {
+        for (i, goal) in self.goals.iter().enumerate() {
+            let day = match goal.state {
+                GoalState::Reached { day } => day,
+                _ => 0,
+            };
+            writer.record("goal", &[&i, &goal.state.name(), &day]);
+        }
+    }
+
+    // Goals come from the scenario, the save only records how they went
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "goal" => {
+                let index: usize = record.get(0)?;
+                let state = match record.str(1)? {
+                    "open" => GoalState::Open,
+                    "reached" => GoalState::Reached {
+                        day: record.get(2)?,
+                    },
+                    "failed" => GoalState::Failed,
+                    other => return Err(record.error(&format!("unknown goal state {other}"))),
+                };
+                if let Some(goal) = self.goals.get_mut(index) {
+                    goal.state = state;
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Best results on this machine, kept apart from the save game so they
+/// outlive it.
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    entries: Vec<Results>,
+}
+
+impl HighScores {
+    // Best first
+    pub fn for_scenario<'a>(&'a self, scenario: &'a str) -> impl Iterator<Item = &'a Results> {
+        self.entries.iter().filter(move |r| r.scenario == scenario)
+    }
+
+    // Returns the rank the results made, None when they didn't make the table
+    pub fn record(&mut self, results: Results) -> Option<usize> {
+        let rank = self
+            .for_scenario(&results.scenario)
+            .take_while(|r| r.score >= results.score)
+            .count();
+        if rank >= HIGH_SCORES_KEPT {
+            return None;
+        }
+
+        let scenario = results.scenario.clone();
+        self.entries.push(results);
+        self.entries.sort_by_key(|r| std::cmp::Reverse(r.score));
+
+        // Drop whatever fell off the end of this scenario's table
+        let mut kept = 0;
+        self.entries.retain(|r| {
+            if r.scenario != scenario {
+                return true;
+            }
+            kept += 1;
+            kept <= HIGH_SCORES_KEPT
+        });
+        Some(rank + 1)
+    }
+}
+
+impl Persist for HighScores {
+    fn save(&self, writer: &mut SaveWriter) {
+        for r in self.entries.iter() {
+            writer.record(
+                "high_score",
+                &[
+                    &r.scenario,
+                    &r.day,
+                    &r.net_worth,
+                    &r.goals_reached,
+                    &r.goals,
+                    &r.score,
+                ],
+            );
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "high_score" => self.entries.push(Results {
+                scenario: record.str(0)?.to_string(),
+                day: record.get(1)?,
+                net_worth: record.get(2)?,
+                goals_reached: record.get(3)?,
+                goals: record.get(4)?,
+                score: record.get(5)?,
+            }),
+            _ => return Ok(false),
+        }
+        self.entries.sort_by_key(|r| std::cmp::Reverse(r.score));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{save, scenario};
+
+    fn results(score: i64) -> Results {
+        Results {
+            scenario: "Harbor Trader".to_string(),
+            day: 30,
+            net_worth: score,
+            goals_reached: 0,
+            goals: 3,
+            score,
+        }
+    }
+
+    #[test]
+    fn goals_count_only_when_reached_by_the_deadline() {
+        let mut campaign = scenario::default_campaign();
+        let reached = campaign.check(1500, 8);
+        assert_eq!(reached.len(), 1);
+        assert_eq!(reached[0].state, GoalState::Reached { day: 8 });
+
+        // Still short of the second goal on its last day
+        assert!(campaign.check(4000, 20).is_empty());
+        // Rich enough the day after, but too late
+        let failed = campaign.check(6000, 21);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].state, GoalState::Failed);
+        assert!(!campaign.is_over());
+
+        campaign.check(0, 31);
+        assert!(campaign.is_over());
+        let end = campaign.results(-200, 31);
+        assert_eq!(
+            (end.goals_reached, end.goals, end.score),
+            (1, 3, GOAL_BONUS)
+        );
+    }
+
+    #[test]
+    fn a_loaded_campaign_keeps_how_the_goals_went() {
+        let mut campaign = scenario::default_campaign();
+        campaign.check(1200, 4);
+        campaign.check(0, 25);
+
+        let mut writer = SaveWriter::new();
+        campaign.save(&mut writer);
+        let mut loaded = scenario::default_campaign();
+        for record in save::parse(&writer.finish()) {
+            assert!(loaded.load(&record).unwrap());
+        }
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", campaign));
+    }
+
+    #[test]
+    fn the_table_keeps_the_best_scores_of_each_scenario() {
+        let mut scores = HighScores::default();
+        for score in 1..=HIGH_SCORES_KEPT as i64 {
+            assert!(scores.record(results(score * 100)).is_some());
+        }
+        assert_eq!(scores.record(results(50)), None);
+        assert_eq!(scores.record(results(550)), Some(6));
+
+        let kept: Vec<_> = scores
+            .for_scenario("Harbor Trader")
+            .map(|r| r.score)
+            .collect();
+        assert_eq!(kept.len(), HIGH_SCORES_KEPT);
+        assert_eq!((kept[0], kept[HIGH_SCORES_KEPT - 1]), (1000, 200));
+
+        let other = Results {
+            scenario: "Manor Heir".to_string(),
+            ..results(10)
+        };
+        assert_eq!(scores.record(other), Some(1));
+        assert_eq!(
+            scores.for_scenario("Harbor Trader").count(),
+            HIGH_SCORES_KEPT
+        );
+    }
+}
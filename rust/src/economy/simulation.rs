@@ -0,0 +1,296 @@
+use super::{
+    clock::{GameTime, HOURS_PER_DAY},
+    market::Market,
+    rng::SimRng,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+};
+
+pub const TICK: GameTime = GameTime::from_hours(1);
+
+const PRICE_NOISE: f32 = 0.03;
+const PRICE_PULL: f32 = 0.1;
+const MIN_PRICE_FACTOR: f32 = 0.25;
+const MAX_PRICE_FACTOR: f32 = 4.;
+
+/// A temporary change to one market, or to some of its goods when `items`
+/// is not empty.
+#[derive(Debug, Clone)]
+pub struct MarketEvent {
+    pub name: String,
+    pub market: String,
+    pub items: Vec<String>,
+    pub price_factor: f32,
+    pub demand_factor: f32,
+    pub ends_at: GameTime,
+}
+
+impl MarketEvent {
+    pub fn affects(&self, market: &str, item: &str) -> bool {
+        self.market == market && (self.items.is_empty() || self.items.iter().any(|i| i == item))
+    }
+}
+
+/// Advances every market in fixed one hour ticks. The same seed and the same
+/// calls always give the same markets.
+#[derive(Debug, Clone)]
+pub struct MarketSimulation {
+    seed: u64,
+    rng: SimRng,
+    time: GameTime,
+    // Time passed that is not yet a whole tick
+    pending: GameTime,
+    markets: Vec<Market>,
+    events: Vec<MarketEvent>,
+}
+
+impl MarketSimulation {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: SimRng::new(seed),
+            time: GameTime::ZERO,
+            pending: GameTime::ZERO,
+            markets: vec![],
+            events: vec![],
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn time(&self) -> GameTime {
+        self.time
+    }
+
+    pub fn rng(&mut self) -> &mut SimRng {
+        &mut self.rng
+    }
+
+    pub fn add_market(&mut self, market: Market) {
+        self.markets.push(market);
+    }
+
+    pub fn markets(&self) -> &[Market] {
+        &self.markets
+    }
+
+    pub fn market(&self, name: &str) -> Option<&Market> {
+        self.markets.iter().find(|m| m.name == name)
+    }
+
+    pub fn market_mut(&mut self, name: &str) -> Option<&mut Market> {
+        self.markets.iter_mut().find(|m| m.name == name)
+    }
+
+    pub fn events(&self) -> &[MarketEvent] {
+        &self.events
+    }
+
+    pub fn add_event(&mut self, event: MarketEvent) {
+        self.events.push(event);
+    }
+
+    // Advances the markets by `duration`, returns the number of ticks run
+    pub fn advance(&mut self, duration: GameTime) -> u64 {
+        self.advance_with(duration, |_| {})
+    }
+
+    // Same as `advance`, calling `on_tick` after every tick so other systems
+    // can step along with the markets
+    pub fn advance_with(
+        &mut self,
+        duration: GameTime,
+        mut on_tick: impl FnMut(&mut MarketSimulation),
+    ) -> u64 {
+        self.pending += duration;
+
+        let mut ticks = 0;
+        while self.pending >= TICK {
+            self.pending = self.pending - TICK;
+            self.tick();
+            on_tick(self);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    fn tick(&mut self) {
+        self.time += TICK;
+        self.events.retain(|e| e.ends_at > self.time);
+
+        let per_tick = TICK.hours() as f32 / HOURS_PER_DAY as f32;
+
+        for market in self.markets.iter_mut() {
+            market.restock(self.time, &mut self.rng);
+
+            for good in market.goods.iter_mut() {
+                let (price_factor, demand_factor) = self
+                    .events
+                    .iter()
+                    .filter(|e| e.affects(&market.name, &good.item))
+                    .fold((1., 1.), |(p, d), e| {
+                        (p * e.price_factor, d * e.demand_factor)
+                    });
+
+                // NPC purchases
+                let bought = roll_units(&mut self.rng, good.demand * demand_factor * per_tick);
+                good.stock = good.stock.saturating_sub(bought);
+
+                // Price drift towards what the stock level and events call for
+                let target = good.scarcity_factor() * price_factor;
+
+                good.price_factor += (target - good.price_factor) * PRICE_PULL;
+                good.price_factor *= 1. + self.rng.spread(PRICE_NOISE);
+                good.price_factor = good.price_factor.clamp(MIN_PRICE_FACTOR, MAX_PRICE_FACTOR);
+            }
+        }
+    }
+}
+
+// The markets themselves come from the scenario, only what changed as time
+// went by is saved
+impl Persist for MarketSimulation {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record(
+            "simulation",
+            &[
+                &self.rng.state(),
+                &self.time.minutes(),
+                &self.pending.minutes(),
+            ],
+        );
+        for m in self.markets.iter() {
+            for g in m.goods.iter() {
+                writer.record(
+                    "market_good",
+                    &[&m.name, &g.item, &g.stock, &g.price_factor, &g.misprice],
+                );
+            }
+            for r in m.restock_rules.iter() {
+                writer.record("restock", &[&m.name, &r.item, &r.next_at.minutes()]);
+            }
+        }
+        for e in self.events.iter() {
+            let ends_at = e.ends_at.minutes();
+            let mut fields: Vec<&dyn std::fmt::Display> = vec![
+                &e.name,
+                &e.market,
+                &e.price_factor,
+                &e.demand_factor,
+                &ends_at,
+            ];
+            fields.extend(e.items.iter().map(|i| i as &dyn std::fmt::Display));
+            writer.record("market_event", &fields);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "simulation" => {
+                self.rng = SimRng::new(record.get(0)?);
+                self.time = GameTime::from_minutes(record.get(1)?);
+                self.pending = GameTime::from_minutes(record.get(2)?);
+                self.events.clear();
+            }
+            // Goods the scenario no longer has are dropped
+            "market_good" => {
+                let item = record.str(1)?;
+                let good = self
+                    .market_mut(record.str(0)?)
+                    .and_then(|m| m.good_mut(item));
+                if let Some(good) = good {
+                    good.stock = record.get(2)?;
+                    good.price_factor = record.get(3)?;
+                    good.misprice = record.get(4)?;
+                }
+            }
+            "restock" => {
+                let item = record.str(1)?;
+                let rule = self
+                    .market_mut(record.str(0)?)
+                    .and_then(|m| m.restock_rules.iter_mut().find(|r| r.item == item));
+                if let Some(rule) = rule {
+                    rule.next_at = GameTime::from_minutes(record.get(2)?);
+                }
+            }
+            "market_event" => self.events.push(MarketEvent {
+                name: record.str(0)?.to_string(),
+                market: record.str(1)?.to_string(),
+                price_factor: record.get(2)?,
+                demand_factor: record.get(3)?,
+                ends_at: GameTime::from_minutes(record.get(4)?),
+                items: record.fields.iter().skip(5).cloned().collect(),
+            }),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+// Turns an expected amount like 2.3 into 2 or 3 units
+fn roll_units(rng: &mut SimRng, expected: f32) -> u32 {
+    let whole = expected.floor();
+    whole as u32 + rng.chance(expected - whole) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::scenario;
+
+    fn snapshot(sim: &MarketSimulation) -> Vec<(String, u32, u32)> {
+        sim.markets()
+            .iter()
+            .flat_map(|m| m.goods.iter().map(|g| (g.item.clone(), g.stock, g.price())))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_markets() {
+        let mut a = scenario::default_world(7);
+        let mut b = scenario::default_world(7);
+        for _ in 0..10 {
+            a.advance(GameTime::from_hours(13));
+            b.advance(GameTime::from_hours(13));
+            assert_eq!(snapshot(&a), snapshot(&b));
+        }
+        assert_eq!(a.time(), b.time());
+    }
+
+    #[test]
+    fn a_loaded_world_goes_on_like_the_saved_one() {
+        let mut a = scenario::default_world(3);
+        a.advance(GameTime::from_hours(50));
+        a.add_event(MarketEvent {
+            name: "Storm".to_string(),
+            market: a.markets()[0].name.clone(),
+            items: vec![],
+            price_factor: 1.5,
+            demand_factor: 0.5,
+            ends_at: a.time() + GameTime::from_days(2),
+        });
+        let mut writer = SaveWriter::new();
+        a.save(&mut writer);
+
+        let mut b = scenario::default_world(3);
+        for record in crate::economy::save::parse(&writer.finish()) {
+            assert!(b.load(&record).unwrap());
+        }
+        assert_eq!(a.time(), b.time());
+        for _ in 0..5 {
+            a.advance(GameTime::from_hours(13));
+            b.advance(GameTime::from_hours(13));
+            assert_eq!(snapshot(&a), snapshot(&b));
+        }
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let mut a = scenario::default_world(1);
+        let mut b = scenario::default_world(2);
+        a.advance(GameTime::from_days(5));
+        b.advance(GameTime::from_days(5));
+        assert_ne!(snapshot(&a), snapshot(&b));
+    }
+}
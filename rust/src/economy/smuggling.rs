@@ -0,0 +1,147 @@
+use std::fmt;
+
+use super::rng::SimRng;
+
+// Guards search some travellers even when they carry nothing forbidden
+const BASE_INSPECTION: f32 = 0.05;
+const MAX_INSPECTION: f32 = 0.9;
+// What a black market pays over the going price for goods nobody else takes
+pub const BLACK_MARKET_PREMIUM: f32 = 1.6;
+
+/// What happened to the player at a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inspection {
+    Waved,
+    // Searched without finding anything forbidden
+    Searched,
+    Fined { fine: u32 },
+    Confiscated { fine: u32 },
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inspection::Waved => write!(f, "The guards wave you through"),
+            Inspection::Searched => write!(f, "The guards search your bags and find nothing"),
+            Inspection::Fined { fine } => {
+                write!(f, "The guards find contraband and fine you {fine} coins")
+            }
+            Inspection::Confiscated { fine } => write!(
+                f,
+                "The guards seize your contraband and fine you {fine} coins"
+            ),
+        }
+    }
+}
+
+/// Guards on the road between two towns. The more contraband the player
+/// carries, by weight, the likelier they are to be searched.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub name: String,
+    pub towns: (String, String),
+    pub inspection_per_weight: f32,
+    pub fine_per_weight: u32,
+    // Chance that found goods are seized on top of the fine
+    pub confiscate_chance: f32,
+}
+
+impl Checkpoint {
+    pub fn new(name: &str, from: &str, to: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            towns: (from.to_string(), to.to_string()),
+            inspection_per_weight: 0.05,
+            fine_per_weight: 20,
+            confiscate_chance: 0.5,
+        }
+    }
+
+    pub fn with_strictness(mut self, inspection_per_weight: f32, confiscate_chance: f32) -> Self {
+        self.inspection_per_weight = inspection_per_weight;
+        self.confiscate_chance = confiscate_chance;
+        self
+    }
+
+    pub fn with_fine(mut self, fine_per_weight: u32) -> Self {
+        self.fine_per_weight = fine_per_weight;
+        self
+    }
+
+    pub fn connects(&self, a: &str, b: &str) -> bool {
+        (self.towns.0 == a && self.towns.1 == b) || (self.towns.0 == b && self.towns.1 == a)
+    }
+
+    pub fn inspection_chance(&self, contraband_weight: f32) -> f32 {
+        (BASE_INSPECTION + contraband_weight.max(0.) * self.inspection_per_weight)
+            .min(MAX_INSPECTION)
+    }
+
+    pub fn inspect(&self, contraband_weight: f32, rng: &mut SimRng) -> Inspection {
+        if !rng.chance(self.inspection_chance(contraband_weight)) {
+            return Inspection::Waved;
+        }
+        if contraband_weight <= 0. {
+            return Inspection::Searched;
+        }
+
+        let fine = (contraband_weight * self.fine_per_weight as f32).ceil() as u32;
+        if rng.chance(self.confiscate_chance) {
+            Inspection::Confiscated { fine }
+        } else {
+            Inspection::Fined { fine }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_contraband_draws_more_searches() {
+        let gate = Checkpoint::new("Gate", "Harbor", "Village").with_strictness(0.1, 0.5);
+        assert_eq!(gate.inspection_chance(0.), BASE_INSPECTION);
+        let chances: Vec<f32> = [1., 2., 4., 8.]
+            .into_iter()
+            .map(|w| gate.inspection_chance(w))
+            .collect();
+        assert!(chances.windows(2).all(|w| w[0] < w[1]), "{chances:?}");
+        assert!((chances[1] - (BASE_INSPECTION + 0.2)).abs() < 1e-6);
+        assert_eq!(gate.inspection_chance(100.), MAX_INSPECTION);
+    }
+
+    #[test]
+    fn guards_fine_by_weight_and_find_nothing_on_honest_traders() {
+        let gate = Checkpoint::new("Gate", "Harbor", "Village")
+            .with_strictness(1., 0.)
+            .with_fine(15);
+        let mut rng = SimRng::new(3);
+        let mut searched = 0;
+        for _ in 0..200 {
+            match gate.inspect(0., &mut rng) {
+                Inspection::Waved => {}
+                Inspection::Searched => searched += 1,
+                other => panic!("{other:?} with nothing to find"),
+            }
+        }
+        assert!(searched > 0 && searched < 30, "{searched}");
+
+        // Heavy enough that every search finds something, and nothing is seized
+        for _ in 0..50 {
+            match gate.inspect(2.5, &mut rng) {
+                Inspection::Waved => {}
+                Inspection::Fined { fine } => assert_eq!(fine, 38),
+                other => panic!("{other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn checkpoints_guard_the_road_both_ways() {
+        let gate = Checkpoint::new("Gate", "Harbor", "Village");
+        assert!(gate.connects("Harbor", "Village"));
+        assert!(gate.connects("Village", "Harbor"));
+        assert!(!gate.connects("Harbor", "Manor"));
+    }
+}
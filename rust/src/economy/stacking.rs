@@ -0,0 +1,102 @@
+// Sizes of the stacks `quantity` units make, none above `max_stacks`
+pub fn chunks(quantity: i64, max_stacks: i64) -> Vec<i64> {
+    let max_stacks = max_stacks.max(1);
+    let mut chunks = vec![];
+    let mut remaining = quantity;
+    while remaining > 0 {
+        let stacks = remaining.min(max_stacks);
+        chunks.push(stacks);
+        remaining -= stacks;
+    }
+    chunks
+}
+
+// How `incoming` units land in the bags when the stack they stack with holds
+// `on_stack`: how many top it up, and the new stacks the rest start
+pub fn fill(on_stack: Option<i64>, incoming: i64, max_stacks: i64) -> (i64, Vec<i64>) {
+    let room = on_stack.map_or(0, |stacks| (max_stacks - stacks).max(0));
+    let topped_up = incoming.clamp(0, room);
+    (topped_up, chunks(incoming - topped_up, max_stacks))
+}
+
+// How many of the `fake_units` among `stacks` go along when `taken` of them
+// leave, in proportion and never more or fewer than can be
+pub fn fakes_among(stacks: i64, fake_units: i64, taken: i64) -> i64 {
+    if stacks <= 0 || taken <= 0 {
+        return 0;
+    }
+    let taken = taken.min(stacks);
+    let share = (fake_units * taken + stacks / 2) / stacks;
+    share.clamp(
+        (fake_units - (stacks - taken)).max(0),
+        fake_units.min(taken),
+    )
+}
+
+// Deals the fakes among some units out to the stacks they're split into
+pub struct FakeShare {
+    units: i64,
+    fakes: i64,
+}
+
+impl FakeShare {
+    pub fn new(units: i64, fakes: i64) -> Self {
+        Self {
+            units: units.max(0),
+            fakes: fakes.clamp(0, units.max(0)),
+        }
+    }
+
+    // How many fakes go along with the next `taken` units
+    pub fn take(&mut self, taken: i64) -> i64 {
+        let fakes = fakes_among(self.units, self.fakes, taken);
+        self.units -= taken.clamp(0, self.units);
+        self.fakes -= fakes;
+        fakes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bulk_order_tops_up_the_partial_stack_first() {
+        let (topped_up, new_stacks) = fill(Some(7), 30, 10);
+        assert_eq!(topped_up, 3);
+        assert_eq!(new_stacks, vec![10, 10, 7]);
+        assert_eq!(topped_up + new_stacks.iter().sum::<i64>(), 30);
+    }
+
+    #[test]
+    fn a_full_or_missing_stack_takes_nothing() {
+        assert_eq!(fill(Some(10), 4, 10), (0, vec![4]));
+        assert_eq!(fill(None, 25, 10), (0, vec![10, 10, 5]));
+        assert_eq!(fill(Some(2), 5, 10), (5, vec![]));
+    }
+
+    #[test]
+    fn chunks_never_pass_the_maximum() {
+        assert_eq!(chunks(0, 99), Vec::<i64>::new());
+        assert_eq!(chunks(5, 1), vec![1; 5]);
+        assert_eq!(chunks(200, 99), vec![99, 99, 2]);
+    }
+
+    #[test]
+    fn fakes_leave_a_stack_in_proportion() {
+        assert_eq!(fakes_among(10, 5, 4), 2);
+        assert_eq!(fakes_among(10, 0, 4), 0);
+        assert_eq!(fakes_among(10, 10, 3), 3);
+        // Every fake goes once more leave than there are genuine units
+        assert_eq!(fakes_among(10, 9, 10), 9);
+        assert_eq!(fakes_among(3, 1, 2), 1);
+    }
+
+    #[test]
+    fn every_fake_lands_in_some_stack() {
+        let mut share = FakeShare::new(25, 7);
+        let dealt: Vec<_> = chunks(25, 10).into_iter().map(|s| share.take(s)).collect();
+        assert_eq!(dealt, vec![3, 3, 1]);
+        assert_eq!(dealt.iter().sum::<i64>(), 7);
+    }
+}
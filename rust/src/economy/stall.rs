@@ -0,0 +1,554 @@
+use std::fmt;
+
+use super::{
+    appraisal::Estimate,
+    auction,
+    clock::GameTime,
+    freshness,
+    rng::SimRng,
+    save::{Persist, SaveError, SaveRecord, SaveWriter},
+    simulation::{MarketSimulation, TICK},
+};
+
+// Chance per hour that a customer walks up to an open stall
+const CUSTOMER_CHANCE: f32 = 0.35;
+// How much a customer may like or dislike an item compared to its market price
+const MIN_APPETITE: f32 = 0.7;
+const MAX_APPETITE: f32 = 1.4;
+// Share of customers who try to talk the price down instead of walking away
+const HAGGLER_CHANCE: f32 = 0.4;
+// A haggler won't try when the asking price is this far above what they'd pay
+const HAGGLE_REACH: f32 = 1.3;
+// Chance a customer sees through a fake and walks away
+const SPOT_FAKE_CHANCE: f32 = 0.3;
+// How much likelier a customer looks at the goods they fancy than at the rest
+const FANCY_WEIGHT: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallError {
+    NotRented,
+    UnknownSlot,
+    SlotTaken,
+    EmptySlot,
+    InvalidQuantity,
+}
+
+impl fmt::Display for StallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StallError::NotRented => write!(f, "the stall is not rented"),
+            StallError::UnknownSlot => write!(f, "no such display slot"),
+            StallError::SlotTaken => write!(f, "display slot is already used"),
+            StallError::EmptySlot => write!(f, "display slot is empty"),
+            StallError::InvalidQuantity => write!(f, "quantity must be above zero"),
+        }
+    }
+}
+
+impl std::error::Error for StallError {}
+
+/// Items the player put on display, `floor` is the lowest a haggler can
+/// talk them down to. The item's hidden value travels with it.
+#[derive(Debug, Clone)]
+pub struct Display {
+    pub item: String,
+    pub quantity: u32,
+    pub asking: u32,
+    pub floor: u32,
+    pub true_value: u32,
+    // Of the units, passed off as the real thing
+    pub fake_units: u32,
+    // What the player made of it, kept for when it comes back off the stall
+    pub estimate: Option<Estimate>,
+    pub known_fake: bool,
+    // Goods on a stall spoil as fast as in the player's bags
+    pub freshness: f32,
+    pub shelf_life: GameTime,
+    pub unit_cost: u32,
+}
+
+/// Someone who may walk up to a stall. They look at the goods they fancy
+/// first, and come with at most `purse` coins to spend.
+#[derive(Debug, Clone)]
+pub struct Customer {
+    pub name: String,
+    pub purse: u32,
+    pub fancies: Vec<String>,
+}
+
+impl Customer {
+    pub fn new(name: &str, purse: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            purse,
+            fancies: vec![],
+        }
+    }
+
+    pub fn with_fancy(mut self, item: &str) -> Self {
+        self.fancies.push(item.to_string());
+        self
+    }
+
+    pub fn fancies(&self, item: &str) -> bool {
+        self.fancies.iter().any(|i| i == item)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StallSale {
+    pub item: String,
+    pub customer: String,
+    pub price: u32,
+    pub haggled: bool,
+    pub at: GameTime,
+}
+
+impl fmt::Display for StallSale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {} {:02}:00 {} bought {} for {}",
+            self.at.day(),
+            self.at.hour_of_day(),
+            self.customer,
+            self.item,
+            self.price
+        )?;
+        if self.haggled {
+            write!(f, " after haggling")?;
+        }
+        Ok(())
+    }
+}
+
+/// A stall the player can rent in a town. Customers pay into the till,
+/// which the player empties along with the sales report when they come back.
+#[derive(Debug, Clone)]
+pub struct Stall {
+    pub town: String,
+    pub rent_per_day: u32,
+    rng: SimRng,
+    rented_until: GameTime,
+    // Come from the scenario, they aren't saved
+    customers: Vec<Customer>,
+    slots: Vec<Option<Display>>,
+    till: u32,
+    sales: Vec<StallSale>,
+}
+
+impl Stall {
+    pub fn new(town: &str, rent_per_day: u32, slots: usize, seed: u64) -> Self {
+        Self {
+            town: town.to_string(),
+            rent_per_day,
+            rng: SimRng::new(seed),
+            rented_until: GameTime::ZERO,
+            customers: vec![],
+            slots: vec![None; slots],
+            till: 0,
+            sales: vec![],
+        }
+    }
+
+    pub fn with_customer(mut self, customer: Customer) -> Self {
+        self.customers.push(customer);
+        self
+    }
+
+    pub fn slots(&self) -> &[Option<Display>] {
+        &self.slots
+    }
+
+    pub fn rented_until(&self) -> GameTime {
+        self.rented_until
+    }
+
+    pub fn is_rented(&self, now: GameTime) -> bool {
+        now < self.rented_until
+    }
+
+    pub fn till(&self) -> u32 {
+        self.till
+    }
+
+    pub fn sales(&self) -> &[StallSale] {
+        &self.sales
+    }
+
+    // Extends the lease by `days` from now or from the end of the current
+    // one, returns the rent to charge
+    pub fn rent(&mut self, days: u32, now: GameTime) -> u32 {
+        let start = self.rented_until.max(now);
+        self.rented_until = start + GameTime::from_days(days as u64);
+        self.rent_per_day * days
+    }
+
+    pub fn place(
+        &mut self,
+        slot: usize,
+        display: Display,
+        now: GameTime,
+    ) -> Result<(), StallError> {
+        if !self.is_rented(now) {
+            return Err(StallError::NotRented);
+        }
+        if display.quantity == 0 {
+            return Err(StallError::InvalidQuantity);
+        }
+        let slot = self.slots.get_mut(slot).ok_or(StallError::UnknownSlot)?;
+        if slot.is_some() {
+            return Err(StallError::SlotTaken);
+        }
+
+        *slot = Some(display);
+        Ok(())
+    }
+
+    // Goods can be taken back even after the lease ran out
+    pub fn take_back(&mut self, slot: usize) -> Result<Display, StallError> {
+        self.slots
+            .get_mut(slot)
+            .ok_or(StallError::UnknownSlot)?
+            .take()
+            .ok_or(StallError::EmptySlot)
+    }
+
+    // Empties the till and the report of what sold since the last visit
+    pub fn collect(&mut self) -> (u32, Vec<StallSale>) {
+        (
+            std::mem::take(&mut self.till),
+            std::mem::take(&mut self.sales),
+        )
+    }
+
+    // Lets a customer visit the stall for one hour
+    pub fn tick(&mut self, now: GameTime, sim: &MarketSimulation) {
+        for display in self.slots.iter_mut().flatten() {
+            display.freshness = freshness::decay(display.freshness, display.shelf_life, TICK, 1.);
+        }
+        if !self.is_rented(now) || self.slots.iter().all(|s| s.is_none()) {
+            return;
+        }
+        if self.customers.is_empty() || !self.rng.chance(CUSTOMER_CHANCE) {
+            return;
+        }
+
+        let customer =
+            self.customers[self.rng.range(0, self.customers.len() as u32 - 1) as usize].clone();
+        let is_haggler = self.rng.chance(HAGGLER_CHANCE);
+        // What they brought along today
+        let budget = self.rng.range(customer.purse / 2, customer.purse);
+
+        // The customer has an eye for one of the displayed goods, most
+        // likely one they fancy
+        let shown: Vec<(usize, u32)> = (0..self.slots.len())
+            .filter_map(|i| {
+                let display = self.slots[i].as_ref()?;
+                let weight = if customer.fancies(&display.item) {
+                    FANCY_WEIGHT
+                } else {
+                    1
+                };
+                Some((i, weight))
+            })
+            .collect();
+        let total: u32 = shown.iter().map(|(_, weight)| weight).sum();
+        let mut roll = self.rng.range(0, total - 1);
+        let mut slot = shown[0].0;
+        for (i, weight) in shown {
+            if roll < weight {
+                slot = i;
+                break;
+            }
+            roll -= weight;
+        }
+        let Some(display) = self.slots[slot].as_mut() else {
+            return;
+        };
+        // Fakes sell among the real units, one in so many
+        let is_fake = display.fake_units > 0
+            && self
+                .rng
+                .chance(display.fake_units as f32 / display.quantity.max(1) as f32);
+        if is_fake && self.rng.chance(SPOT_FAKE_CHANCE) {
+            return;
+        }
+
+        let appetite = MIN_APPETITE + self.rng.next_f32() * (MAX_APPETITE - MIN_APPETITE);
+        // Customers go by what the item sells for, not what it's really worth
+        let market_price = match town_price(sim, &self.town, &display.item) {
+            0 => display.true_value,
+            price => price,
+        };
+        // Nobody pays full price for yesterday's fish, or more than they have
+        let valuation = freshness::price(
+            (market_price as f32 * appetite).round() as u32,
+            display.freshness,
+        )
+        .min(budget);
+
+        let (price, haggled) = if valuation >= display.asking {
+            (display.asking, false)
+        } else if is_haggler
+            && display.asking as f32 <= valuation as f32 * HAGGLE_REACH
+            && valuation >= display.floor
+        {
+            (valuation, true)
+        } else {
+            return;
+        };
+
+        display.quantity -= 1;
+        if is_fake {
+            display.fake_units -= 1;
+        }
+        self.till += price;
+        self.sales.push(StallSale {
+            item: display.item.clone(),
+            customer: customer.name,
+            price,
+            haggled,
+            at: now,
+        });
+        if display.quantity == 0 {
+            self.slots[slot] = None;
+        }
+    }
+}
+
+// What the item costs in the town's own market, or the cheapest elsewhere
+// when the town doesn't sell it
+pub fn town_price(sim: &MarketSimulation, town: &str, item: &str) -> u32 {
+    sim.markets()
+        .iter()
+        .filter(|m| m.town == town)
+        .find_map(|m| m.good(item))
+        .map_or_else(|| auction::market_price(sim, item), |g| g.price())
+}
+
+impl Persist for Stall {
+    fn save(&self, writer: &mut SaveWriter) {
+        writer.record(
+            "stall",
+            &[
+                &self.town,
+                &self.rng.state(),
+                &self.rented_until.minutes(),
+                &self.till,
+            ],
+        );
+        for (i, slot) in self.slots.iter().enumerate() {
+            if let Some(d) = slot {
+                let (low, high) = d.estimate.map_or((0, 0), |e| (e.low, e.high));
+                writer.record(
+                    "stall_slot",
+                    &[
+                        &self.town,
+                        &i,
+                        &d.item,
+                        &d.quantity,
+                        &d.asking,
+                        &d.floor,
+                        &d.true_value,
+                        &d.fake_units,
+                        &low,
+                        &high,
+                        &d.known_fake,
+                        &d.freshness,
+                        &d.unit_cost,
+                        &d.shelf_life.minutes(),
+                    ],
+                );
+            }
+        }
+        for s in self.sales.iter() {
+            writer.record(
+                "stall_sale",
+                &[
+                    &self.town,
+                    &s.item,
+                    &s.customer,
+                    &s.price,
+                    &s.haggled,
+                    &s.at.minutes(),
+                ],
+            );
+        }
+    }
+
+    // Every stall only claims the records of its own town
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        let is_stall = matches!(record.kind.as_str(), "stall" | "stall_slot" | "stall_sale");
+        if !is_stall || record.str(0)? != self.town {
+            return Ok(false);
+        }
+
+        match record.kind.as_str() {
+            "stall" => {
+                self.rng = SimRng::new(record.get(1)?);
+                self.rented_until = GameTime::from_minutes(record.get(2)?);
+                self.till = record.get(3)?;
+            }
+            "stall_slot" => {
+                let slot = self
+                    .slots
+                    .get_mut(record.get::<usize>(1)?)
+                    .ok_or_else(|| record.error("bad slot"))?;
+                *slot = Some(Display {
+                    item: record.str(2)?.to_string(),
+                    quantity: record.get(3)?,
+                    asking: record.get(4)?,
+                    floor: record.get(5)?,
+                    true_value: record.get(6)?,
+                    fake_units: record.get(7)?,
+                    estimate: match (record.get(8)?, record.get(9)?) {
+                        (_, 0) => None,
+                        (low, high) => Some(Estimate { low, high }),
+                    },
+                    known_fake: record.get(10)?,
+                    freshness: record.get(11)?,
+                    unit_cost: record.get(12)?,
+                    shelf_life: GameTime::from_minutes(record.get(13)?),
+                });
+            }
+            _ => self.sales.push(StallSale {
+                item: record.str(1)?.to_string(),
+                customer: record.str(2)?.to_string(),
+                price: record.get(3)?,
+                haggled: record.get(4)?,
+                at: GameTime::from_minutes(record.get(5)?),
+            }),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::scenario;
+
+    // Silver cups go for 60 in the harbor, customers value them at 42 to 84
+    fn cups(asking: u32, floor: u32) -> Display {
+        Display {
+            item: "Silver Cup".to_string(),
+            quantity: 500,
+            asking,
+            floor,
+            true_value: 60,
+            fake_units: 0,
+            estimate: None,
+            known_fake: false,
+            freshness: freshness::FRESH,
+            shelf_life: GameTime::ZERO,
+            unit_cost: 30,
+        }
+    }
+
+    fn open_stall(customer: Customer, displays: Vec<Display>) -> (Stall, MarketSimulation) {
+        let sim = scenario::default_world(5);
+        let mut stall = Stall::new("Harbor", 10, 2, 5).with_customer(customer);
+        stall.rent(60, sim.time());
+        for (slot, display) in displays.into_iter().enumerate() {
+            stall.place(slot, display, sim.time()).unwrap();
+        }
+        (stall, sim)
+    }
+
+    // Prices stay put, only the stall's own clock moves
+    fn run(stall: &mut Stall, sim: &MarketSimulation, hours: u64) {
+        let mut now = sim.time();
+        for _ in 0..hours {
+            now += TICK;
+            stall.tick(now, sim);
+        }
+    }
+
+    #[test]
+    fn a_customer_who_likes_the_price_pays_it() {
+        let (mut stall, sim) = open_stall(Customer::new("a scholar", 500), vec![cups(40, 40)]);
+        run(&mut stall, &sim, 200);
+        assert!(!stall.sales().is_empty());
+        assert!(stall.sales().iter().all(|s| s.price == 40 && !s.haggled));
+        assert_eq!(stall.till(), 40 * stall.sales().len() as u32);
+        let left = stall.slots()[0].as_ref().unwrap().quantity;
+        assert_eq!(left as usize, 500 - stall.sales().len());
+    }
+
+    #[test]
+    fn hagglers_never_go_below_the_floor() {
+        let (mut stall, sim) = open_stall(Customer::new("a scholar", 500), vec![cups(100, 80)]);
+        run(&mut stall, &sim, 2000);
+        assert!(!stall.sales().is_empty());
+        for sale in stall.sales() {
+            assert!(sale.haggled);
+            assert!((80..100).contains(&sale.price), "sold for {}", sale.price);
+        }
+    }
+
+    #[test]
+    fn nobody_buys_what_is_priced_out_of_reach() {
+        let (mut stall, sim) = open_stall(Customer::new("a scholar", 500), vec![cups(200, 150)]);
+        run(&mut stall, &sim, 500);
+        assert!(stall.sales().is_empty());
+        assert_eq!(stall.slots()[0].as_ref().unwrap().quantity, 500);
+
+        // The cups are cheap enough, but not for what this one carries
+        let (mut stall, sim) = open_stall(Customer::new("a farmer", 30), vec![cups(40, 40)]);
+        run(&mut stall, &sim, 500);
+        assert!(stall.sales().is_empty());
+    }
+
+    #[test]
+    fn a_purse_caps_what_a_haggler_offers() {
+        let (mut stall, sim) = open_stall(Customer::new("a cook", 50), vec![cups(60, 20)]);
+        run(&mut stall, &sim, 1000);
+        assert!(stall.sales().iter().any(|s| s.haggled));
+        assert!(stall.sales().iter().all(|s| s.price <= 50));
+    }
+
+    #[test]
+    fn goods_on_display_spoil_and_sell_for_less() {
+        let mut fish = cups(40, 40);
+        fish.shelf_life = GameTime::from_hours(24);
+        let (mut stall, sim) = open_stall(Customer::new("a cook", 0), vec![fish.clone()]);
+        run(&mut stall, &sim, 12);
+        let left = stall.slots()[0].as_ref().unwrap().freshness;
+        assert!((left - 0.5).abs() < 1e-4, "{left}");
+
+        // Spoiled before anyone comes by, nobody pays 40 for it any more
+        fish.shelf_life = TICK;
+        let (mut stall, sim) = open_stall(Customer::new("a scholar", 500), vec![fish]);
+        run(&mut stall, &sim, 500);
+        assert!(stall.sales().is_empty());
+    }
+
+    #[test]
+    fn customers_look_at_what_they_fancy_first() {
+        let mut fish = cups(1, 1);
+        fish.item = "Fish".to_string();
+        let customer = Customer::new("a scholar", 500).with_fancy("Silver Cup");
+        let (mut stall, sim) = open_stall(customer, vec![fish, cups(1, 1)]);
+        run(&mut stall, &sim, 1000);
+        let sold = |item: &str| stall.sales().iter().filter(|s| s.item == item).count();
+        assert!(sold("Silver Cup") > 2 * sold("Fish"), "{}", sold("Fish"));
+    }
+
+    #[test]
+    fn collecting_empties_the_till_and_the_report() {
+        let (mut stall, sim) = open_stall(Customer::new("a scholar", 500), vec![cups(40, 40)]);
+        run(&mut stall, &sim, 100);
+        let sold = stall.sales().len();
+        assert!(sold > 0);
+
+        let (till, sales) = stall.collect();
+        assert_eq!(till, 40 * sold as u32);
+        assert_eq!(sales.len(), sold);
+        assert!(sales.iter().all(|s| s.customer == "a scholar"));
+        assert!(sales.windows(2).all(|w| w[0].at < w[1].at));
+        let (till, sales) = stall.collect();
+        assert_eq!(till, 0);
+        assert!(sales.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use super::save::{Persist, SaveError, SaveRecord, SaveWriter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenceError {
+    NotRequired,
+    AlreadyHeld,
+}
+
+impl fmt::Display for LicenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenceError::NotRequired => write!(f, "no licence is needed for that here"),
+            LicenceError::AlreadyHeld => write!(f, "you already hold that licence"),
+        }
+    }
+}
+
+impl std::error::Error for LicenceError {}
+
+/// What a town charges traders. `sales_tax` is the share of every sale the
+/// town keeps, `listing_fee` is paid for each display put up on a stall.
+#[derive(Debug, Clone)]
+pub struct TownDues {
+    pub town: String,
+    pub sales_tax: f32,
+    pub listing_fee: u32,
+    // Item categories that may only be traded with a licence, and its price
+    pub licences: Vec<(String, u32)>,
+}
+
+impl TownDues {
+    pub fn new(town: &str, sales_tax: f32) -> Self {
+        Self {
+            town: town.to_string(),
+            sales_tax,
+            listing_fee: 0,
+            licences: vec![],
+        }
+    }
+
+    pub fn with_listing_fee(mut self, listing_fee: u32) -> Self {
+        self.listing_fee = listing_fee;
+        self
+    }
+
+    pub fn with_licence(mut self, category: &str, price: u32) -> Self {
+        self.licences.push((category.to_string(), price));
+        self
+    }
+}
+
+/// Every town's dues and the licences the player has bought.
+#[derive(Debug, Clone, Default)]
+pub struct TaxOffice {
+    towns: Vec<TownDues>,
+    // Share of the reserve the auction house charges to list a lot
+    pub auction_fee: f32,
+    pub min_auction_fee: u32,
+    held: Vec<(String, String)>,
+}
+
+impl TaxOffice {
+    pub fn new(auction_fee: f32, min_auction_fee: u32) -> Self {
+        Self {
+            auction_fee,
+            min_auction_fee,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_town(mut self, dues: TownDues) -> Self {
+        self.towns.push(dues);
+        self
+    }
+
+    pub fn dues(&self, town: &str) -> Option<&TownDues> {
+        self.towns.iter().find(|d| d.town == town)
+    }
+
+    // What the town keeps of a sale worth `amount`
+    pub fn sales_tax(&self, town: &str, amount: u32) -> u32 {
+        self.dues(town)
+            .map_or(0, |d| (amount as f32 * d.sales_tax).round() as u32)
+    }
+
+    pub fn listing_fee(&self, town: &str) -> u32 {
+        self.dues(town).map_or(0, |d| d.listing_fee)
+    }
+
+    pub fn auction_listing_fee(&self, reserve: u32) -> u32 {
+        ((reserve as f32 * self.auction_fee).round() as u32).max(self.min_auction_fee)
+    }
+
+    // Price of the licence the town wants for the category, if any
+    pub fn licence_price(&self, town: &str, category: &str) -> Option<u32> {
+        self.dues(town)?
+            .licences
+            .iter()
+            .find(|(c, _)| c == category)
+            .map(|(_, price)| *price)
+    }
+
+    pub fn holds(&self, town: &str, category: &str) -> bool {
+        self.held.iter().any(|(t, c)| t == town && c == category)
+    }
+
+    pub fn may_trade(&self, town: &str, category: &str) -> bool {
+        self.licence_price(town, category).is_none() || self.holds(town, category)
+    }
+
+    // Licences the town sells that the player doesn't hold yet
+    pub fn missing_licences<'a>(
+        &'a self,
+        town: &'a str,
+    ) -> impl Iterator<Item = &'a (String, u32)> {
+        self.dues(town)
+            .into_iter()
+            .flat_map(|d| d.licences.iter())
+            .filter(move |(c, _)| !self.holds(town, c))
+    }
+
+    // Grants the licence, returns its price for the caller to charge
+    pub fn buy_licence(&mut self, town: &str, category: &str) -> Result<u32, LicenceError> {
+        let price = self
+            .licence_price(town, category)
+            .ok_or(LicenceError::NotRequired)?;
+        if self.holds(town, category) {
+            return Err(LicenceError::AlreadyHeld);
+        }
+        self.held.push((town.to_string(), category.to_string()));
+        Ok(price)
+    }
+}
+
+// Dues come from the scenario, the save only keeps the licences bought
+impl Persist for TaxOffice {
+    fn save(&self, writer: &mut SaveWriter) {
+        for (town, category) in self.held.iter() {
+            writer.record("licence", &[town, category]);
+        }
+    }
+
+    fn load(&mut self, record: &SaveRecord) -> Result<bool, SaveError> {
+        match record.kind.as_str() {
+            "licence" => self
+                .held
+                .push((record.str(0)?.to_string(), record.str(1)?.to_string())),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
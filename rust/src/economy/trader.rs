@@ -0,0 +1,164 @@
+use super::{
+    reputation::{self, StockTier},
+    simulation::MarketSimulation,
+};
+
+#[derive(Debug, Clone)]
+pub struct Cargo {
+    pub item: String,
+    pub quantity: u32,
+    // What each unit cost, kept fractional so the profit on the whole load
+    // comes out exact
+    pub unit_cost: f32,
+    pub origin: String,
+}
+
+impl Cargo {
+    // What the whole load cost
+    pub fn cost(&self) -> i64 {
+        (self.unit_cost * self.quantity as f32).round() as i64
+    }
+}
+
+/// Result of selling one cargo line, the route is `origin -> market`.
+#[derive(Debug, Clone)]
+pub struct Sale {
+    pub item: String,
+    pub origin: String,
+    pub market: String,
+    pub quantity: u32,
+    pub profit: i64,
+}
+
+/// A trader that sells everything it carries where it stands, then buys the
+/// single deal with the best spread to another market and heads there.
+#[derive(Debug, Clone)]
+pub struct Trader {
+    pub name: String,
+    pub funds: i64,
+    pub location: String,
+    pub capacity: u32,
+    pub cargo: Vec<Cargo>,
+}
+
+impl Trader {
+    pub fn new(name: &str, funds: i64, location: &str, capacity: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            funds,
+            location: location.to_string(),
+            capacity,
+            cargo: vec![],
+        }
+    }
+
+    pub fn carried(&self) -> u32 {
+        self.cargo.iter().map(|c| c.quantity).sum()
+    }
+
+    // Funds plus cargo valued at the current market's price
+    pub fn net_worth(&self, sim: &MarketSimulation) -> i64 {
+        let market = sim.market(&self.location);
+        let cargo_value: i64 = self
+            .cargo
+            .iter()
+            .map(|c| {
+                let price = market
+                    .and_then(|m| m.good(&c.item))
+                    .map_or(c.unit_cost.round() as u32, |g| g.price());
+                price as i64 * c.quantity as i64
+            })
+            .sum();
+
+        self.funds + cargo_value
+    }
+
+    pub fn sell_all(&mut self, sim: &mut MarketSimulation) -> Vec<Sale> {
+        let mut sales = vec![];
+        let market = match sim.market_mut(&self.location) {
+            Some(market) => market,
+            None => return sales,
+        };
+
+        self.cargo.retain(|c| {
+            if c.origin == market.name {
+                return true;
+            }
+            match market.sell(&c.item, c.quantity, 0, None, false) {
+                Ok(total) => {
+                    self.funds += total as i64;
+                    sales.push(Sale {
+                        item: c.item.clone(),
+                        origin: c.origin.clone(),
+                        market: market.name.clone(),
+                        quantity: c.quantity,
+                        profit: total as i64 - c.cost(),
+                    });
+                    false
+                }
+                Err(_) => true,
+            }
+        });
+
+        sales
+    }
+
+    // Buys the most profitable load for a single hop once `trip_cost` to the
+    // destination market is paid, returns the destination
+    pub fn buy_best_deal(
+        &mut self,
+        sim: &mut MarketSimulation,
+        trip_cost: impl Fn(&str) -> i64,
+    ) -> Option<String> {
+        let here = sim.market(&self.location)?;
+        let room = self.capacity.saturating_sub(self.carried());
+
+        let mut best: Option<(String, String, u32, i64)> = None;
+        for good in here.goods.iter().filter(|g| g.tier == StockTier::Common) {
+            for market in sim.markets().iter().filter(|m| m.name != here.name) {
+                let Some(other) = market.good(&good.item) else {
+                    continue;
+                };
+                // Keeps back what the trip costs
+                let fare = trip_cost(&market.name);
+                let budget = (self.funds - fare).max(0) as u32;
+                let most = good
+                    .stock
+                    .min(room)
+                    .min(here.affordable(&good.item, 0, budget));
+
+                // Both prices move against the trader with every unit, so
+                // stop at the first one that no longer pays for itself
+                let (mut quantity, mut profit) = (0, 0);
+                while quantity < most {
+                    let cost = reputation::buy_price(good.impact_price(quantity), 0);
+                    let revenue =
+                        reputation::sell_price(other.glut_price(other.price(), quantity), 0);
+                    if revenue <= cost {
+                        break;
+                    }
+                    profit += revenue as i64 - cost as i64;
+                    quantity += 1;
+                }
+                let profit = profit - fare;
+                if profit > best.as_ref().map_or(0, |b| b.3) {
+                    best = Some((good.item.clone(), market.name.clone(), quantity, profit));
+                }
+            }
+        }
+
+        let (item, destination, quantity, _) = best?;
+        let market = sim.market_mut(&self.location)?;
+        let total = market.buy(&item, quantity, 0).ok()?;
+
+        self.funds -= total as i64;
+        self.cargo.push(Cargo {
+            item,
+            quantity,
+            unit_cost: total as f32 / quantity as f32,
+            origin: self.location.clone(),
+        });
+
+        Some(destination)
+    }
+}
@@ -4,7 +4,18 @@ use godot::{
     prelude::*,
 };
 
-use crate::{item::Item, pick_up_item::PickUpItem, ui::inventory_ui::InventoryUI};
+use crate::{
+    economy::{
+        appraisal::{self, Estimate},
+        clock::GameTime,
+        collections::ItemSet,
+        freshness,
+        stacking::{self, FakeShare},
+    },
+    item::{self, Item},
+    pick_up_item::PickUpItem,
+    ui::inventory_ui::InventoryUI,
+};
 
 #[derive(GodotClass)]
 #[class(tool, init, base=Node)]
@@ -14,9 +25,163 @@ pub struct Inventory {
     #[export]
     #[init(val = array![])]
     items: Array<Option<Gd<Item>>>,
+    // Quest items, kept apart so they never take up a slot in the bags
+    #[export]
+    #[init(val = array![])]
+    key_items: Array<Option<Gd<Item>>>,
+    #[export]
+    #[init(val = 100)]
+    funds: i64,
+    // How well the player judges an item's value alone, grows with practice
+    #[export]
+    #[init(val = 0.2)]
+    appraisal_skill: f32,
+    // How fast perishables spoil in the player's bags
+    #[export]
+    #[init(val = 1.)]
+    spoil_rate: f32,
     base: Base<Node>,
 }
 
+impl Inventory {
+    // Adds what `stacks` units of the item, `fakes` of them fakes, are worth
+    // to the stack, before its own count goes up. A merged stack is worth,
+    // and cost, the average of its units, and the player only knows that it
+    // lies somewhere within both estimates
+    fn merge_values(stack_gd: &mut Gd<Item>, item_gd: &Gd<Item>, stacks: i64, fakes: i64) {
+        let item = item_gd.bind();
+        let (value, estimate) = (item.value(), item.estimate());
+        let unit_cost = item.get_unit_cost();
+        let appraised_level = item.get_appraised_level();
+        let freshness = item.get_freshness();
+        drop(item);
+
+        let mut stack = stack_gd.bind_mut();
+        let total = stack.value() as i64 * stack.get_stacks() + value as i64 * stacks;
+        let merged = Estimate {
+            low: stack.estimate().low.min(estimate.low),
+            high: stack.estimate().high.max(estimate.high),
+        };
+        let new_value = total / (stack.get_stacks() + stacks).max(1);
+        let cost = stack.get_unit_cost() as i64 * stack.get_stacks() + unit_cost as i64 * stacks;
+        let new_cost = cost / (stack.get_stacks() + stacks).max(1);
+
+        stack.set_true_value(new_value as u32);
+        stack.set_unit_cost(new_cost as u32);
+        stack.set_estimate(merged);
+        let fake_units = stack.get_fake_units() + fakes;
+        stack.set_fake_units(fake_units);
+        // The units nobody looked at closely yet are worth another look
+        let appraised_level = stack.get_appraised_level().min(appraised_level);
+        stack.set_appraised_level(appraised_level);
+
+        let merged_freshness = freshness::merge(
+            stack.get_freshness(),
+            stack.get_stacks() as u32,
+            freshness,
+            stacks as u32,
+        );
+        stack.set_freshness(merged_freshness);
+    }
+
+    // Swaps everything the player carries for what a save file held
+    pub fn restore(
+        &mut self,
+        items: Vec<Gd<Item>>,
+        key_items: Vec<Gd<Item>>,
+        funds: i64,
+        appraisal_skill: f32,
+    ) {
+        self.items = items.into_iter().map(Some).collect();
+        self.key_items = key_items.into_iter().map(Some).collect();
+        self.funds = funds;
+        self.appraisal_skill = appraisal_skill;
+
+        self.base_mut().emit_signal("on_items_removed".into(), &[]);
+        self.base_mut()
+            .emit_signal("on_key_items_changed".into(), &[]);
+        self.base_mut()
+            .emit_signal("on_funds_changed".into(), &[funds.to_variant()]);
+    }
+
+    // Takes `quantity` out of the named stacks, newest first, as many units
+    // of each as `available` lets go. Returns every stack taken from with how
+    // many units and how many fakes among them, or None without touching
+    // anything if there isn't enough
+    fn remove_matching(
+        &mut self,
+        name: GString,
+        quantity: i64,
+        available: impl Fn(&Item) -> i64,
+    ) -> Option<Vec<(Gd<Item>, i64, i64)>> {
+        let held: i64 = self
+            .items
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| item_gd.bind().get_name() == name)
+            .map(|item_gd| available(&item_gd.bind()))
+            .sum();
+        if quantity <= 0 || held < quantity {
+            return None;
+        }
+
+        let mut taken = vec![];
+        let mut remaining = quantity;
+        for i in (0..self.items.len()).rev() {
+            if remaining == 0 {
+                break;
+            }
+            if let Some(mut inventory_item_gd) = self.items.at(i) {
+                let (stacks, fake_units, genuine_value, may_go) = {
+                    let item = inventory_item_gd.bind();
+                    if item.get_name() != name {
+                        continue;
+                    }
+                    (
+                        item.get_stacks(),
+                        item.get_fake_units(),
+                        item.genuine_value(),
+                        available(&item),
+                    )
+                };
+                if may_go <= 0 {
+                    continue;
+                }
+
+                // Only the fakes among the units that may go can go with them
+                let fakes_may_go = (fake_units - (stacks - may_go)).max(0);
+                let units = may_go.min(remaining);
+                let fakes = stacking::fakes_among(may_go, fakes_may_go, units);
+                if units == stacks {
+                    self.items.remove(i);
+                } else {
+                    // What is left is worth more or less as the fakes stay
+                    // behind or go
+                    let (stacks, fake_units) = (stacks - units, fake_units - fakes);
+                    let mut item = inventory_item_gd.bind_mut();
+                    item.set_stacks(stacks);
+                    item.set_fake_units(fake_units);
+                    item.set_true_value(appraisal::mixed_value(genuine_value, stacks, fake_units));
+                }
+                remaining -= units;
+                taken.push((inventory_item_gd, units, fakes));
+            };
+        }
+
+        self.base_mut().emit_signal("on_items_removed".into(), &[]);
+        Some(taken)
+    }
+
+    // Pieces of the set somewhere in the player's bags
+    pub fn pieces_held(&self, set: &ItemSet) -> usize {
+        set.held(|item| self.count_item(item.into()).max(0) as u32)
+    }
+
+    pub fn has_set(&self, set: &ItemSet) -> bool {
+        self.pieces_held(set) == set.pieces.len()
+    }
+}
+
 #[godot_api]
 impl Inventory {
     #[signal]
@@ -28,8 +193,291 @@ impl Inventory {
     #[signal]
     fn on_update_stacks_label(&mut self, item_gd: Gd<Item>, stacks: i64);
 
+    #[signal]
+    fn on_funds_changed(&mut self, funds: i64);
+
+    #[signal]
+    fn on_items_removed(&mut self);
+
+    #[signal]
+    fn on_items_aged(&mut self);
+
+    #[signal]
+    fn on_key_items_changed(&mut self);
+
+    #[func]
+    pub fn spend(&mut self, amount: i64) -> bool {
+        if amount < 0 || self.funds < amount {
+            return false;
+        }
+
+        self.funds -= amount;
+        let funds = self.funds;
+        self.base_mut()
+            .emit_signal("on_funds_changed".into(), &[funds.to_variant()]);
+        true
+    }
+
+    #[func]
+    pub fn earn(&mut self, amount: i64) {
+        self.funds += amount;
+        let funds = self.funds;
+        self.base_mut()
+            .emit_signal("on_funds_changed".into(), &[funds.to_variant()]);
+    }
+
+    // Takes as much of `amount` as the player has, returns what is still owed
+    #[func]
+    pub fn charge(&mut self, amount: i64) -> i64 {
+        let paid = amount.clamp(0, self.funds.max(0));
+        self.spend(paid);
+        amount - paid
+    }
+
+    #[func]
+    pub fn count_item(&self, name: GString) -> i64 {
+        self.items
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| item_gd.bind().get_name() == name)
+            .map(|item_gd| item_gd.bind().get_stacks())
+            .sum()
+    }
+
+    // What the player could sell of the named item, leaving out bound and
+    // unsellable stacks
     #[func]
-    fn add_item(&mut self, item_gd: Gd<Item>) {
+    pub fn count_sellable(&self, name: GString) -> i64 {
+        self.items
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| {
+                let item = item_gd.bind();
+                item.get_name() == name && item.is_sellable()
+            })
+            .map(|item_gd| item_gd.bind().get_stacks())
+            .sum()
+    }
+
+    // Takes `quantity` of the named item out of the inventory, newest stacks
+    // first, returns false without touching anything if there isn't enough
+    #[func]
+    pub fn remove_item(&mut self, name: GString, quantity: i64) -> bool {
+        self.remove_matching(name, quantity, |item| item.get_stacks())
+            .is_some()
+    }
+
+    // Like `remove_item`, for when it matters what the units were like
+    pub fn take_units(
+        &mut self,
+        name: GString,
+        quantity: i64,
+    ) -> Option<Vec<(Gd<Item>, i64, i64)>> {
+        self.remove_matching(name, quantity, |item| item.get_stacks())
+    }
+
+    // Like `remove_item`, for when the units are being sold
+    #[func]
+    pub fn remove_sellable(&mut self, name: GString, quantity: i64) -> bool {
+        self.remove_matching(name, quantity, |item| {
+            if item.is_sellable() {
+                item.get_stacks()
+            } else {
+                0
+            }
+        })
+        .is_some()
+    }
+
+    // Like `count_sellable`, for buyers who can tell a fake from the real thing
+    #[func]
+    pub fn count_genuine(&self, name: GString) -> i64 {
+        self.items
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| {
+                let item = item_gd.bind();
+                item.get_name() == name && item.is_sellable()
+            })
+            .map(|item_gd| item_gd.bind().genuine_units())
+            .sum()
+    }
+
+    // Leaves the fakes behind in the stacks they were mixed into
+    #[func]
+    pub fn remove_genuine(&mut self, name: GString, quantity: i64) -> bool {
+        self.remove_matching(name, quantity, |item| {
+            if item.is_sellable() {
+                item.genuine_units()
+            } else {
+                0
+            }
+        })
+        .is_some()
+    }
+
+    #[func]
+    pub fn has_key_item(&self, name: GString) -> bool {
+        self.key_items
+            .iter_shared()
+            .flatten()
+            .any(|item_gd| item_gd.bind().get_name() == name)
+    }
+
+    // For handing a quest item over once it has served its purpose
+    #[func]
+    pub fn remove_key_item(&mut self, name: GString) -> bool {
+        let Some(index) = self
+            .key_items
+            .iter_shared()
+            .position(|i| i.is_some_and(|item_gd| item_gd.bind().get_name() == name))
+        else {
+            return false;
+        };
+        self.key_items.remove(index);
+        self.base_mut()
+            .emit_signal("on_key_items_changed".into(), &[]);
+        true
+    }
+
+    // Once the player knows them for what they are, the fakes mixed into a
+    // stack go to a stack of their own, worth what fakes are
+    pub fn split_off_fakes(&mut self, mut item_gd: Gd<Item>) {
+        let (stacks, fake_units, value) = {
+            let item = item_gd.bind();
+            (item.get_stacks(), item.get_fake_units(), item.value())
+        };
+        if fake_units <= 0 {
+            return;
+        }
+        let (genuine, fake) = appraisal::split_value(value, stacks, fake_units);
+        if fake_units >= stacks {
+            let mut item = item_gd.bind_mut();
+            item.set_true_value(fake);
+            item.set_estimate(Estimate::reveal(fake));
+            item.set_known_fake(true);
+            return;
+        }
+
+        let Some(mut fakes_gd) = item_gd.duplicate().and_then(|i| i.try_cast::<Item>().ok()) else {
+            return;
+        };
+        {
+            let mut item = item_gd.bind_mut();
+            item.set_stacks(stacks - fake_units);
+            item.set_fake_units(0);
+            item.set_true_value(genuine);
+        }
+        self.base_mut().emit_signal(
+            "on_update_stacks_label".into(),
+            &[item_gd.to_variant(), (stacks - fake_units).to_variant()],
+        );
+
+        {
+            let mut fakes = fakes_gd.bind_mut();
+            fakes.set_stacks(fake_units);
+            fakes.set_true_value(fake);
+            fakes.set_estimate(Estimate::reveal(fake));
+            fakes.set_known_fake(true);
+        }
+        self.add_item(fakes_gd);
+    }
+
+    // Takes `quantity` out of one particular stack, for when it matters which
+    // copy of an item leaves the inventory
+    #[func]
+    pub fn take_item(&mut self, mut item_gd: Gd<Item>, quantity: i64) -> bool {
+        let Some(index) = self
+            .items
+            .iter_shared()
+            .position(|i| i.as_ref() == Some(&item_gd))
+        else {
+            return false;
+        };
+        let stacks = item_gd.bind().get_stacks();
+        if quantity <= 0 || stacks < quantity {
+            return false;
+        }
+
+        if stacks == quantity {
+            self.items.remove(index);
+        } else {
+            let mut item = item_gd.bind_mut();
+            let fake_units = item.get_fake_units();
+            item.set_stacks(stacks - quantity);
+            item.set_fake_units(fake_units - stacking::fakes_among(stacks, fake_units, quantity));
+        }
+
+        self.base_mut().emit_signal("on_items_removed".into(), &[]);
+        true
+    }
+
+    #[func]
+    pub fn contraband_weight(&self) -> f32 {
+        self.items
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| item_gd.bind().get_contraband())
+            .map(|item_gd| {
+                let item = item_gd.bind();
+                item.get_weight() * item.get_stacks() as f32
+            })
+            .sum()
+    }
+
+    // How many more units of the item fit, in the free slots and on top of
+    // the stack new units would join
+    #[func]
+    pub fn room_for(&self, item_gd: Gd<Item>) -> i64 {
+        // Key items don't count against the bags
+        if item_gd.bind().get_quest() {
+            return i32::MAX as i64;
+        }
+        let max_stacks = item_gd.bind().get_max_stacks().max(1);
+        let free_slots = (self.inventory_ui.bind().get_size() - self.items.len() as i64).max(0);
+        if max_stacks == 1 {
+            return free_slots;
+        }
+
+        let on_stack = self
+            .items
+            .iter_shared()
+            .flatten()
+            .filter(|i| i.bind().stacks_with(&item_gd.bind()))
+            .last()
+            .map_or(0, |i| (max_stacks - i.bind().get_stacks()).max(0));
+        free_slots * max_stacks + on_stack
+    }
+
+    #[func]
+    pub fn age_items(&mut self, minutes: i64) {
+        let perishable = self
+            .items
+            .iter_shared()
+            .flatten()
+            .any(|item_gd| item_gd.bind().is_perishable());
+        if minutes <= 0 || !perishable {
+            return;
+        }
+
+        let elapsed = GameTime::from_minutes(minutes as u64);
+        let spoil_rate = self.spoil_rate;
+        if item::age_items(&mut self.items, elapsed, spoil_rate) {
+            self.base_mut().emit_signal("on_items_removed".into(), &[]);
+        } else {
+            self.base_mut().emit_signal("on_items_aged".into(), &[]);
+        }
+    }
+
+    #[func]
+    pub fn add_item(&mut self, item_gd: Gd<Item>) {
+        if item_gd.bind().get_quest() {
+            self.key_items.push(Some(item_gd));
+            self.base_mut()
+                .emit_signal("on_key_items_changed".into(), &[]);
+            return;
+        }
+
         if item_gd.bind().get_stacks() > 0 && item_gd.bind().get_max_stacks() > 1 {
             self.add_stackable_item_into_inventory(item_gd.clone());
         } else {
@@ -40,69 +488,54 @@ impl Inventory {
         }
     }
 
+    // Tops up the last stack it stacks with, the units that don't fit there
+    // start new stacks of their own
     #[func]
     fn add_stackable_item_into_inventory(&mut self, item_gd: Gd<Item>) {
-        let mut item_index: Option<usize> = None;
+        let stack = self
+            .items
+            .iter_shared()
+            .flatten()
+            .filter(|i| i.bind().stacks_with(&item_gd.bind()))
+            .last();
+        let (incoming, max_stacks, fake_units) = {
+            let item = item_gd.bind();
+            (
+                item.get_stacks(),
+                item.get_max_stacks(),
+                item.get_fake_units(),
+            )
+        };
+        let mut fakes = FakeShare::new(incoming, fake_units);
+        let (topped_up, new_stacks) = stacking::fill(
+            stack.as_ref().map(|s| s.bind().get_stacks()),
+            incoming,
+            max_stacks,
+        );
 
-        // Reverse searching for the item in the inventory
-        for i in 0..self.items.len() {
-            if let Some(inventory_item) = self.items.at(i) {
-                if inventory_item.bind().get_name() == item_gd.bind().get_name() {
-                    item_index = Some(i);
-                };
-            };
+        if let Some(mut stack_gd) = stack.filter(|_| topped_up > 0) {
+            Self::merge_values(&mut stack_gd, &item_gd, topped_up, fakes.take(topped_up));
+            let stacks = stack_gd.bind().get_stacks() + topped_up;
+            stack_gd.bind_mut().set_stacks(stacks);
+            self.base_mut().emit_signal(
+                "on_update_stacks_label".into(),
+                &[stack_gd.to_variant(), stacks.to_variant()],
+            );
         }
 
-        // If the item is found in the inventory
-        if item_index.is_some() {
-            if let Some(mut inventory_item_gd) = self.items.at(item_index.unwrap()) {
-                if inventory_item_gd.bind().get_stacks() + item_gd.bind().get_stacks()
-                    <= item_gd.bind().get_max_stacks()
-                {
-                    let new_stacks =
-                        inventory_item_gd.bind().get_stacks() + item_gd.bind().get_stacks();
-
-                    inventory_item_gd.bind_mut().set_stacks(new_stacks);
-
-                    self.base_mut().emit_signal(
-                        "on_update_stacks_label".into(),
-                        &[
-                            inventory_item_gd.clone().to_variant(),
-                            new_stacks.to_variant(),
-                        ],
-                    );
-                } else if let Some(inventory_item_gd_dub) = inventory_item_gd.duplicate() {
-                    self.base_mut().emit_signal(
-                        "on_update_stacks_label".into(),
-                        &[
-                            inventory_item_gd.clone().to_variant(),
-                            item_gd.bind().get_max_stacks().to_variant(),
-                        ],
-                    );
-
-                    let stacks_diff = (item_gd.bind().get_stacks()
-                        + inventory_item_gd.bind().get_stacks())
-                        - item_gd.bind().get_max_stacks();
-
-                    if let Ok(mut new_item_gd) = inventory_item_gd_dub.clone().try_cast::<Item>() {
-                        new_item_gd.bind_mut().set_stacks(stacks_diff);
-
-                        self.items
-                            .extend(array![Some(new_item_gd.clone())].iter_shared());
-                        self.base_mut()
-                            .emit_signal("on_add_item".into(), &[new_item_gd.to_variant()]);
-                    };
-                }
+        for stacks in new_stacks {
+            let Some(new_item_gd) = item_gd.duplicate() else {
+                continue;
             };
-        } else if let Some(new_item_gd_dub) = item_gd.duplicate() {
-            let mut new_item_gd = new_item_gd_dub.try_cast::<Item>().unwrap();
-            new_item_gd
-                .bind_mut()
-                .set_stacks(item_gd.bind().get_stacks());
-
-            self.items
-                .extend(array![Some(new_item_gd.clone())].iter_shared());
-
+            let Ok(mut new_item_gd) = new_item_gd.try_cast::<Item>() else {
+                continue;
+            };
+            {
+                let mut new_item = new_item_gd.bind_mut();
+                new_item.set_stacks(stacks);
+                new_item.set_fake_units(fakes.take(stacks));
+            }
+            self.items.push(Some(new_item_gd.clone()));
             self.base_mut()
                 .emit_signal("on_add_item".into(), &[new_item_gd.to_variant()]);
         }
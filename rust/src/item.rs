@@ -3,7 +3,15 @@ use godot::{
     prelude::*,
 };
 
-use crate::ui::inventory_slot::SlotType;
+use crate::{
+    economy::{
+        appraisal::{self, Estimate},
+        clock::GameTime,
+        freshness::{self, FRESH},
+        stacking,
+    },
+    ui::inventory_slot::SlotType,
+};
 
 #[derive(GodotClass)]
 #[class(tool, init, base=Resource)]
@@ -27,5 +35,192 @@ pub struct Item {
     #[export]
     #[init(val = SlotType::NotEquippable)]
     slot_type: SlotType,
+    // What one unit is really worth, 0 means exactly its price
+    #[export]
+    true_value: u32,
+    // The range the player believes the value lies in, 0 when unknown
+    #[export]
+    estimate_low: u32,
+    #[export]
+    estimate_high: u32,
+    // Highest eye level a free appraisal looked at it with, 0 before any
+    #[export]
+    appraised_level: u32,
+    // What the player paid for one unit, 0 for goods they came by for free
+    #[export]
+    unit_cost: u32,
+    // How many of its units are fakes, which look like the real thing until
+    // an appraisal gives them away
+    #[export]
+    fake_units: i64,
+    #[export]
+    known_fake: bool,
+    // Towns may ask for a licence to trade a category, like "Weapons"
+    #[export]
+    category: GString,
+    // Only black markets deal in it, and guards seize it
+    #[export]
+    contraband: bool,
+    // Of one unit, for what guards notice in the player's bags
+    #[export]
+    #[init(val = 1.)]
+    weight: f32,
+    // Hours it keeps in the player's bags, 0 for goods that never spoil
+    #[export]
+    shelf_life_hours: u32,
+    #[export]
+    #[init(val = FRESH)]
+    freshness: f32,
+    // What it turns into once spoiled, without one it just stays worthless
+    #[export]
+    spoiled: Option<Gd<Item>>,
+    // Needed for a quest, kept with the key items rather than in the bags
+    #[export]
+    quest: bool,
+    // Tied to the player, it can't be sold, dropped or put away
+    #[export]
+    bound: bool,
+    #[export]
+    unsellable: bool,
+    #[export]
+    undroppable: bool,
     base: Base<Resource>,
 }
+
+impl Item {
+    pub fn value(&self) -> u32 {
+        if self.true_value == 0 {
+            self.price
+        } else {
+            self.true_value
+        }
+    }
+
+    pub fn estimate(&self) -> Estimate {
+        if self.estimate_high == 0 {
+            Estimate::unknown(self.price)
+        } else {
+            Estimate {
+                low: self.estimate_low,
+                high: self.estimate_high,
+            }
+        }
+    }
+
+    pub fn value_label(&self) -> String {
+        let label = if self.known_fake {
+            format!("Fake! {}", self.estimate().label())
+        } else {
+            self.estimate().label()
+        };
+        if self.is_perishable() {
+            format!("{label}, {}", freshness::label(self.freshness))
+        } else {
+            label
+        }
+    }
+
+    // None when the player didn't pay for it
+    pub fn cost_basis(&self) -> Option<u32> {
+        (self.unit_cost > 0).then_some(self.unit_cost)
+    }
+
+    pub fn is_perishable(&self) -> bool {
+        self.shelf_life_hours > 0
+    }
+
+    // Stacks only merge with stacks of the same bucket
+    pub fn freshness_bucket(&self) -> u32 {
+        freshness::bucket(self.freshness)
+    }
+
+    pub fn has_fakes(&self) -> bool {
+        self.fake_units > 0
+    }
+
+    pub fn genuine_units(&self) -> i64 {
+        (self.stacks - self.fake_units).max(0)
+    }
+
+    // How many fakes go along when `units` of the stack leave it
+    pub fn fakes_in(&self, units: i64) -> i64 {
+        stacking::fakes_among(self.stacks, self.fake_units, units)
+    }
+
+    // What one of its real units is worth, the fakes mixed in bring the
+    // stack's value down
+    pub fn genuine_value(&self) -> u32 {
+        appraisal::split_value(self.value(), self.stacks, self.fake_units).0
+    }
+
+    // What `units` of the stack are worth on average, with their share of fakes
+    pub fn value_of(&self, units: i64) -> u32 {
+        appraisal::mixed_value(self.genuine_value(), units, self.fakes_in(units))
+    }
+
+    // Unnoticed fakes stack with the real thing, only the fakes the player
+    // knows about are kept apart
+    pub fn stacks_with(&self, other: &Item) -> bool {
+        self.name == other.name
+            && self.freshness_bucket() == other.freshness_bucket()
+            && self.known_fake == other.known_fake
+    }
+
+    // Whether shops, auctions, stalls and collectors may have it
+    pub fn is_sellable(&self) -> bool {
+        !(self.quest || self.bound || self.unsellable)
+    }
+
+    pub fn is_droppable(&self) -> bool {
+        !(self.quest || self.bound || self.undroppable)
+    }
+
+    // Whether it may leave the player's hands for a stash or a stall
+    pub fn is_storable(&self) -> bool {
+        !(self.quest || self.bound)
+    }
+
+    pub fn set_estimate(&mut self, estimate: Estimate) {
+        self.estimate_low = estimate.low;
+        self.estimate_high = estimate.high;
+    }
+}
+
+// Ages the perishables among `items` by `elapsed` spent spoiling at `rate`,
+// spoiled ones are swapped for their spoiled variant. Returns whether any
+// slot was swapped
+pub fn age_items(items: &mut Array<Option<Gd<Item>>>, elapsed: GameTime, rate: f32) -> bool {
+    let mut swapped = false;
+    for i in 0..items.len() {
+        let Some(mut item_gd) = items.at(i) else {
+            continue;
+        };
+        let spoiled = {
+            let mut item = item_gd.bind_mut();
+            if !item.is_perishable() {
+                continue;
+            }
+            let shelf_life = GameTime::from_hours(item.shelf_life_hours as u64);
+            item.freshness = freshness::decay(item.freshness, shelf_life, elapsed, rate);
+            if !freshness::is_spoiled(item.freshness) {
+                continue;
+            }
+            item.spoiled.clone()
+        };
+
+        let Some(spoiled_gd) = spoiled.and_then(|s| s.duplicate()) else {
+            continue;
+        };
+        if let Ok(mut spoiled_gd) = spoiled_gd.try_cast::<Item>() {
+            let (stacks, fake_units) = {
+                let item = item_gd.bind();
+                (item.get_stacks(), item.get_fake_units())
+            };
+            spoiled_gd.bind_mut().set_stacks(stacks);
+            spoiled_gd.bind_mut().set_fake_units(fake_units);
+            items.set(i, Some(spoiled_gd));
+            swapped = true;
+        }
+    }
+    swapped
+}
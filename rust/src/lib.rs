@@ -1,8 +1,23 @@
+pub mod auctioneer;
+pub mod checkpoint;
+pub mod collector;
+pub mod economy;
+pub mod estate_agent;
 pub mod inventory;
 pub mod item;
+pub mod market_event_data;
+pub mod market_stall;
+pub mod merchant;
+pub mod moneylender;
+pub mod notice_board;
 pub mod pick_up_item;
 pub mod player;
+pub mod recipe_data;
+pub mod rival_trader;
+pub mod stash;
 pub mod ui;
+pub mod workbench;
+pub mod world;
 
 use godot::prelude::*;
 
@@ -0,0 +1,76 @@
+use godot::prelude::*;
+
+use crate::economy::events::{EventDef, EventTrigger};
+
+/// A market event as designers define it in the editor. With a `first_day`
+/// of 0 the event is random and uses `chance_per_day` instead.
+#[derive(GodotClass)]
+#[class(tool, init, base=Resource)]
+pub struct MarketEventData {
+    #[export]
+    name: GString,
+    #[export]
+    description: GString,
+    // Towns and goods it hits, empty for all of them
+    #[export]
+    towns: PackedStringArray,
+    #[export]
+    items: PackedStringArray,
+    #[export]
+    #[init(val = 1.)]
+    price_factor: f32,
+    #[export]
+    #[init(val = 1.)]
+    demand_factor: f32,
+    #[export]
+    #[init(val = 1.)]
+    stock_factor: f32,
+    #[export]
+    #[init(val = 1)]
+    duration_days: i64,
+    #[export]
+    first_day: i64,
+    #[export]
+    every_days: i64,
+    #[export]
+    chance_per_day: f32,
+    base: Base<Resource>,
+}
+
+impl MarketEventData {
+    pub fn to_def(&self) -> EventDef {
+        let trigger = if self.first_day > 0 {
+            EventTrigger::Scheduled {
+                first_day: self.first_day as u64,
+                every_days: self.every_days.max(0) as u64,
+            }
+        } else {
+            EventTrigger::Random {
+                chance_per_day: self.chance_per_day,
+            }
+        };
+
+        let mut def = EventDef::new(
+            &self.name.to_string(),
+            &self.description.to_string(),
+            trigger,
+        )
+        .with_prices(self.price_factor)
+        .with_demand(self.demand_factor)
+        .with_stock(self.stock_factor)
+        .lasting(self.duration_days.max(1) as u64);
+        def.towns = self
+            .towns
+            .as_slice()
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        def.items = self
+            .items
+            .as_slice()
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        def
+    }
+}
@@ -0,0 +1,78 @@
+use godot::{
+    classes::{Area2D, IArea2D, InputEvent, InputEventKey, Sprite2D},
+    global::Key,
+    prelude::*,
+};
+
+use crate::{item::Item, player::Player};
+
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct Merchant {
+    // Name of the market this merchant runs in the `World` simulation
+    #[export]
+    market: GString,
+    // Item resources used to show and hand over the goods of the market
+    #[export]
+    #[init(val = array![])]
+    catalogue: Array<Gd<Item>>,
+    // Black market traders only show themselves to someone standing close
+    #[export]
+    hidden: bool,
+    is_player_near: bool,
+    base: Base<Area2D>,
+}
+
+impl Merchant {
+    pub fn find_item(&self, name: &GString) -> Option<Gd<Item>> {
+        self.catalogue
+            .iter_shared()
+            .find(|item_gd| item_gd.bind().get_name() == *name)
+    }
+}
+
+#[godot_api]
+impl Merchant {
+    #[signal]
+    fn on_toggle_shop(&mut self);
+
+    #[signal]
+    fn on_close_shop(&mut self);
+
+    #[func]
+    fn area2d_entered(&mut self, player_area2d: Gd<Area2D>) {
+        let is_player_near = self.base().overlaps_area(player_area2d);
+
+        if self.is_player_near && !is_player_near {
+            self.base_mut().emit_signal("on_close_shop".into(), &[]);
+        }
+        self.is_player_near = is_player_near;
+
+        if self.hidden {
+            let mut sprite = self.base().get_node_as::<Sprite2D>("Sprite2D");
+            sprite.set_visible(is_player_near);
+        }
+    }
+}
+
+#[godot_api]
+impl IArea2D for Merchant {
+    fn ready(&mut self) {
+        let mut player_node = self.base_mut().get_node_as::<Player>("../Player");
+        let area2d_entered_callable = self.base().callable("area2d_entered");
+        player_node.connect("on_area2d_entered".into(), area2d_entered_callable);
+
+        if self.hidden {
+            let mut sprite = self.base().get_node_as::<Sprite2D>("Sprite2D");
+            sprite.set_visible(false);
+        }
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && e.get_keycode() == Key::E && self.is_player_near {
+                self.base_mut().emit_signal("on_toggle_shop".into(), &[]);
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use godot::prelude::*;
+
+use crate::economy::{clock::GameTime, crafting::Recipe};
+
+/// A crafting recipe as designers define it in the editor. `inputs` and
+/// `input_quantities` pair up by position.
+#[derive(GodotClass)]
+#[class(tool, init, base=Resource)]
+pub struct RecipeData {
+    #[export]
+    name: GString,
+    #[export]
+    workbench: GString,
+    #[export]
+    inputs: PackedStringArray,
+    #[export]
+    input_quantities: PackedInt64Array,
+    #[export]
+    output: GString,
+    #[export]
+    #[init(val = 1)]
+    quantity: i64,
+    #[export]
+    #[init(val = 1)]
+    duration_hours: i64,
+    #[export]
+    known: bool,
+    base: Base<Resource>,
+}
+
+impl RecipeData {
+    pub fn to_recipe(&self) -> Recipe {
+        let mut recipe = Recipe::new(
+            &self.name.to_string(),
+            &self.workbench.to_string(),
+            &self.output.to_string(),
+            GameTime::from_hours(self.duration_hours.max(0) as u64),
+        )
+        .making(self.quantity.max(1) as u32);
+        for (i, item) in self.inputs.as_slice().iter().enumerate() {
+            let quantity = self
+                .input_quantities
+                .as_slice()
+                .get(i)
+                .copied()
+                .unwrap_or(1);
+            recipe = recipe.with_input(&item.to_string(), quantity.max(1) as u32);
+        }
+        if self.known {
+            recipe = recipe.known();
+        }
+        recipe
+    }
+}
@@ -0,0 +1,63 @@
+use godot::{
+    classes::{ISprite2D, Sprite2D},
+    prelude::*,
+};
+
+use crate::world::World;
+
+// A rival trader's figure in one town, seen only while both the rival and
+// the player are there
+#[derive(GodotClass)]
+#[class(init, base=Sprite2D)]
+pub struct RivalTrader {
+    // Name of the rival in the `World` simulation
+    #[export]
+    rival: GString,
+    #[export]
+    town: GString,
+    world_node: Option<Gd<World>>,
+    base: Base<Sprite2D>,
+}
+
+#[godot_api]
+impl RivalTrader {
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        self.refresh();
+    }
+
+    #[func]
+    fn on_travelled(&mut self, _town: GString, _report: GString) {
+        self.refresh();
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let Some(world_gd) = self.world_node.clone() else {
+            return;
+        };
+        let (rival_town, location) = {
+            let world = world_gd.bind();
+            (
+                world.get_rival_town(self.rival.clone()),
+                world.get_location(),
+            )
+        };
+        let is_here = rival_town == self.town && location == self.town;
+        self.base_mut().set_visible(is_here);
+    }
+}
+
+#[godot_api]
+impl ISprite2D for RivalTrader {
+    fn ready(&mut self) {
+        let mut world_node = self.base_mut().get_node_as::<World>("../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        let on_travelled_callable = self.base().callable("on_travelled");
+        world_node.connect("on_travelled".into(), on_travelled_callable);
+        self.world_node = Some(world_node);
+
+        self.refresh();
+    }
+}
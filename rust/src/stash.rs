@@ -0,0 +1,112 @@
+use godot::{
+    classes::{Area2D, IArea2D, InputEvent, InputEventKey},
+    global::Key,
+    prelude::*,
+};
+
+use crate::{
+    economy::{clock::GameTime, freshness::COLD_SPOIL_RATE},
+    item::{self, Item},
+    player::Player,
+};
+
+// A cold cellar the player keeps goods in, perishables last longer here than
+// in their bags
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct Stash {
+    #[export]
+    #[init(val = array![])]
+    items: Array<Option<Gd<Item>>>,
+    // Stacks it holds
+    #[export]
+    #[init(val = 8)]
+    capacity: i64,
+    // Stacks the player's warehouses add on top
+    #[var]
+    extra_capacity: i64,
+    #[export]
+    #[init(val = COLD_SPOIL_RATE)]
+    spoil_rate: f32,
+    is_player_near: bool,
+    base: Base<Area2D>,
+}
+
+#[godot_api]
+impl Stash {
+    #[signal]
+    fn on_toggle_stash(&mut self);
+
+    #[signal]
+    fn on_close_stash(&mut self);
+
+    #[func]
+    pub fn total_capacity(&self) -> i64 {
+        self.capacity + self.extra_capacity
+    }
+
+    #[func]
+    pub fn is_full(&self) -> bool {
+        self.items.len() as i64 >= self.total_capacity()
+    }
+
+    // Stacks are kept as they are, so a stack's freshness never mixes with
+    // another's while it sits here
+    #[func]
+    pub fn store(&mut self, item_gd: Gd<Item>) -> bool {
+        if self.is_full() || !item_gd.bind().is_storable() {
+            return false;
+        }
+        self.items.push(Some(item_gd));
+        true
+    }
+
+    #[func]
+    pub fn take(&mut self, index: i64) -> Option<Gd<Item>> {
+        let item_gd = self.items.get(index as usize).flatten()?;
+        self.items.remove(index as usize);
+        Some(item_gd)
+    }
+
+    // Puts back what a save file held, whether it fits or not
+    pub fn restore(&mut self, items: Vec<Gd<Item>>) {
+        self.items = items.into_iter().map(Some).collect();
+    }
+
+    #[func]
+    pub fn age_items(&mut self, minutes: i64) {
+        if minutes <= 0 || self.items.is_empty() {
+            return;
+        }
+        let elapsed = GameTime::from_minutes(minutes as u64);
+        let spoil_rate = self.spoil_rate;
+        item::age_items(&mut self.items, elapsed, spoil_rate);
+    }
+
+    #[func]
+    fn area2d_entered(&mut self, player_area2d: Gd<Area2D>) {
+        let is_player_near = self.base().overlaps_area(player_area2d);
+
+        if self.is_player_near && !is_player_near {
+            self.base_mut().emit_signal("on_close_stash".into(), &[]);
+        }
+        self.is_player_near = is_player_near;
+    }
+}
+
+#[godot_api]
+impl IArea2D for Stash {
+    fn ready(&mut self) {
+        let mut player_node = self.base_mut().get_node_as::<Player>("../Player");
+        let area2d_entered_callable = self.base().callable("area2d_entered");
+        player_node.connect("on_area2d_entered".into(), area2d_entered_callable);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && e.get_keycode() == Key::E && self.is_player_near {
+                self.base_mut().emit_signal("on_toggle_stash".into(), &[]);
+            }
+        }
+    }
+}
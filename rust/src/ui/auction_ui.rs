@@ -0,0 +1,190 @@
+use godot::{
+    classes::{Button, CanvasLayer, HBoxContainer, ICanvasLayer, SpinBox, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{
+    auctioneer::Auctioneer,
+    economy::{
+        auction::{self, Party},
+        freshness,
+    },
+    inventory::Inventory,
+    item::Item,
+    world::World,
+};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct AuctionUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Lots")]
+    lots: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Sellables")]
+    sellables: OnReady<Gd<HBoxContainer>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/ListingControls/ReserveSpinBox"
+    )]
+    reserve_spin_box: OnReady<Gd<SpinBox>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/ListingControls/HoursSpinBox"
+    )]
+    hours_spin_box: OnReady<Gd<SpinBox>>,
+    #[init(node = "..")]
+    auctioneer_node: OnReady<Gd<Auctioneer>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl AuctionUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let (world_gd, inventory_gd) = match (self.world_node.clone(), self.inventory_node.clone())
+        {
+            (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+            _ => return,
+        };
+
+        for mut child in self.lots.get_children().iter_shared() {
+            self.lots.remove_child(child.clone());
+            child.queue_free();
+        }
+        for mut child in self.sellables.get_children().iter_shared() {
+            self.sellables.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let lots = world_gd.bind().auction().lots().to_vec();
+        for lot in lots {
+            let best = match &lot.best_bid {
+                Some(bid) => format!("best {} by {}", bid.amount, bid.bidder),
+                None => "no bids".to_string(),
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(
+                format!(
+                    "#{} {} x{} from {}, {}, ends {}",
+                    lot.id,
+                    lot.item,
+                    lot.quantity,
+                    lot.seller,
+                    best,
+                    auction::closing_label(lot.ends_at)
+                )
+                .into(),
+            );
+
+            let is_leading = lot
+                .best_bid
+                .as_ref()
+                .is_some_and(|b| b.bidder == Party::Player);
+            if lot.seller == Party::Player || is_leading {
+                button.set_disabled(true);
+            } else {
+                button.set_tooltip_text(format!("Bid {}", lot.min_bid()).into());
+                let bid_callable = self
+                    .base()
+                    .callable("bid")
+                    .bindv(varray![lot.id as i64, lot.min_bid() as i64]);
+                button.connect("pressed".into(), bid_callable);
+            }
+
+            self.lots.add_child(button.upcast());
+        }
+
+        // One button per stack the player could put up
+        for item_gd in inventory_gd.bind().get_items().iter_shared().flatten() {
+            let text = {
+                let item = item_gd.bind();
+                if !item.is_sellable() {
+                    continue;
+                }
+                if item.is_perishable() {
+                    let freshness = freshness::label(item.get_freshness());
+                    format!("List {} ({freshness})", item.get_name())
+                } else {
+                    format!("List {}", item.get_name())
+                }
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(text.into());
+
+            let list_item_callable = self.base().callable("list_item").bindv(varray![item_gd]);
+            button.connect("pressed".into(), list_item_callable);
+
+            self.sellables.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn bid(&mut self, id: i64, amount: i64) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().bid_on_lot(id, amount);
+        }
+        self.refresh();
+    }
+
+    // Lists the whole stack
+    #[func]
+    fn list_item(&mut self, item_gd: Gd<Item>) {
+        let Some(mut world_gd) = self.world_node.clone() else {
+            return;
+        };
+
+        let quantity = item_gd.bind().get_stacks();
+        let reserve = self.reserve_spin_box.get_value() as i64;
+        let hours = self.hours_spin_box.get_value() as i64;
+        world_gd
+            .bind_mut()
+            .list_on_auction(item_gd, quantity, reserve, hours);
+
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for AuctionUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.auctioneer_node
+            .connect("on_toggle_auction".into(), toggle_callable);
+        self.auctioneer_node
+            .connect("on_close_auction".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+
+        let inventory_node = self
+            .base_mut()
+            .get_node_as::<Inventory>("../../Player/Inventory");
+        self.inventory_node = Some(inventory_node);
+    }
+}
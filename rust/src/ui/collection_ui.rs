@@ -0,0 +1,108 @@
+use godot::{
+    classes::{CanvasLayer, ICanvasLayer, InputEvent, InputEventKey, Label, VBoxContainer},
+    global::Key,
+    prelude::*,
+};
+
+use crate::{inventory::Inventory, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct CollectionUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    base: Base<CanvasLayer>,
+}
+
+impl CollectionUI {
+    fn add_line(&mut self, text: String, color: Color) {
+        let mut label = Label::new_alloc();
+        label.add_theme_color_override("font_color".into(), color);
+        label.set_text(text.into());
+        self.list.add_child(label.upcast());
+    }
+}
+
+#[godot_api]
+impl CollectionUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    // Every set with the pieces found so far, the ones still carried ticked
+    #[func]
+    fn refresh(&mut self) {
+        let (Some(world_gd), Some(inventory_gd)) =
+            (self.world_node.clone(), self.inventory_node.clone())
+        else {
+            return;
+        };
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let lines: Vec<(String, Color)> = {
+            let world = world_gd.bind();
+            let inventory = inventory_gd.bind();
+            let collections = world.collections();
+            let mut lines = vec![];
+            for set in collections.sets() {
+                lines.push((
+                    format!(
+                        "{} ({}/{})",
+                        set.name,
+                        inventory.pieces_held(set),
+                        set.pieces.len()
+                    ),
+                    Color::BLACK,
+                ));
+                for piece in set.pieces.iter() {
+                    let line = if !collections.is_discovered(piece) {
+                        ("  ???".to_string(), Color::DIM_GRAY)
+                    } else if inventory.count_item(piece.into()) > 0 {
+                        (format!("  [x] {piece}"), Color::BLACK)
+                    } else {
+                        (format!("  [ ] {piece}"), Color::BLACK)
+                    };
+                    lines.push(line);
+                }
+            }
+            lines
+        };
+
+        for (text, color) in lines {
+            self.add_line(text, color);
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for CollectionUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        self.world_node = Some(self.base_mut().get_node_as::<World>("../World"));
+        self.inventory_node = Some(
+            self.base_mut()
+                .get_node_as::<Inventory>("../Player/Inventory"),
+        );
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && !e.is_echo() && e.get_keycode() == Key::C {
+                self.toggle();
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, Label, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{collector::Collector, inventory::Inventory, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct CollectorUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "..")]
+    collector_node: OnReady<Gd<Collector>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl CollectorUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    // The sets the collector is after, how far along the player is with each
+    // and, once one is complete, what the collector pays for it
+    #[func]
+    fn refresh(&mut self) {
+        let (Some(world_gd), Some(inventory_gd)) =
+            (self.world_node.clone(), self.inventory_node.clone())
+        else {
+            return;
+        };
+        let name = self.collector_node.bind().get_collector();
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let (title, sets) = {
+            let world = world_gd.bind();
+            let inventory = inventory_gd.bind();
+            let collections = world.collections();
+            let Some(collector) = collections.collector(&name.to_string()) else {
+                godot_error!("There is no collector called {name}");
+                return;
+            };
+            let title = if collector.town == world.routes().location {
+                collector.name.clone()
+            } else {
+                format!("{} only deals in {}", collector.name, collector.town)
+            };
+            let sets: Vec<_> = collector
+                .wants
+                .iter()
+                .filter_map(|(set, premium)| collections.set(set).map(|s| (s, *premium)))
+                .map(|(set, premium)| {
+                    let offer = collections
+                        .offer(&collector.name, &set.name, world.simulation(), |item| {
+                            inventory.count_sellable(item.into()).max(0) as u32
+                        })
+                        .ok();
+                    (
+                        set.name.clone(),
+                        format!(
+                            "{}: {}/{} pieces, pays {premium}x",
+                            set.name,
+                            inventory.pieces_held(set),
+                            set.pieces.len()
+                        ),
+                        offer,
+                    )
+                })
+                .collect();
+            (title, sets)
+        };
+        self.title_label.set_text(title.into());
+
+        for (set, text, offer) in sets {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(text.into());
+            self.list.add_child(label.upcast());
+
+            let Some(offer) = offer else {
+                continue;
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Sell the {set} for {offer}").into());
+            let sell_callable = self
+                .base()
+                .callable("sell")
+                .bindv(varray![GString::from(set.as_str())]);
+            button.connect("pressed".into(), sell_callable);
+            self.list.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn sell(&mut self, set: GString) {
+        let collector = self.collector_node.bind().get_collector();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().sell_set(collector, set);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for CollectorUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.collector_node
+            .connect("on_toggle_collector".into(), toggle_callable);
+        self.collector_node
+            .connect("on_close_collector".into(), close_callable);
+
+        self.world_node = Some(self.base_mut().get_node_as::<World>("../../World"));
+        self.inventory_node = Some(
+            self.base_mut()
+                .get_node_as::<Inventory>("../../Player/Inventory"),
+        );
+    }
+}
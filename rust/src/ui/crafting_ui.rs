@@ -0,0 +1,130 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, Label, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{inventory::Inventory, workbench::Workbench, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct CraftingUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "..")]
+    workbench_node: OnReady<Gd<Workbench>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl CraftingUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if is_visible {
+            return;
+        }
+
+        // Whatever the player brought along may give them ideas
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().discover_recipes();
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    // Every recipe of this workbench, how often the player's stacks allow
+    // making it, and a placeholder for those not discovered yet
+    #[func]
+    fn refresh(&mut self) {
+        let (Some(world_gd), Some(inventory_gd)) =
+            (self.world_node.clone(), self.inventory_node.clone())
+        else {
+            return;
+        };
+        let workbench = self.workbench_node.bind().get_workbench();
+        self.title_label.set_text(workbench.clone());
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let recipes: Vec<_> = {
+            let world = world_gd.bind();
+            let inventory = inventory_gd.bind();
+            let cookbook = world.cookbook();
+            cookbook
+                .at(&workbench.to_string())
+                .map(|recipe| {
+                    let available =
+                        recipe.available(|item| inventory.count_item(item.into()).max(0) as u32);
+                    (
+                        recipe.name.clone(),
+                        recipe.to_string(),
+                        cookbook.is_discovered(&recipe.name),
+                        available,
+                    )
+                })
+                .collect()
+        };
+
+        for (name, text, is_discovered, available) in recipes {
+            if !is_discovered {
+                let mut label = Label::new_alloc();
+                label.add_theme_color_override("font_color".into(), Color::DIM_GRAY);
+                label.set_text("??? Bring along what it takes to work it out".into());
+                self.list.add_child(label.upcast());
+                continue;
+            }
+
+            let mut button = Button::new_alloc();
+            button.set_text(format!("{text} (x{available})").into());
+            button.set_disabled(available == 0);
+            let craft_callable = self
+                .base()
+                .callable("craft")
+                .bindv(varray![GString::from(name.as_str())]);
+            button.connect("pressed".into(), craft_callable);
+            self.list.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn craft(&mut self, recipe: GString) {
+        let workbench = self.workbench_node.bind().get_workbench();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().craft(workbench, recipe);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for CraftingUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.workbench_node
+            .connect("on_toggle_workbench".into(), toggle_callable);
+        self.workbench_node
+            .connect("on_close_workbench".into(), close_callable);
+
+        self.world_node = Some(self.base_mut().get_node_as::<World>("../../World"));
+        self.inventory_node = Some(
+            self.base_mut()
+                .get_node_as::<Inventory>("../../Player/Inventory"),
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, Label, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{estate_agent::EstateAgent, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct EstateUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "..")]
+    estate_agent_node: OnReady<Gd<EstateAgent>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl EstateUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    // Every property with what buying or improving it costs, which only
+    // the agent in the property's town can arrange
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let properties: Vec<_> = {
+            let world = world_gd.bind();
+            let location = world.routes().location.clone();
+            world
+                .estate()
+                .properties()
+                .iter()
+                .map(|p| {
+                    let action = match p.next_cost() {
+                        Some(cost) if p.is_owned() => Some(format!("Upgrade for {cost}")),
+                        Some(cost) => Some(format!("Buy for {cost}")),
+                        None => None,
+                    };
+                    (
+                        p.name.clone(),
+                        p.to_string(),
+                        action.filter(|_| p.town == location),
+                    )
+                })
+                .collect()
+        };
+
+        for (name, text, action) in properties {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(text.into());
+            self.list.add_child(label.upcast());
+
+            let Some(action) = action else {
+                continue;
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(action.into());
+            let improve_callable = self
+                .base()
+                .callable("improve")
+                .bindv(varray![GString::from(name.as_str())]);
+            button.connect("pressed".into(), improve_callable);
+            self.list.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn improve(&mut self, property: GString) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().improve_property(property);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for EstateUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.estate_agent_node
+            .connect("on_toggle_estate".into(), toggle_callable);
+        self.estate_agent_node
+            .connect("on_close_estate".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+    }
+}
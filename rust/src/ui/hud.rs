@@ -0,0 +1,154 @@
+use godot::{
+    classes::{CanvasLayer, ICanvasLayer, Label},
+    prelude::*,
+};
+
+use crate::{inventory::Inventory, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct Hud {
+    #[init(node = "./MarginContainer/HBoxContainer/DayLabel")]
+    day_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/HBoxContainer/LocationLabel")]
+    location_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/HBoxContainer/FundsLabel")]
+    funds_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/HBoxContainer/DebtLabel")]
+    debt_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/HBoxContainer/MessageLabel")]
+    message_label: OnReady<Gd<Label>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl Hud {
+    #[func]
+    fn on_time_advanced(&mut self, day: i64, hour: i64) {
+        self.day_label
+            .set_text(format!("Day {day} {hour:02}:00").into());
+    }
+
+    #[func]
+    fn on_funds_changed(&mut self, funds: i64) {
+        self.funds_label.set_text(format!("{funds} coins").into());
+    }
+
+    // Only shown while the player owes anything
+    #[func]
+    fn on_debt_changed(&mut self, debt: i64) {
+        self.debt_label.set_text(format!("Debt {debt}").into());
+        self.debt_label.set_visible(debt > 0);
+    }
+
+    // What the guards did, shown until the next checkpoint
+    #[func]
+    fn on_inspected(&mut self, checkpoint: GString, outcome: GString) {
+        self.message_label
+            .set_text(format!("{checkpoint}: {outcome}").into());
+    }
+
+    #[func]
+    fn on_message(&mut self, text: GString) {
+        self.message_label.set_text(text);
+    }
+
+    #[func]
+    fn on_fake_detected(&mut self, by: GString, item: GString, confiscated: bool) {
+        let text = if confiscated {
+            format!("{by} saw through your fake {item} and kept it")
+        } else {
+            format!("{by} saw through your fake {item}")
+        };
+        self.message_label.set_text(text.into());
+    }
+
+    #[func]
+    fn on_goal_changed(&mut self, goal: GString) {
+        self.message_label.set_text(goal);
+    }
+
+    #[func]
+    fn on_travelled(&mut self, town: GString, report: GString) {
+        self.location_label.set_text(town);
+        self.message_label.set_text(report);
+    }
+
+    #[func]
+    fn on_recipe_discovered(&mut self, recipe: GString) {
+        self.message_label
+            .set_text(format!("You worked out a new recipe: {recipe}").into());
+    }
+
+    #[func]
+    fn on_crafted(&mut self, _recipe: GString, item: GString, quantity: i64) {
+        self.message_label
+            .set_text(format!("You made {quantity} {item}").into());
+    }
+
+    #[func]
+    fn on_piece_discovered(&mut self, piece: GString, set: GString) {
+        self.message_label
+            .set_text(format!("{piece} is part of the {set} set").into());
+    }
+
+    #[func]
+    fn on_property_changed(&mut self, property: GString, level: i64) {
+        let text = if level > 0 {
+            format!("{property} is now level {level}")
+        } else {
+            format!("You lost {property}, the upkeep went unpaid")
+        };
+        self.message_label.set_text(text.into());
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for Hud {
+    fn ready(&mut self) {
+        let mut world_node = self.base_mut().get_node_as::<World>("../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        let on_debt_changed_callable = self.base().callable("on_debt_changed");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        world_node.connect("on_debt_changed".into(), on_debt_changed_callable);
+        let on_message_callable = self.base().callable("on_message");
+        world_node.connect("on_message".into(), on_message_callable);
+        let on_inspected_callable = self.base().callable("on_inspected");
+        world_node.connect("on_inspected".into(), on_inspected_callable);
+        let on_fake_detected_callable = self.base().callable("on_fake_detected");
+        world_node.connect("on_fake_detected".into(), on_fake_detected_callable);
+        let on_goal_changed_callable = self.base().callable("on_goal_changed");
+        world_node.connect("on_goal_changed".into(), on_goal_changed_callable);
+        let on_travelled_callable = self.base().callable("on_travelled");
+        world_node.connect("on_travelled".into(), on_travelled_callable);
+        let on_recipe_discovered_callable = self.base().callable("on_recipe_discovered");
+        world_node.connect("on_recipe_discovered".into(), on_recipe_discovered_callable);
+        let on_crafted_callable = self.base().callable("on_crafted");
+        world_node.connect("on_crafted".into(), on_crafted_callable);
+        let on_piece_discovered_callable = self.base().callable("on_piece_discovered");
+        world_node.connect("on_piece_discovered".into(), on_piece_discovered_callable);
+        let on_property_changed_callable = self.base().callable("on_property_changed");
+        world_node.connect("on_property_changed".into(), on_property_changed_callable);
+
+        let mut inventory_node = self
+            .base_mut()
+            .get_node_as::<Inventory>("../Player/Inventory");
+        let on_funds_changed_callable = self.base().callable("on_funds_changed");
+        inventory_node.connect("on_funds_changed".into(), on_funds_changed_callable);
+
+        let (day, hour, debt, location) = {
+            let world = world_node.bind();
+            (
+                world.get_day(),
+                world.get_hour(),
+                world.get_debt(),
+                world.get_location(),
+            )
+        };
+        let funds = inventory_node.bind().get_funds();
+        self.on_time_advanced(day, hour);
+        self.on_funds_changed(funds);
+        self.on_debt_changed(debt);
+        self.location_label.set_text(location);
+    }
+}
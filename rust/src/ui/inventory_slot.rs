@@ -1,5 +1,8 @@
 use godot::{
-    classes::{Button, IVBoxContainer, Label, MenuButton, Texture2D, TextureRect, VBoxContainer},
+    classes::{
+        Button, ColorRect, IVBoxContainer, Label, MenuButton, ProgressBar, Texture2D, TextureRect,
+        VBoxContainer,
+    },
     prelude::*,
 };
 
@@ -52,6 +55,12 @@ pub struct InventorySlot {
     #[init(node = "PriceLabel")]
     price_label: OnReady<Gd<Label>>,
     #[var]
+    #[init(node = "NinePatchRect/MarginIndicator")]
+    margin_indicator: OnReady<Gd<ColorRect>>,
+    #[var]
+    #[init(node = "NinePatchRect/FreshnessBar")]
+    freshness_bar: OnReady<Gd<ProgressBar>>,
+    #[var]
     #[init(node = "NinePatchRect/OnClickButton")]
     on_click_button: OnReady<Gd<Button>>,
     #[var]
@@ -60,6 +69,48 @@ pub struct InventorySlot {
     base: Base<VBoxContainer>,
 }
 
+impl InventorySlot {
+    // Shows what the stack cost, what it sells for at best and a dot that
+    // is green for a profit, red for a loss and grey when the cost is unknown
+    pub fn show_margin(&mut self, cost: Option<u32>, sell: Option<u32>) {
+        let cost_text = cost.map_or("-".to_string(), |c| c.to_string());
+        let sell_text = sell.map_or("-".to_string(), |s| s.to_string());
+        let color = match (cost, sell) {
+            (Some(cost), Some(sell)) if sell > cost => Color::from_rgb(0.2, 0.6, 0.2),
+            (Some(cost), Some(sell)) if sell < cost => Color::from_rgb(0.86, 0.2, 0.18),
+            (Some(_), Some(_)) => Color::from_rgb(0.85, 0.65, 0.1),
+            _ => Color::GRAY,
+        };
+
+        self.price_label
+            .set_text(format!("{cost_text} > {sell_text}").into());
+        self.price_label
+            .set_tooltip_text(format!("Paid {cost_text} each, sells for {sell_text}").into());
+        self.price_label.set_visible(true);
+        self.margin_indicator.set_color(color);
+        self.margin_indicator.set_visible(true);
+    }
+
+    pub fn hide_margin(&mut self) {
+        self.price_label.set_visible(false);
+        self.margin_indicator.set_visible(false);
+    }
+
+    // Fills from red to green as the stack goes from spoiled to fresh
+    pub fn show_freshness(&mut self, freshness: f32, label: &str) {
+        let color =
+            Color::from_rgb(0.86, 0.2, 0.18).lerp(Color::from_rgb(0.2, 0.6, 0.2), freshness as f64);
+        self.freshness_bar.set_value(freshness as f64);
+        self.freshness_bar.set_modulate(color);
+        self.freshness_bar.set_tooltip_text(label.into());
+        self.freshness_bar.set_visible(true);
+    }
+
+    pub fn hide_freshness(&mut self) {
+        self.freshness_bar.set_visible(false);
+    }
+}
+
 #[godot_api]
 impl IVBoxContainer for InventorySlot {
     fn ready(&mut self) {
@@ -1,37 +1,239 @@
 use godot::{
-    classes::{CanvasLayer, GridContainer, ICanvasLayer},
+    classes::{CanvasLayer, CheckButton, GridContainer, ICanvasLayer, Label, VBoxContainer},
     prelude::*,
 };
 
-use crate::{inventory::Inventory, item::Item};
+use crate::{
+    economy::{appraisal, freshness},
+    inventory::Inventory,
+    item::Item,
+    world::World,
+};
 
 use super::inventory_slot::{InventorySlot, SlotType};
 
+// Slot menu entry added while the player owns a loupe, the others are in the
+// slot scene
+const LOUPE_ID: i64 = 4;
+
 #[derive(GodotClass)]
 #[class(tool, init, base=CanvasLayer)]
 pub struct InventoryUI {
     #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/GridContainer")]
     grid_container: OnReady<Gd<GridContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/MarginsButton")]
+    margins_button: OnReady<Gd<CheckButton>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/KeyItemsButton")]
+    key_items_button: OnReady<Gd<CheckButton>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/KeyItems")]
+    key_items: OnReady<Gd<VBoxContainer>>,
     inventory_node: Option<Gd<Inventory>>,
+    world_node: Option<Gd<World>>,
     #[export]
     #[init(val = 8)]
     size: i64,
     #[export]
     #[init(val = 4)]
     columns: i64,
+    // Cost basis, best sell price and margin under every slot
+    #[export]
+    show_margins: bool,
     base: Base<CanvasLayer>,
 }
 
+impl InventoryUI {
+    fn create_slots(&mut self) {
+        for slot_index in 0..self.size {
+            let inventory_slot_scene =
+                match load::<PackedScene>("res://Scenes/UI/inventory_slot.tscn").instantiate() {
+                    Some(scene) => scene,
+                    None => {
+                        godot_error!("Failed to load inventory slot scene");
+                        return;
+                    }
+                };
+
+            self.grid_container.add_child(inventory_slot_scene.clone());
+
+            if let Ok(slot_gd) = inventory_slot_scene.try_cast::<InventorySlot>() {
+                let slot_menu_callable = self
+                    .base()
+                    .callable("on_slot_menu")
+                    .bindv(varray![slot_index]);
+                if let Some(mut popup) = slot_gd.bind().get_menu_button().get_popup() {
+                    popup.connect("id_pressed".into(), slot_menu_callable);
+                }
+            }
+        }
+    }
+
+    // The loupe entry is only in the slot menus while the player has one
+    fn update_loupe_entries(&mut self) {
+        let has_loupe = self.inventory_node.as_ref().is_some_and(|inventory_gd| {
+            inventory_gd.bind().count_item(appraisal::LOUPE_ITEM.into()) > 0
+        });
+        for child in self.grid_container.get_children().iter_shared() {
+            let Ok(slot_gd) = child.try_cast::<InventorySlot>() else {
+                continue;
+            };
+            let Some(mut popup) = slot_gd.bind().get_menu_button().get_popup() else {
+                continue;
+            };
+            let index = popup.get_item_index(LOUPE_ID as i32);
+            if has_loupe && index < 0 {
+                popup
+                    .add_item_ex("Use loupe".into())
+                    .id(LOUPE_ID as i32)
+                    .done();
+            } else if !has_loupe && index >= 0 {
+                popup.remove_item(index);
+            }
+        }
+    }
+
+    fn update_margin(&self, slot_gd: &mut Gd<InventorySlot>, item_gd: &Gd<Item>) {
+        let world_gd = match self.world_node.as_ref() {
+            Some(world_gd) if self.show_margins => world_gd,
+            _ => {
+                slot_gd.bind_mut().hide_margin();
+                return;
+            }
+        };
+        let (cost, sell) = {
+            let world = world_gd.bind();
+            (
+                item_gd.bind().cost_basis(),
+                world.best_offer(item_gd).map(|(_, price)| price),
+            )
+        };
+        slot_gd.bind_mut().show_margin(cost, sell);
+    }
+
+    fn update_freshness(slot_gd: &mut Gd<InventorySlot>, item_gd: &Gd<Item>) {
+        let (is_perishable, freshness) = {
+            let item = item_gd.bind();
+            (item.is_perishable(), item.get_freshness())
+        };
+        if is_perishable {
+            slot_gd
+                .bind_mut()
+                .show_freshness(freshness, freshness::label(freshness));
+        } else {
+            slot_gd.bind_mut().hide_freshness();
+        }
+    }
+
+    fn update_value_tooltip(slot_gd: &Gd<InventorySlot>, item_gd: &Gd<Item>) {
+        let label = item_gd.bind().value_label();
+        slot_gd
+            .bind()
+            .get_menu_button()
+            .clone()
+            .set_tooltip_text(label.into());
+    }
+}
+
 #[godot_api]
 impl InventoryUI {
     #[func]
     fn toggle(&mut self) {
         let is_visible = self.base().is_visible();
         self.base_mut().set_visible(!is_visible);
+
+        // Prices moved and trades happened while the bag was closed
+        if !is_visible {
+            self.update_margins();
+        }
+    }
+
+    // Deferred when items come and go, the world may be busy handing them over
+    #[func]
+    fn update_margins(&mut self) {
+        let Some(inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let items = inventory_gd.bind().get_items();
+        for (slot_index, item_gd) in items.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            if let Ok(mut slot_gd) = self
+                .grid_container
+                .get_children()
+                .at(slot_index)
+                .try_cast::<InventorySlot>()
+            {
+                self.update_margin(&mut slot_gd, &item_gd);
+            }
+        }
+    }
+
+    // Slots follow the inventory's order, one item per slot
+    #[func]
+    fn update_freshness_bars(&mut self) {
+        let Some(inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let items = inventory_gd.bind().get_items();
+        for (slot_index, item_gd) in items.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            if let Ok(mut slot_gd) = self
+                .grid_container
+                .get_children()
+                .at(slot_index)
+                .try_cast::<InventorySlot>()
+            {
+                Self::update_freshness(&mut slot_gd, &item_gd);
+                Self::update_value_tooltip(&slot_gd, &item_gd);
+            }
+        }
+    }
+
+    // Quest items live on their own tab instead of the slots
+    #[func]
+    fn update_key_items(&mut self) {
+        for mut child in self.key_items.get_children().iter_shared() {
+            self.key_items.remove_child(child.clone());
+            child.queue_free();
+        }
+        let Some(inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let key_items = inventory_gd.bind().get_key_items();
+        if key_items.is_empty() {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::DIM_GRAY);
+            label.set_text("No key items".into());
+            self.key_items.add_child(label.upcast());
+        }
+        for item_gd in key_items.iter_shared().flatten() {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(item_gd.bind().get_name());
+            self.key_items.add_child(label.upcast());
+        }
+    }
+
+    #[func]
+    fn on_key_items_toggled(&mut self, show_key_items: bool) {
+        self.grid_container.set_visible(!show_key_items);
+        self.key_items.set_visible(show_key_items);
+        if show_key_items {
+            self.update_key_items();
+        }
+    }
+
+    #[func]
+    fn on_margins_toggled(&mut self, show_margins: bool) {
+        self.show_margins = show_margins;
+        self.update_margins();
     }
 
     #[func]
     fn add_item(&mut self, item_gd: Gd<Item>) {
+        self.update_loupe_entries();
         let empty_slot_index = self.get_empty_slot_index();
 
         if let Ok(mut slot_gd) = self
@@ -51,6 +253,15 @@ impl InventoryUI {
             }
 
             slot_gd.bind_mut().set_is_empty(false);
+            if let Some(mut popup) = slot_gd.bind().get_menu_button().get_popup() {
+                let drop_index = popup.get_item_index(1);
+                popup.set_item_disabled(drop_index, !item_gd.bind().is_droppable());
+            }
+            Self::update_value_tooltip(&slot_gd, &item_gd);
+            Self::update_freshness(&mut slot_gd, &item_gd);
+            if self.show_margins {
+                self.base_mut().call_deferred("update_margins".into(), &[]);
+            }
 
             let mut menu_button_context = slot_gd.bind().get_menu_button().clone();
             menu_button_context.set_disabled(false);
@@ -76,19 +287,89 @@ impl InventoryUI {
         }
     }
 
+    // Lays every slot out again from the inventory, used when items leave it
+    #[func]
+    fn rebuild(&mut self) {
+        for mut child in self.grid_container.get_children().iter_shared() {
+            self.grid_container.remove_child(child.clone());
+            child.queue_free();
+        }
+        self.create_slots();
+
+        if let Some(inventory_gd) = self.inventory_node.clone() {
+            let items = inventory_gd.bind().get_items();
+            for item_gd in items.iter_shared().flatten() {
+                self.add_item(item_gd);
+            }
+        }
+    }
+
+    // Slot i shows the i-th item of the inventory
+    #[func]
+    fn on_slot_menu(&mut self, id: i64, slot_index: i64) {
+        let (Some(inventory_gd), Some(mut world_gd)) =
+            (self.inventory_node.clone(), self.world_node.clone())
+        else {
+            return;
+        };
+        let Some(item_gd) = inventory_gd
+            .bind()
+            .get_items()
+            .get(slot_index as usize)
+            .flatten()
+        else {
+            return;
+        };
+
+        let method = match id {
+            // Deferred, the slots are laid out again once the stack is gone
+            1 => {
+                world_gd.call_deferred("drop_item".into(), &[item_gd.to_variant()]);
+                return;
+            }
+            2 => "Skill",
+            3 => "Paid",
+            LOUPE_ID => "Loupe",
+            _ => return,
+        };
+
+        if !world_gd.bind_mut().appraise(item_gd.clone(), method.into()) {
+            return;
+        }
+
+        if let Ok(slot_gd) = self
+            .grid_container
+            .get_children()
+            .at(slot_index as usize)
+            .try_cast::<InventorySlot>()
+        {
+            Self::update_value_tooltip(&slot_gd, &item_gd);
+        }
+    }
+
+    // Found by position, stacks of different freshness share a name
     #[func]
     fn update_stacks_label(&mut self, item_gd: Gd<Item>, stacks: i64) {
-        let slot_index = self.get_item_slot_index_by_name(item_gd.bind().get_name().clone());
+        let Some(slot_index) = self.inventory_node.as_ref().and_then(|inventory_gd| {
+            inventory_gd
+                .bind()
+                .get_items()
+                .iter_shared()
+                .position(|i| i.as_ref() == Some(&item_gd))
+        }) else {
+            return;
+        };
 
         if let Ok(mut slot_gd) = self
             .grid_container
             .get_children()
-            .at(slot_index as usize)
+            .at(slot_index)
             .try_cast::<InventorySlot>()
         {
             let mut stack_label = slot_gd.bind().get_stack_label().clone();
             stack_label.set_text(stacks.to_string().into());
             slot_gd.bind_mut().set_stack_label(stack_label);
+            Self::update_freshness(&mut slot_gd, &item_gd);
         }
     }
 
@@ -143,19 +424,28 @@ impl ICanvasLayer for InventoryUI {
             update_stacks_label_callable,
         );
 
+        let rebuild_callable = self.base().callable("rebuild");
+        inventory_node.connect("on_items_removed".into(), rebuild_callable);
+        let update_freshness_bars_callable = self.base().callable("update_freshness_bars");
+        inventory_node.connect("on_items_aged".into(), update_freshness_bars_callable);
+
+        let update_key_items_callable = self.base().callable("update_key_items");
+        inventory_node.connect("on_key_items_changed".into(), update_key_items_callable);
+
+        self.world_node = self.base().try_get_node_as::<World>("../../World");
+
         self.grid_container.set_columns(self.columns as i32);
 
-        for _ in 0..self.size {
-            let inventory_slot_scene =
-                match load::<PackedScene>("res://Scenes/UI/inventory_slot.tscn").instantiate() {
-                    Some(scene) => scene,
-                    None => {
-                        godot_error!("Failed to load inventory slot scene");
-                        return;
-                    }
-                };
+        let show_margins = self.show_margins;
+        self.margins_button.set_pressed_no_signal(show_margins);
+        let on_margins_toggled_callable = self.base().callable("on_margins_toggled");
+        self.margins_button
+            .connect("toggled".into(), on_margins_toggled_callable);
+        self.key_items.set_visible(false);
+        let on_key_items_toggled_callable = self.base().callable("on_key_items_toggled");
+        self.key_items_button
+            .connect("toggled".into(), on_key_items_toggled_callable);
 
-            self.grid_container.add_child(inventory_slot_scene.clone());
-        }
+        self.create_slots();
     }
 }
@@ -0,0 +1,129 @@
+use godot::{
+    classes::{CanvasLayer, ICanvasLayer, InputEvent, InputEventKey, Label, VBoxContainer},
+    global::Key,
+    prelude::*,
+};
+
+use crate::{economy::auction, world::World};
+
+// Most recent entries listed under the totals
+const RECENT_ENTRIES: usize = 10;
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct LedgerUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Summary")]
+    summary_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Columns/Days")]
+    days: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Columns/Items")]
+    items: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Recent")]
+    recent: OnReady<Gd<VBoxContainer>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+impl LedgerUI {
+    fn add_line(list: &mut Gd<VBoxContainer>, text: String) {
+        let mut label = Label::new_alloc();
+        label.add_theme_color_override("font_color".into(), Color::BLACK);
+        label.set_text(text.into());
+        list.add_child(label.upcast());
+    }
+
+    fn clear(list: &mut Gd<VBoxContainer>) {
+        for mut child in list.get_children().iter_shared() {
+            list.remove_child(child.clone());
+            child.queue_free();
+        }
+    }
+}
+
+#[godot_api]
+impl LedgerUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let world = world_gd.bind();
+        let ledger = world.ledger().clone();
+        // Held goods are valued at the best price a market asks for them
+        let unrealised = ledger.unrealised(|item| auction::market_price(world.simulation(), item));
+        drop(world);
+
+        self.summary_label.set_text(
+            format!(
+                "Realised {:+}   Unrealised {unrealised:+}",
+                ledger.realised()
+            )
+            .into(),
+        );
+
+        Self::clear(&mut self.days);
+        for d in ledger.by_day().iter().rev() {
+            let text = format!(
+                "Day {}: bought {}, sold {}, fees {}, repaid {}, profit {:+}",
+                d.day, d.bought, d.sold, d.fees, d.loan_payments, d.profit
+            );
+            Self::add_line(&mut self.days, text);
+        }
+
+        Self::clear(&mut self.items);
+        for t in ledger.by_item() {
+            let basis = match ledger.cost_basis(&t.item) {
+                Some(cost) => format!(", holding {} at {cost}", ledger.held(&t.item)),
+                None => String::new(),
+            };
+            let text = format!(
+                "{}: bought {} for {}, sold {} for {}, profit {:+}{basis}",
+                t.item, t.bought, t.spent, t.sold, t.earned, t.profit
+            );
+            Self::add_line(&mut self.items, text);
+        }
+
+        Self::clear(&mut self.recent);
+        for e in ledger.entries().iter().rev().take(RECENT_ENTRIES) {
+            Self::add_line(&mut self.recent, e.to_string());
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for LedgerUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && !e.is_echo() && e.get_keycode() == Key::L {
+                self.toggle();
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, InputEvent, InputEventKey, Label, VBoxContainer},
+    global::Key,
+    prelude::*,
+};
+
+use crate::world::World;
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct MapUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Location")]
+    location_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Roads")]
+    roads: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Itinerary")]
+    itinerary: OnReady<Gd<VBoxContainer>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+impl MapUI {
+    fn clear(list: &mut Gd<VBoxContainer>) {
+        for mut child in list.get_children().iter_shared() {
+            list.remove_child(child.clone());
+            child.queue_free();
+        }
+    }
+}
+
+#[godot_api]
+impl MapUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn on_travelled(&mut self, _town: GString, _report: GString) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let (location, roads, plans) = {
+            let world = world_gd.bind();
+            let map = world.routes();
+            let roads: Vec<String> = map
+                .routes()
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{} - {}: {}h, toll {}, food {}/day",
+                        r.towns.0,
+                        r.towns.1,
+                        r.duration.hours(),
+                        r.toll,
+                        r.food_per_day
+                    )
+                })
+                .collect();
+            (map.location.clone(), roads, world.route_plans())
+        };
+
+        self.location_label
+            .set_text(format!("You are in {location}").into());
+
+        Self::clear(&mut self.roads);
+        for text in roads {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(text.into());
+            self.roads.add_child(label.upcast());
+        }
+
+        // Buttons call the world directly, travelling signals back into this UI
+        Self::clear(&mut self.itinerary);
+        for plan in plans {
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Travel to {plan}").into());
+            let travel_callable = world_gd
+                .callable("travel")
+                .bindv(varray![GString::from(plan.to.as_str())]);
+            button.connect("pressed".into(), travel_callable);
+            self.itinerary.add_child(button.upcast());
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for MapUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../World");
+        let on_travelled_callable = self.base().callable("on_travelled");
+        world_node.connect("on_travelled".into(), on_travelled_callable);
+        self.world_node = Some(world_node);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && !e.is_echo() && e.get_keycode() == Key::M {
+                self.toggle();
+            }
+        }
+    }
+}
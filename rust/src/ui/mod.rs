@@ -1,2 +1,16 @@
+pub mod auction_ui;
+pub mod collection_ui;
+pub mod collector_ui;
+pub mod crafting_ui;
+pub mod estate_ui;
+pub mod hud;
 pub mod inventory_slot;
 pub mod inventory_ui;
+pub mod ledger_ui;
+pub mod map_ui;
+pub mod moneylender_ui;
+pub mod notice_board_ui;
+pub mod results_ui;
+pub mod shop_ui;
+pub mod stall_ui;
+pub mod stash_ui;
@@ -0,0 +1,150 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, Label, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{economy::finance, moneylender::Moneylender, world::World};
+
+// Sums the lender offers, as long as they stay under the lender's limit
+const LOAN_AMOUNTS: [u64; 4] = [100, 250, 500, 1000];
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct MoneylenderUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "..")]
+    moneylender_node: OnReady<Gd<Moneylender>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+impl MoneylenderUI {
+    fn add_button(&mut self, text: String, callable: Callable) {
+        let mut button = Button::new_alloc();
+        button.set_text(text.into());
+        button.connect("pressed".into(), callable);
+        self.list.add_child(button.upcast());
+    }
+}
+
+#[godot_api]
+impl MoneylenderUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    // Offers first, then the player's loans with this lender
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let name = self.moneylender_node.bind().get_lender();
+        self.title_label.set_text(name.clone());
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let world = world_gd.bind();
+        let Some(terms) = world.lender(&name.to_string()).cloned() else {
+            return;
+        };
+        let owed = world.loans().owed_to(&terms.name);
+        let loans: Vec<_> = world
+            .loans()
+            .loans()
+            .iter()
+            .filter(|l| l.lender == terms.name)
+            .cloned()
+            .collect();
+        drop(world);
+
+        for amount in LOAN_AMOUNTS {
+            if owed + amount > terms.max_principal {
+                continue;
+            }
+            let payment = finance::installment(amount, terms.daily_rate, terms.installments);
+            let text = format!(
+                "Borrow {amount} at {:.1}% a day, {} payments of {payment} every {} days",
+                terms.daily_rate * 100.,
+                terms.installments,
+                finance::INSTALLMENT_DAYS
+            );
+            let borrow_callable = self.base().callable("borrow").bindv(varray![amount as i64]);
+            self.add_button(text, borrow_callable);
+        }
+
+        for loan in loans {
+            // Once the installment is covered the rest can be paid off early
+            let amount = match loan.due_now() {
+                0 => loan.balance,
+                due => due,
+            };
+            let repay_callable = self
+                .base()
+                .callable("repay")
+                .bindv(varray![loan.id as i64, amount as i64]);
+            self.add_button(format!("{loan}. Pay {amount}"), repay_callable);
+        }
+    }
+
+    #[func]
+    fn borrow(&mut self, amount: i64) {
+        let lender = self.moneylender_node.bind().get_lender();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().take_loan(lender, amount);
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn repay(&mut self, id: i64, amount: i64) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().repay_loan(id, amount);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for MoneylenderUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.moneylender_node
+            .connect("on_toggle_loans".into(), toggle_callable);
+        self.moneylender_node
+            .connect("on_close_loans".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+    }
+}
@@ -0,0 +1,153 @@
+use godot::{
+    classes::{Button, CanvasLayer, ICanvasLayer, Label, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{economy::contract::ContractState, notice_board::NoticeBoard, world::World};
+
+// Latest rumours pinned under the market news
+const RUMOURS_SHOWN: usize = 5;
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct NoticeBoardUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/List")]
+    list: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "..")]
+    notice_board_node: OnReady<Gd<NoticeBoard>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl NoticeBoardUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if is_visible {
+            return;
+        }
+
+        // Accepted contracts are handed in as soon as the player checks the board
+        let town = self.notice_board_node.bind().get_town();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().deliver_contracts(town);
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let town = self.notice_board_node.bind().get_town();
+        self.title_label
+            .set_text(format!("{town} Notice Board").into());
+
+        for mut child in self.list.get_children().iter_shared() {
+            self.list.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        // Market news is pinned above the contracts
+        let announcements: Vec<_> = world_gd
+            .bind()
+            .calendar()
+            .in_town(&town.to_string())
+            .cloned()
+            .collect();
+        for announcement in announcements {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(format!("{}. {}", announcement.title, announcement.text).into());
+            label.set_tooltip_text(format!("Until day {}", announcement.ends_at.day()).into());
+            self.list.add_child(label.upcast());
+        }
+
+        // Then whatever people say the other traders are up to
+        let rumours: Vec<String> = world_gd
+            .bind()
+            .rivals()
+            .rumours_in(&town.to_string())
+            .take(RUMOURS_SHOWN)
+            .map(|r| r.to_string())
+            .collect();
+        for rumour in rumours {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::DIM_GRAY);
+            label.set_text(rumour.into());
+            self.list.add_child(label.upcast());
+        }
+
+        let contracts: Vec<_> = world_gd
+            .bind()
+            .contracts()
+            .in_town(&town.to_string())
+            .cloned()
+            .collect();
+
+        for contract in contracts {
+            let mut button = Button::new_alloc();
+
+            if contract.state == ContractState::Open {
+                button.set_text(format!("{contract}").into());
+
+                let accept_callable = self
+                    .base()
+                    .callable("accept")
+                    .bindv(varray![contract.id as i64]);
+                button.connect("pressed".into(), accept_callable);
+            } else {
+                button.set_text(format!("{contract} (accepted)").into());
+                button.set_disabled(true);
+            }
+
+            self.list.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn accept(&mut self, id: i64) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().accept_contract(id);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for NoticeBoardUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.notice_board_node
+            .connect("on_toggle_board".into(), toggle_callable);
+        self.notice_board_node
+            .connect("on_close_board".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+    }
+}
@@ -0,0 +1,126 @@
+use godot::{
+    classes::{CanvasLayer, ICanvasLayer, InputEvent, InputEventKey, Label, VBoxContainer},
+    global::Key,
+    prelude::*,
+};
+
+use crate::world::World;
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct ResultsUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/NetWorth")]
+    net_worth_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Goals")]
+    goals: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Results")]
+    results_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/HighScores")]
+    high_scores: OnReady<Gd<VBoxContainer>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+impl ResultsUI {
+    fn add_line(list: &mut Gd<VBoxContainer>, text: String) {
+        let mut label = Label::new_alloc();
+        label.add_theme_color_override("font_color".into(), Color::BLACK);
+        label.set_text(text.into());
+        list.add_child(label.upcast());
+    }
+
+    fn clear(list: &mut Gd<VBoxContainer>) {
+        for mut child in list.get_children().iter_shared() {
+            list.remove_child(child.clone());
+            child.queue_free();
+        }
+    }
+}
+
+#[godot_api]
+impl ResultsUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    // The results stay on the screen until the player closes it
+    #[func]
+    fn on_campaign_over(&mut self, results: GString, rank: i64) {
+        let text = if rank > 0 {
+            format!("{results}. New high score, rank {rank}!")
+        } else {
+            results.to_string()
+        };
+        self.results_label.set_text(text.into());
+        self.results_label.set_visible(true);
+        self.base_mut().set_visible(true);
+        self.refresh();
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let world = world_gd.bind();
+        let campaign = world.campaign().clone();
+        let net_worth = world.net_worth();
+        let high_scores: Vec<String> = world
+            .high_scores()
+            .for_scenario(&campaign.scenario)
+            .enumerate()
+            .map(|(i, r)| format!("{}. {} points, day {}", i + 1, r.score, r.day))
+            .collect();
+        drop(world);
+
+        let title = if campaign.is_over() {
+            "Campaign over".to_string()
+        } else {
+            campaign.scenario.clone()
+        };
+        self.title_label.set_text(title.into());
+        self.net_worth_label
+            .set_text(format!("Net worth: {net_worth}").into());
+
+        Self::clear(&mut self.goals);
+        for goal in campaign.goals() {
+            Self::add_line(&mut self.goals, goal.to_string());
+        }
+
+        Self::clear(&mut self.high_scores);
+        Self::add_line(&mut self.high_scores, "High scores".to_string());
+        for line in high_scores {
+            Self::add_line(&mut self.high_scores, line);
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for ResultsUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+        self.results_label.set_visible(false);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../World");
+        let on_campaign_over_callable = self.base().callable("on_campaign_over");
+        world_node.connect("on_campaign_over".into(), on_campaign_over_callable);
+        self.world_node = Some(world_node);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if let Ok(e) = event.try_cast::<InputEventKey>() {
+            if e.is_pressed() && !e.is_echo() && e.get_keycode() == Key::G {
+                self.toggle();
+            }
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use godot::{
+    classes::{Button, CanvasLayer, GridContainer, HBoxContainer, ICanvasLayer, Label, SpinBox},
+    prelude::*,
+};
+
+use crate::{
+    economy::stacking,
+    inventory::Inventory,
+    item::Item,
+    merchant::Merchant,
+    world::{ShopGood, World},
+};
+
+use super::inventory_slot::InventorySlot;
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct ShopUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/GridContainer")]
+    grid_container: OnReady<Gd<GridContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order")]
+    order: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/ItemLabel")]
+    item_label: OnReady<Gd<Label>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/QuantitySpinBox"
+    )]
+    quantity_spin_box: OnReady<Gd<SpinBox>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/MaxAffordableButton"
+    )]
+    max_affordable_button: OnReady<Gd<Button>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/MaxFitsButton"
+    )]
+    max_fits_button: OnReady<Gd<Button>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/TotalLabel"
+    )]
+    total_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Order/BuyButton")]
+    buy_button: OnReady<Gd<Button>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Sellables")]
+    sellables: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Buyback")]
+    buyback: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Licences")]
+    licences: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "..")]
+    merchant_node: OnReady<Gd<Merchant>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    // Shelf position of the good the order is for
+    selected: Option<usize>,
+    #[export]
+    #[init(val = 4)]
+    columns: i64,
+    base: Base<CanvasLayer>,
+}
+
+impl ShopUI {
+    fn create_slots(&mut self, count: usize) {
+        for mut child in self.grid_container.get_children().iter_shared() {
+            self.grid_container.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        for slot_index in 0..count {
+            let inventory_slot_scene =
+                match load::<PackedScene>("res://Scenes/UI/inventory_slot.tscn").instantiate() {
+                    Some(scene) => scene,
+                    None => {
+                        godot_error!("Failed to load inventory slot scene");
+                        return;
+                    }
+                };
+
+            if let Ok(mut slot_gd) = inventory_slot_scene.try_cast::<InventorySlot>() {
+                slot_gd.bind_mut().set_single_button_press(true);
+                self.grid_container.add_child(slot_gd.clone().upcast());
+
+                let select_slot_callable = self
+                    .base()
+                    .callable("select_slot")
+                    .bindv(varray![slot_index as i64]);
+                slot_gd
+                    .bind()
+                    .get_on_click_button()
+                    .clone()
+                    .connect("pressed".into(), select_slot_callable);
+            }
+        }
+    }
+
+    // The selected good, with the merchant's catalogue copy of it
+    fn selected_good(&self) -> Option<(ShopGood, Gd<Item>)> {
+        let world_gd = self.world_node.clone()?;
+        let market = self.merchant_node.bind().get_market();
+        let good = world_gd
+            .bind()
+            .shop_goods(&market)
+            .into_iter()
+            .nth(self.selected?)?;
+        let item_gd = self.merchant_node.bind().find_item(&good.item)?;
+        Some((good, item_gd))
+    }
+
+    // Most of the selected good the player can carry away, stock permitting
+    fn max_fits(&self) -> i64 {
+        match (self.selected_good(), self.inventory_node.as_ref()) {
+            (Some((good, item_gd)), Some(inventory_gd)) => {
+                inventory_gd.bind().room_for(item_gd).min(good.stock as i64)
+            }
+            _ => 0,
+        }
+    }
+
+    fn max_affordable(&self) -> i64 {
+        let (Some((good, _)), Some(world_gd), Some(inventory_gd)) = (
+            self.selected_good(),
+            self.world_node.as_ref(),
+            self.inventory_node.as_ref(),
+        ) else {
+            return 0;
+        };
+        let market = self.merchant_node.bind().get_market();
+        let funds = inventory_gd.bind().get_funds();
+        world_gd.bind().get_affordable(market, good.item, funds)
+    }
+
+    // Shows the order's total, each unit a little dearer as the shelf empties
+    fn update_total(&mut self) {
+        let (Some((good, _)), Some(world_gd), Some(inventory_gd)) = (
+            self.selected_good(),
+            self.world_node.clone(),
+            self.inventory_node.clone(),
+        ) else {
+            self.order.set_visible(false);
+            return;
+        };
+        let market = self.merchant_node.bind().get_market();
+        // Clamped quietly first, so the new limit doesn't fire value_changed
+        let stock = good.stock.max(1) as f64;
+        let quantity = self.quantity_spin_box.get_value().min(stock);
+        self.quantity_spin_box.set_value_no_signal(quantity);
+        self.quantity_spin_box.set_max(stock);
+
+        let quantity = quantity as i64;
+        let total = world_gd
+            .bind()
+            .get_quote(market, good.item.clone(), quantity);
+        let funds = inventory_gd.bind().get_funds();
+        let fits = quantity <= self.max_fits();
+
+        self.order.set_visible(true);
+        self.item_label.set_text(good.item);
+        let text = match total {
+            -1 => "Not available".to_string(),
+            total if total > funds => format!("{total} coins, can't afford"),
+            total if !fits => format!("{total} coins, won't fit"),
+            total => format!("{total} coins"),
+        };
+        self.total_label.set_text(text.into());
+        self.buy_button
+            .set_disabled(total < 0 || total > funds || !fits);
+    }
+
+    // One button per inventory stack the merchant trades in, and one per
+    // recent sale they still keep aside, plus the town's licences the player
+    // still lacks
+    fn list_sales(&mut self) {
+        let (world_gd, inventory_gd) = match (self.world_node.clone(), self.inventory_node.clone())
+        {
+            (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+            _ => return,
+        };
+        let market = self.merchant_node.bind().get_market();
+
+        for list in [&mut self.sellables, &mut self.buyback, &mut self.licences] {
+            for mut child in list.get_children().iter_shared() {
+                list.remove_child(child.clone());
+                child.queue_free();
+            }
+        }
+
+        let is_black_market = world_gd
+            .bind()
+            .simulation()
+            .market(&market.to_string())
+            .is_some_and(|m| m.black_market);
+        let items = inventory_gd.bind().get_items();
+        for (index, item_gd) in items.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            let (name, stacks, contraband, is_sellable) = {
+                let item = item_gd.bind();
+                (
+                    item.get_name(),
+                    item.get_stacks(),
+                    item.get_contraband(),
+                    item.is_sellable(),
+                )
+            };
+            // Contraband is only worth showing to a black market
+            if !is_sellable
+                || world_gd.bind().good(&market, &name).is_none()
+                || contraband && !is_black_market
+            {
+                continue;
+            }
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Sell {name} x{stacks}").into());
+            let sell_callable = self.base().callable("sell").bindv(varray![index as i64]);
+            button.connect("pressed".into(), sell_callable);
+            self.sellables.add_child(button.upcast());
+        }
+
+        let sold: Vec<_> = world_gd
+            .bind()
+            .buyback()
+            .in_market(&market.to_string())
+            .rev()
+            .map(|s| (s.id, s.to_string()))
+            .collect();
+        for (id, text) in sold {
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Buy back {text}").into());
+            let buy_back_callable = self.base().callable("buy_back").bindv(varray![id as i64]);
+            button.connect("pressed".into(), buy_back_callable);
+            self.buyback.add_child(button.upcast());
+        }
+
+        let Some(town) = world_gd
+            .bind()
+            .simulation()
+            .market(&market.to_string())
+            .map(|m| m.town.clone())
+        else {
+            return;
+        };
+        let missing: Vec<_> = world_gd
+            .bind()
+            .taxes()
+            .missing_licences(&town)
+            .cloned()
+            .collect();
+        for (category, price) in missing {
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Buy {category} licence ({price} coins)").into());
+            let buy_licence_callable = self.base().callable("buy_licence").bindv(varray![
+                GString::from(town.as_str()),
+                GString::from(category.as_str())
+            ]);
+            button.connect("pressed".into(), buy_licence_callable);
+            self.licences.add_child(button.upcast());
+        }
+    }
+
+    fn set_quantity(&mut self, quantity: i64) {
+        self.quantity_spin_box
+            .set_value_no_signal(quantity.max(1) as f64);
+        self.update_total();
+    }
+}
+
+#[godot_api]
+impl ShopUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    // Lists the merchant's goods, the stack label shows the remaining stock
+    #[func]
+    fn refresh(&mut self) {
+        let world_gd = match self.world_node.clone() {
+            Some(world_gd) => world_gd,
+            None => return,
+        };
+        let market = self.merchant_node.bind().get_market();
+        let goods = world_gd.bind().shop_goods(&market);
+
+        self.title_label.set_text(market);
+
+        if self.grid_container.get_child_count() as usize != goods.len() {
+            self.create_slots(goods.len());
+        }
+
+        for (slot_index, good) in goods.into_iter().enumerate() {
+            let (name, price, stock) = (good.item, good.price, good.stock);
+            if let Ok(mut slot_gd) = self
+                .grid_container
+                .get_children()
+                .at(slot_index)
+                .try_cast::<InventorySlot>()
+            {
+                slot_gd.bind_mut().set_is_empty(stock == 0);
+
+                if let Some(item_gd) = self.merchant_node.bind().find_item(&name) {
+                    if let Some(item_texture) = item_gd.bind().get_texture() {
+                        let mut texture_rect = slot_gd.bind().get_texture_rect().clone();
+                        texture_rect.set_texture(item_texture);
+                    }
+                }
+
+                let mut name_label = slot_gd.bind().get_name_label().clone();
+                name_label.set_text(name);
+
+                let mut stack_label = slot_gd.bind().get_stack_label().clone();
+                stack_label.set_text(stock.to_string().into());
+
+                let mut price_label = slot_gd.bind().get_price_label().clone();
+                price_label.set_text(price.to_string().into());
+                price_label.set_visible(true);
+
+                let mut on_click_button = slot_gd.bind().get_on_click_button().clone();
+                on_click_button.set_disabled(stock == 0);
+            }
+        }
+
+        self.update_total();
+        self.list_sales();
+    }
+
+    // Picks the good the order below the shelf is for
+    #[func]
+    fn select_slot(&mut self, slot_index: i64) {
+        self.selected = Some(slot_index as usize);
+        self.set_quantity(1);
+    }
+
+    #[func]
+    fn on_quantity_changed(&mut self, _value: f64) {
+        self.update_total();
+    }
+
+    #[func]
+    fn on_max_affordable(&mut self) {
+        let quantity = self.max_affordable();
+        self.set_quantity(quantity);
+    }
+
+    #[func]
+    fn on_max_fits(&mut self) {
+        let quantity = self.max_fits();
+        self.set_quantity(quantity);
+    }
+
+    // Buys the whole order as one trade, stacks are split to fit the slots
+    #[func]
+    fn buy(&mut self) {
+        let (mut world_gd, mut inventory_gd) =
+            match (self.world_node.clone(), self.inventory_node.clone()) {
+                (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+                _ => return,
+            };
+        let market = self.merchant_node.bind().get_market();
+        let Some((good, item_gd)) = self.selected_good() else {
+            return;
+        };
+
+        let quantity = self.quantity_spin_box.get_value() as i64;
+        let quote = world_gd
+            .bind()
+            .get_quote(market.clone(), good.item.clone(), quantity);
+        if quote < 0 || inventory_gd.bind().get_funds() < quote || quantity > self.max_fits() {
+            return;
+        }
+
+        let total = world_gd.bind_mut().buy(market.clone(), good.item, quantity);
+        if total < 0 || !inventory_gd.bind_mut().spend(total) {
+            return;
+        }
+
+        let unit_price = (total / quantity) as u32;
+        let max_stacks = item_gd.bind().get_max_stacks();
+        for stacks in stacking::chunks(quantity, max_stacks) {
+            let Some(item_gd_dub) = item_gd.duplicate() else {
+                continue;
+            };
+            if let Ok(mut new_item_gd) = item_gd_dub.try_cast::<Item>() {
+                new_item_gd.bind_mut().set_stacks(stacks);
+                world_gd
+                    .bind_mut()
+                    .value_bought_item(&market, &mut new_item_gd, unit_price);
+                inventory_gd.bind_mut().add_item(new_item_gd);
+            }
+        }
+
+        self.set_quantity(1);
+        self.refresh();
+    }
+
+    // Sells a whole inventory stack to the merchant
+    #[func]
+    fn sell(&mut self, index: i64) {
+        let (mut world_gd, mut inventory_gd) =
+            match (self.world_node.clone(), self.inventory_node.clone()) {
+                (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+                _ => return,
+            };
+        let Some(item_gd) = inventory_gd
+            .bind()
+            .get_items()
+            .get(index as usize)
+            .flatten()
+        else {
+            return;
+        };
+        let market = self.merchant_node.bind().get_market();
+        let stacks = item_gd.bind().get_stacks();
+
+        let total = world_gd
+            .bind_mut()
+            .sell_item(market, item_gd.clone(), stacks);
+        if total >= 0 && inventory_gd.bind_mut().take_item(item_gd, stacks) {
+            inventory_gd.bind_mut().earn(total);
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn buy_licence(&mut self, town: GString, category: GString) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().buy_licence(town, category);
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn buy_back(&mut self, id: i64) {
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().buy_back(id);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for ShopUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+        self.grid_container.set_columns(self.columns as i32);
+        self.order.set_visible(false);
+
+        let on_quantity_changed_callable = self.base().callable("on_quantity_changed");
+        self.quantity_spin_box
+            .connect("value_changed".into(), on_quantity_changed_callable);
+        let on_max_affordable_callable = self.base().callable("on_max_affordable");
+        self.max_affordable_button
+            .connect("pressed".into(), on_max_affordable_callable);
+        let on_max_fits_callable = self.base().callable("on_max_fits");
+        self.max_fits_button
+            .connect("pressed".into(), on_max_fits_callable);
+        let buy_callable = self.base().callable("buy");
+        self.buy_button.connect("pressed".into(), buy_callable);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.merchant_node
+            .connect("on_toggle_shop".into(), toggle_callable);
+        self.merchant_node
+            .connect("on_close_shop".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+
+        let inventory_node = self
+            .base_mut()
+            .get_node_as::<Inventory>("../../Player/Inventory");
+        self.inventory_node = Some(inventory_node);
+    }
+}
@@ -0,0 +1,258 @@
+use godot::{
+    classes::{Button, CanvasLayer, HBoxContainer, ICanvasLayer, Label, SpinBox, VBoxContainer},
+    prelude::*,
+};
+
+use crate::{inventory::Inventory, market_stall::MarketStall, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct StallUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/StatusLabel")]
+    status_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Report")]
+    report: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Slots")]
+    slots: OnReady<Gd<VBoxContainer>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Controls/RentButton"
+    )]
+    rent_button: OnReady<Gd<Button>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Controls/AskingSpinBox"
+    )]
+    asking_spin_box: OnReady<Gd<SpinBox>>,
+    #[init(
+        node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Controls/FloorSpinBox"
+    )]
+    floor_spin_box: OnReady<Gd<SpinBox>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Sellables")]
+    sellables: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "..")]
+    market_stall_node: OnReady<Gd<MarketStall>>,
+    world_node: Option<Gd<World>>,
+    inventory_node: Option<Gd<Inventory>>,
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl StallUI {
+    // Coming back to the stall empties the till and shows what sold meanwhile
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if is_visible {
+            return;
+        }
+
+        let town = self.market_stall_node.bind().get_town();
+        let Some(mut world_gd) = self.world_node.clone() else {
+            return;
+        };
+        let sales = world_gd.bind_mut().collect_stall(&town.to_string());
+
+        for mut child in self.report.get_children().iter_shared() {
+            self.report.remove_child(child.clone());
+            child.queue_free();
+        }
+        let total: u32 = sales.iter().map(|s| s.price).sum();
+        let mut summary = Label::new_alloc();
+        summary.add_theme_color_override("font_color".into(), Color::BLACK);
+        summary.set_text(match sales.len() {
+            0 => "Nothing sold while you were away".into(),
+            n => format!("Sold {n} items for {total} coins while you were away").into(),
+        });
+        self.report.add_child(summary.upcast());
+        for sale in sales {
+            let mut label = Label::new_alloc();
+            label.add_theme_color_override("font_color".into(), Color::BLACK);
+            label.set_text(sale.to_string().into());
+            self.report.add_child(label.upcast());
+        }
+
+        self.refresh();
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let (world_gd, inventory_gd) = match (self.world_node.clone(), self.inventory_node.clone())
+        {
+            (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+            _ => return,
+        };
+        let town = self.market_stall_node.bind().get_town();
+        self.title_label.set_text(format!("{town} Stall").into());
+
+        for mut child in self.slots.get_children().iter_shared() {
+            self.slots.remove_child(child.clone());
+            child.queue_free();
+        }
+        for mut child in self.sellables.get_children().iter_shared() {
+            self.sellables.remove_child(child.clone());
+            child.queue_free();
+        }
+
+        let world = world_gd.bind();
+        let now = world.simulation().time();
+        let Some(stall) = world.stall(&town.to_string()).cloned() else {
+            return;
+        };
+        drop(world);
+
+        let status = if stall.is_rented(now) {
+            format!("Rented until day {}", stall.rented_until().day())
+        } else {
+            "Not rented".to_string()
+        };
+        self.status_label.set_text(status.into());
+        self.rent_button
+            .set_text(format!("Rent a day for {}", stall.rent_per_day).into());
+
+        for (slot, display) in stall.slots().iter().enumerate() {
+            let mut button = Button::new_alloc();
+            match display {
+                Some(d) => {
+                    button.set_text(
+                        format!(
+                            "{} x{} for {} (no less than {}), take back",
+                            d.item, d.quantity, d.asking, d.floor
+                        )
+                        .into(),
+                    );
+                    let take_back_callable = self
+                        .base()
+                        .callable("take_back")
+                        .bindv(varray![slot as i64]);
+                    button.connect("pressed".into(), take_back_callable);
+                }
+                None => {
+                    button.set_text("Empty".into());
+                    button.set_disabled(true);
+                }
+            }
+            self.slots.add_child(button.upcast());
+        }
+
+        if !stall.is_rented(now) || stall.slots().iter().all(|s| s.is_some()) {
+            return;
+        }
+
+        // One button per inventory stack the player could put out
+        let items = inventory_gd.bind().get_items();
+        for (index, item_gd) in items.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            let (name, stacks) = {
+                let item = item_gd.bind();
+                if !item.is_sellable() {
+                    continue;
+                }
+                (item.get_name(), item.get_stacks())
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Display {name} x{stacks}").into());
+
+            let display_callable = self.base().callable("display").bindv(varray![index as i64]);
+            button.connect("pressed".into(), display_callable);
+
+            self.sellables.add_child(button.upcast());
+        }
+    }
+
+    #[func]
+    fn rent(&mut self) {
+        let town = self.market_stall_node.bind().get_town();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().rent_stall(town, 1);
+        }
+        self.refresh();
+    }
+
+    // Puts the whole stack in the first empty slot
+    #[func]
+    fn display(&mut self, index: i64) {
+        let (mut world_gd, inventory_gd) =
+            match (self.world_node.clone(), self.inventory_node.clone()) {
+                (Some(world_gd), Some(inventory_gd)) => (world_gd, inventory_gd),
+                _ => return,
+            };
+        let Some(item_gd) = inventory_gd
+            .bind()
+            .get_items()
+            .get(index as usize)
+            .flatten()
+        else {
+            return;
+        };
+        let town = self.market_stall_node.bind().get_town();
+        let Some(slot) = world_gd
+            .bind()
+            .stall(&town.to_string())
+            .and_then(|s| s.slots().iter().position(|d| d.is_none()))
+        else {
+            return;
+        };
+
+        let stacks = item_gd.bind().get_stacks();
+        let asking = self.asking_spin_box.get_value() as i64;
+        let floor = self.floor_spin_box.get_value() as i64;
+        world_gd
+            .bind_mut()
+            .display_item(town, slot as i64, item_gd, stacks, asking, floor);
+
+        self.refresh();
+    }
+
+    #[func]
+    fn take_back(&mut self, slot: i64) {
+        let town = self.market_stall_node.bind().get_town();
+        if let Some(mut world_gd) = self.world_node.clone() {
+            world_gd.bind_mut().take_back_display(town, slot);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for StallUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.market_stall_node
+            .connect("on_toggle_stall".into(), toggle_callable);
+        self.market_stall_node
+            .connect("on_close_stall".into(), close_callable);
+
+        let rent_callable = self.base().callable("rent");
+        self.rent_button.connect("pressed".into(), rent_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+
+        let inventory_node = self
+            .base_mut()
+            .get_node_as::<Inventory>("../../Player/Inventory");
+        self.inventory_node = Some(inventory_node);
+    }
+}
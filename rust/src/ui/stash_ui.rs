@@ -0,0 +1,184 @@
+use godot::{
+    classes::{Button, CanvasLayer, HBoxContainer, ICanvasLayer, Label},
+    prelude::*,
+};
+
+use crate::{economy::freshness, inventory::Inventory, item::Item, stash::Stash, world::World};
+
+#[derive(GodotClass)]
+#[class(init, base=CanvasLayer)]
+pub struct StashUI {
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Label")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Stored")]
+    stored: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "./MarginContainer/NinePatchRect/MarginContainer/VBoxContainer/Carried")]
+    carried: OnReady<Gd<HBoxContainer>>,
+    #[init(node = "..")]
+    stash_node: OnReady<Gd<Stash>>,
+    inventory_node: Option<Gd<Inventory>>,
+    world_node: Option<Gd<World>>,
+    base: Base<CanvasLayer>,
+}
+
+impl StashUI {
+    fn describe(item_gd: &Gd<Item>) -> String {
+        let item = item_gd.bind();
+        let text = format!("{} x{}", item.get_name(), item.get_stacks());
+        if item.is_perishable() {
+            format!("{text} ({})", freshness::label(item.get_freshness()))
+        } else {
+            text
+        }
+    }
+}
+
+#[godot_api]
+impl StashUI {
+    #[func]
+    fn toggle(&mut self) {
+        let is_visible = self.base().is_visible();
+        self.base_mut().set_visible(!is_visible);
+
+        if !is_visible {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_advanced(&mut self, _day: i64, _hour: i64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+
+    #[func]
+    fn refresh(&mut self) {
+        let Some(inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let (stored, capacity) = {
+            let stash = self.stash_node.bind();
+            (stash.get_items(), stash.total_capacity())
+        };
+        self.title_label
+            .set_text(format!("Cold Stash ({}/{capacity})", stored.len()).into());
+
+        for list in [&mut self.stored, &mut self.carried] {
+            for mut child in list.get_children().iter_shared() {
+                list.remove_child(child.clone());
+                child.queue_free();
+            }
+        }
+
+        for (index, item_gd) in stored.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Take {}", Self::describe(&item_gd)).into());
+            let take_callable = self.base().callable("take").bindv(varray![index as i64]);
+            button.connect("pressed".into(), take_callable);
+            self.stored.add_child(button.upcast());
+        }
+
+        let is_full = self.stash_node.bind().is_full();
+        let items = inventory_gd.bind().get_items();
+        for (index, item_gd) in items.iter_shared().enumerate() {
+            let Some(item_gd) = item_gd else {
+                continue;
+            };
+            let mut button = Button::new_alloc();
+            button.set_text(format!("Store {}", Self::describe(&item_gd)).into());
+            button.set_disabled(is_full || !item_gd.bind().is_storable());
+            let store_callable = self.base().callable("store").bindv(varray![index as i64]);
+            button.connect("pressed".into(), store_callable);
+            self.carried.add_child(button.upcast());
+        }
+    }
+
+    // Moves a whole inventory stack into the stash
+    #[func]
+    fn store(&mut self, index: i64) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let Some(item_gd) = inventory_gd
+            .bind()
+            .get_items()
+            .get(index as usize)
+            .flatten()
+        else {
+            return;
+        };
+        let (stacks, is_storable) = {
+            let item = item_gd.bind();
+            (item.get_stacks(), item.is_storable())
+        };
+
+        if is_storable
+            && !self.stash_node.bind().is_full()
+            && inventory_gd.bind_mut().take_item(item_gd.clone(), stacks)
+        {
+            self.stash_node.bind_mut().store(item_gd);
+        }
+        self.refresh();
+    }
+
+    #[func]
+    fn take(&mut self, index: i64) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let Some(item_gd) = self
+            .stash_node
+            .bind()
+            .get_items()
+            .get(index as usize)
+            .flatten()
+        else {
+            return;
+        };
+        if inventory_gd.bind().room_for(item_gd.clone()) < item_gd.bind().get_stacks() {
+            if let Some(world_gd) = self.world_node.as_mut() {
+                let text = format!("No room for {} in your bags", item_gd.bind().get_name());
+                world_gd.bind_mut().tell(text.into());
+            }
+            return;
+        }
+
+        if let Some(item_gd) = self.stash_node.bind_mut().take(index) {
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for StashUI {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        let toggle_callable = self.base().callable("toggle");
+        let close_callable = self.base().callable("close");
+        self.stash_node
+            .connect("on_toggle_stash".into(), toggle_callable);
+        self.stash_node
+            .connect("on_close_stash".into(), close_callable);
+
+        let mut world_node = self.base_mut().get_node_as::<World>("../../World");
+        let on_time_advanced_callable = self.base().callable("on_time_advanced");
+        world_node.connect("on_time_advanced".into(), on_time_advanced_callable);
+        self.world_node = Some(world_node);
+
+        let inventory_node = self
+            .base_mut()
+            .get_node_as::<Inventory>("../../Player/Inventory");
+        self.inventory_node = Some(inventory_node);
+    }
+}
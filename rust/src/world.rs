@@ -0,0 +1,2409 @@
+use godot::{
+    classes::{file_access::ModeFlags, FileAccess},
+    prelude::*,
+};
+
+use crate::{
+    economy::{
+        appraisal::{self, Appraiser, Estimate, FakeVerdict},
+        auction::{self, AuctionEvent, AuctionHouse, Lot, Party},
+        belongings::{Belongings, Place, PlayerState, SavedStack},
+        buyback::{BuybackList, SoldItem},
+        clock::GameTime,
+        collections::{CollectionError, Collections},
+        contract::{ContractBoard, ContractState},
+        crafting::Cookbook,
+        events::EventCalendar,
+        finance::{Lender, Loan, LoanBook, LoanEvent},
+        freshness::{self, FRESH},
+        ledger::Ledger,
+        market::{MarketGood, TradeError},
+        property::{Estate, PropertyEvent},
+        reputation::{self, Reputation, ReputationEvent, StockTier},
+        rivals::Rivals,
+        rng::SimRng,
+        routes::{self, Encounter, GoodInfo, KnownPrices, RouteMap, RoutePlan, TravelError},
+        save::{self, Persist, SaveWriter},
+        scenario,
+        scoring::{Campaign, HighScores, NetWorth},
+        simulation::{MarketSimulation, TICK},
+        smuggling::{Checkpoint, Inspection},
+        stacking::{self, FakeShare},
+        stall::{self, Display, Stall, StallSale},
+        taxes::TaxOffice,
+    },
+    inventory::Inventory,
+    item::Item,
+    market_event_data::MarketEventData,
+    recipe_data::RecipeData,
+    stash::Stash,
+};
+
+// How much the player's own appraisal skill grows with each use
+const SKILL_GAIN: f32 = 0.02;
+const MAX_SKILL: f32 = 0.9;
+// Where the ledger says auction trades and appraisals took place
+const AUCTION_HOUSE: &str = "Auction House";
+const APPRAISER: &str = "Appraiser";
+// Mixed into the seed for the player's own luck, kept apart from the markets
+const PLAYER_SEED_SALT: u64 = 0x7EA0_5EED;
+
+/// A good as the player sees it in a shop, priced for their standing.
+pub struct ShopGood {
+    pub item: GString,
+    pub price: u32,
+    pub stock: u32,
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct World {
+    #[export]
+    seed: i64,
+    // In-game minutes that pass per real second
+    #[export]
+    #[init(val = 2.)]
+    time_scale: f64,
+    #[export]
+    #[init(val = "user://save.txt".into())]
+    save_path: GString,
+    // Kept apart from the save so a new game doesn't wipe them
+    #[export]
+    #[init(val = "user://high_scores.txt".into())]
+    high_score_path: GString,
+    // Every item the world can hand to the player, looked up by name
+    #[export]
+    #[init(val = array![])]
+    catalogue: Array<Gd<Item>>,
+    // Festivals, shortages and crashes, the scenario's own when left empty
+    #[export]
+    #[init(val = array![])]
+    market_events: Array<Gd<MarketEventData>>,
+    // What can be crafted at workbenches, the scenario's own when left empty
+    #[export]
+    #[init(val = array![])]
+    recipes: Array<Gd<RecipeData>>,
+    #[init(val = scenario::default_world(0))]
+    simulation: MarketSimulation,
+    #[init(val = scenario::default_auction_house(0))]
+    auction: AuctionHouse,
+    reputation: Reputation,
+    contracts: ContractBoard,
+    #[init(val = scenario::default_lenders())]
+    lenders: Vec<Lender>,
+    loans: LoanBook,
+    ledger: Ledger,
+    buyback: BuybackList,
+    #[init(val = scenario::default_stalls(0))]
+    stalls: Vec<Stall>,
+    #[init(val = scenario::default_events(0))]
+    calendar: EventCalendar,
+    #[init(val = scenario::default_checkpoints())]
+    checkpoints: Vec<Checkpoint>,
+    #[init(val = scenario::default_routes())]
+    routes: RouteMap,
+    #[init(val = scenario::default_rivals())]
+    rivals: Rivals,
+    #[init(val = scenario::default_taxes())]
+    taxes: TaxOffice,
+    // What the player saw on the shelves, the route planner goes by these
+    known_prices: KnownPrices,
+    #[init(val = scenario::default_recipes())]
+    cookbook: Cookbook,
+    #[init(val = scenario::default_collections())]
+    collections: Collections,
+    #[init(val = scenario::default_estate())]
+    estate: Estate,
+    #[init(val = scenario::default_campaign())]
+    campaign: Campaign,
+    high_scores: HighScores,
+    // Rolls for what the player does, appraisals, inspections and the road,
+    // so they don't shift what happens in the markets
+    #[init(val = SimRng::new(PLAYER_SEED_SALT))]
+    rng: SimRng,
+    inventory_node: Option<Gd<Inventory>>,
+    stash_node: Option<Gd<Stash>>,
+    // In-game minutes that are not yet a whole minute
+    elapsed: f64,
+    base: Base<Node>,
+}
+
+impl World {
+    pub fn simulation(&self) -> &MarketSimulation {
+        &self.simulation
+    }
+
+    pub fn simulation_mut(&mut self) -> &mut MarketSimulation {
+        &mut self.simulation
+    }
+
+    pub fn reputation(&self) -> &Reputation {
+        &self.reputation
+    }
+
+    pub fn contracts(&self) -> &ContractBoard {
+        &self.contracts
+    }
+
+    pub fn auction(&self) -> &AuctionHouse {
+        &self.auction
+    }
+
+    pub fn lender(&self, name: &str) -> Option<&Lender> {
+        self.lenders.iter().find(|l| l.name == name)
+    }
+
+    pub fn loans(&self) -> &LoanBook {
+        &self.loans
+    }
+
+    pub fn buyback(&self) -> &BuybackList {
+        &self.buyback
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn calendar(&self) -> &EventCalendar {
+        &self.calendar
+    }
+
+    pub fn routes(&self) -> &RouteMap {
+        &self.routes
+    }
+
+    // Every trip out of the player's town, most profitable first
+    pub fn route_plans(&self) -> Vec<RoutePlan> {
+        let Some(inventory_gd) = self.inventory_node.as_ref() else {
+            return vec![];
+        };
+        let inventory = inventory_gd.bind();
+        routes::plan(
+            &self.simulation,
+            &self.known_prices,
+            &self.taxes,
+            &self.routes,
+            &self.routes.location,
+            inventory.get_funds().max(0) as u32,
+            |item| {
+                self.find_item(item)
+                    .map_or(0, |item_gd| inventory.room_for(item_gd).max(0) as u32)
+            },
+            |market| self.standing(&market.into()),
+            |item| {
+                self.find_item(item)
+                    .map_or_else(GoodInfo::default, |item_gd| {
+                        let item = item_gd.bind();
+                        GoodInfo {
+                            category: item.get_category().to_string(),
+                            contraband: item.get_contraband(),
+                        }
+                    })
+            },
+        )
+    }
+
+    pub fn taxes(&self) -> &TaxOffice {
+        &self.taxes
+    }
+
+    pub fn cookbook(&self) -> &Cookbook {
+        &self.cookbook
+    }
+
+    fn new_calendar(&self) -> EventCalendar {
+        if self.market_events.is_empty() {
+            scenario::default_events(self.seed as u64)
+        } else {
+            self.market_events
+                .iter_shared()
+                .fold(EventCalendar::new(self.seed as u64), |calendar, data| {
+                    calendar.with_event(data.bind().to_def())
+                })
+        }
+    }
+
+    fn new_cookbook(&self) -> Cookbook {
+        if self.recipes.is_empty() {
+            return scenario::default_recipes();
+        }
+        self.recipes
+            .iter_shared()
+            .fold(Cookbook::default(), |cookbook, data| {
+                cookbook.with_recipe(data.bind().to_recipe())
+            })
+    }
+
+    pub fn collections(&self) -> &Collections {
+        &self.collections
+    }
+
+    pub fn estate(&self) -> &Estate {
+        &self.estate
+    }
+
+    pub fn campaign(&self) -> &Campaign {
+        &self.campaign
+    }
+
+    pub fn high_scores(&self) -> &HighScores {
+        &self.high_scores
+    }
+
+    // Goods are valued at what the player's appraisal says they are worth
+    // or, short of a firm figure, at what the markets ask for them. Goods on
+    // display and takings waiting in a stall's till count as property. What
+    // the player can't sell is worth nothing to them
+    pub fn net_worth(&self) -> NetWorth {
+        let Some(inventory_gd) = self.inventory_node.as_ref() else {
+            return NetWorth::default();
+        };
+        let inventory = inventory_gd.bind();
+
+        // Whatever sits in the stash is the player's as much as what they carry
+        let stashed = self
+            .stash_node
+            .as_ref()
+            .map_or(array![], |stash_gd| stash_gd.bind().get_items());
+        let goods = inventory
+            .get_items()
+            .iter_shared()
+            .chain(stashed.iter_shared())
+            .flatten()
+            .filter(|item_gd| item_gd.bind().is_sellable())
+            .map(|item_gd| {
+                let item = item_gd.bind();
+                let estimate = item.estimate();
+                let unit = if estimate.is_exact() {
+                    estimate.low
+                } else {
+                    auction::market_price(&self.simulation, &item.get_name().to_string())
+                };
+                freshness::price(unit, item.get_freshness()) as i64 * item.get_stacks()
+            })
+            .sum();
+        let property = self
+            .stalls
+            .iter()
+            .map(|stall| {
+                let displayed: i64 = stall
+                    .slots()
+                    .iter()
+                    .flatten()
+                    .map(|d| {
+                        stall::town_price(&self.simulation, &stall.town, &d.item) as i64
+                            * d.quantity as i64
+                    })
+                    .sum();
+                displayed + stall.till() as i64
+            })
+            .sum::<i64>()
+            + self.estate.value() as i64;
+
+        NetWorth {
+            funds: inventory.get_funds(),
+            inventory: goods,
+            property,
+            debt: self.loans.total_debt() as i64,
+        }
+    }
+
+    // Best price any market the player could sell the item to pays for one
+    // unit at their standing. Only black markets take contraband
+    pub fn best_offer(&self, item_gd: &Gd<Item>) -> Option<(String, u32)> {
+        let (name, contraband, freshness) = {
+            let item = item_gd.bind();
+            (
+                item.get_name().to_string(),
+                item.get_contraband(),
+                item.get_freshness(),
+            )
+        };
+        self.simulation
+            .markets()
+            .iter()
+            .filter(|m| m.black_market || !contraband)
+            .filter_map(|m| {
+                let unit = m.offer(&name, None, contraband)?;
+                let standing = self.reputation.standing(m);
+                let price = reputation::sell_price(unit, standing);
+                Some((m.name.clone(), freshness::price(price, freshness)))
+            })
+            .max_by_key(|(_, price)| *price)
+    }
+
+    pub fn rivals(&self) -> &Rivals {
+        &self.rivals
+    }
+
+    pub fn stall(&self, town: &str) -> Option<&Stall> {
+        self.stalls.iter().find(|s| s.town == town)
+    }
+
+    fn stall_mut(&mut self, town: &str) -> Option<&mut Stall> {
+        self.stalls.iter_mut().find(|s| s.town == town)
+    }
+
+    // Pays the till into the player's funds and hands back what sold since
+    // the last visit
+    pub fn collect_stall(&mut self, town: &str) -> Vec<StallSale> {
+        let Some((till, sales)) = self.stall_mut(town).map(|s| s.collect()) else {
+            return vec![];
+        };
+        let tax = self.taxes.sales_tax(town, till);
+        if let Some(inventory_gd) = self.inventory_node.as_mut() {
+            inventory_gd.bind_mut().earn(till as i64 - tax as i64);
+        }
+        for sale in sales.iter() {
+            self.ledger.sell(&sale.item, 1, sale.price, town, sale.at);
+        }
+        if tax > 0 {
+            self.ledger
+                .fee("Sales tax", tax, town, self.simulation.time());
+        }
+        sales
+    }
+
+    pub fn find_item(&self, name: &str) -> Option<Gd<Item>> {
+        self.catalogue
+            .iter_shared()
+            .find(|item_gd| item_gd.bind().get_name().to_string() == name)
+    }
+
+    // Fresh copies of a catalogue item, in stacks no larger than it allows
+    pub fn make_items(&self, name: &str, quantity: u32) -> Vec<Gd<Item>> {
+        let Some(item_gd) = self.find_item(name) else {
+            godot_error!("{name} is not in the world catalogue");
+            return vec![];
+        };
+
+        let max_stacks = item_gd.bind().get_max_stacks();
+        let mut items = vec![];
+        for stacks in stacking::chunks(quantity as i64, max_stacks) {
+            if let Some(item_gd_dub) = item_gd.duplicate() {
+                if let Ok(mut new_item_gd) = item_gd_dub.try_cast::<Item>() {
+                    new_item_gd.bind_mut().set_stacks(stacks);
+                    items.push(new_item_gd);
+                }
+            }
+        }
+        items
+    }
+
+    // A catalogue item, or what one turns into once spoiled
+    fn find_any_item(&self, name: &str) -> Option<Gd<Item>> {
+        self.find_item(name).or_else(|| {
+            self.catalogue
+                .iter_shared()
+                .filter_map(|item_gd| item_gd.bind().get_spoiled())
+                .find(|item_gd| item_gd.bind().get_name().to_string() == name)
+        })
+    }
+
+    fn saved_item(&self, saved: &SavedStack) -> Option<Gd<Item>> {
+        let Some(item_gd) = self.find_any_item(&saved.item) else {
+            godot_error!("{} is not in the world catalogue", saved.item);
+            return None;
+        };
+        let mut item_gd = item_gd.duplicate()?.try_cast::<Item>().ok()?;
+        {
+            let mut item = item_gd.bind_mut();
+            item.set_stacks(saved.stacks);
+            item.set_true_value(saved.true_value);
+            if let Some(estimate) = saved.estimate {
+                item.set_estimate(estimate);
+            }
+            item.set_appraised_level(saved.appraised_level);
+            item.set_unit_cost(saved.unit_cost);
+            item.set_fake_units(saved.fake_units);
+            item.set_known_fake(saved.known_fake);
+            item.set_freshness(saved.freshness);
+            item.set_quest(saved.quest);
+            item.set_bound(saved.bound);
+            item.set_unsellable(saved.unsellable);
+            item.set_undroppable(saved.undroppable);
+        }
+        Some(item_gd)
+    }
+
+    // Every stack the player holds, as the save file keeps it
+    fn belongings(&self) -> Belongings {
+        let Some(inventory_gd) = self.inventory_node.as_ref() else {
+            return Belongings::default();
+        };
+        let inventory = inventory_gd.bind();
+        let stash_items = self
+            .stash_node
+            .as_ref()
+            .map_or_else(Array::new, |s| s.bind().get_items());
+        let places = [
+            (Place::Bags, inventory.get_items()),
+            (Place::KeyItems, inventory.get_key_items()),
+            (Place::Stash, stash_items),
+        ];
+
+        let mut stacks = vec![];
+        for (place, items) in places {
+            for item_gd in items.iter_shared().flatten() {
+                let item = item_gd.bind();
+                stacks.push(SavedStack {
+                    place,
+                    item: item.get_name().to_string(),
+                    stacks: item.get_stacks(),
+                    true_value: item.get_true_value(),
+                    estimate: (item.get_estimate_high() > 0).then(|| item.estimate()),
+                    appraised_level: item.get_appraised_level(),
+                    unit_cost: item.get_unit_cost(),
+                    fake_units: item.get_fake_units(),
+                    known_fake: item.get_known_fake(),
+                    freshness: item.get_freshness(),
+                    quest: item.get_quest(),
+                    bound: item.get_bound(),
+                    unsellable: item.get_unsellable(),
+                    undroppable: item.get_undroppable(),
+                });
+            }
+        }
+        Belongings {
+            player: Some(PlayerState {
+                funds: inventory.get_funds(),
+                appraisal_skill: inventory.get_appraisal_skill(),
+                rng: self.rng.state(),
+            }),
+            stacks,
+        }
+    }
+
+    // Older saves didn't keep the player's things, those keep the scene's
+    fn restore_belongings(&mut self, belongings: &Belongings) {
+        let Some(player) = belongings.player else {
+            return;
+        };
+        self.rng = SimRng::new(player.rng);
+
+        let items_in = |place| -> Vec<Gd<Item>> {
+            belongings
+                .in_place(place)
+                .filter_map(|saved| self.saved_item(saved))
+                .collect()
+        };
+        let (bags, key_items, stash) = (
+            items_in(Place::Bags),
+            items_in(Place::KeyItems),
+            items_in(Place::Stash),
+        );
+        if let Some(inventory_gd) = self.inventory_node.as_mut() {
+            inventory_gd
+                .bind_mut()
+                .restore(bags, key_items, player.funds, player.appraisal_skill);
+        }
+        if let Some(stash_gd) = self.stash_node.as_mut() {
+            stash_gd.bind_mut().restore(stash);
+        }
+    }
+
+    // Puts fresh copies of a catalogue item into the player's inventory
+    pub fn give_item(&mut self, name: &str, quantity: u32) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let items = self.make_items(name, quantity);
+        let given = !items.is_empty();
+        for item_gd in items {
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+        given
+    }
+
+    pub fn good(&self, market: &GString, item: &GString) -> Option<&MarketGood> {
+        self.simulation
+            .market(&market.to_string())?
+            .good(&item.to_string())
+    }
+
+    pub fn standing(&self, market: &GString) -> i32 {
+        self.simulation
+            .market(&market.to_string())
+            .map_or(0, |m| self.reputation.standing(m))
+    }
+
+    // Goods the merchant is willing to show the player, in shelf order
+    pub fn shop_goods(&self, market: &GString) -> Vec<ShopGood> {
+        let Some(m) = self.simulation.market(&market.to_string()) else {
+            return vec![];
+        };
+        let standing = self.reputation.standing(m);
+        let tier = StockTier::unlocked_at(standing);
+
+        m.goods
+            .iter()
+            .filter(|g| g.tier <= tier)
+            .map(|g| ShopGood {
+                item: g.item.as_str().into(),
+                price: reputation::buy_price(g.price(), standing),
+                stock: g.stock,
+            })
+            .collect()
+    }
+
+    // Gives a freshly bought item its hidden value, the player only knows
+    // what they paid for it
+    pub fn value_bought_item(&mut self, market: &GString, item_gd: &mut Gd<Item>, paid: u32) {
+        let Some(fair_price) = self
+            .good(market, &item_gd.bind().get_name())
+            .map(|g| g.fair_price())
+        else {
+            return;
+        };
+
+        let knowledge = self
+            .simulation
+            .market(&market.to_string())
+            .map_or(1., |m| m.knowledge);
+        let rng = &mut self.rng;
+        let mut true_value = appraisal::roll_true_value(fair_price, rng);
+        let is_fake = appraisal::roll_fake(knowledge, rng);
+        if is_fake {
+            true_value = appraisal::fake_value(true_value);
+        }
+
+        let mut item = item_gd.bind_mut();
+        let fake_units = if is_fake { item.get_stacks() } else { 0 };
+        item.set_true_value(true_value);
+        item.set_fake_units(fake_units);
+        item.set_estimate(Estimate::unknown(paid));
+        item.set_unit_cost(paid);
+    }
+
+    // A merchant looks over a fake the player offers, and remembers it if
+    // they spot it
+    fn inspect_fake(&mut self, market: &GString, item_gd: &Gd<Item>) -> FakeVerdict {
+        let Some(knowledge) = self
+            .simulation
+            .market(&market.to_string())
+            .map(|m| m.knowledge)
+        else {
+            return FakeVerdict::Unnoticed;
+        };
+        let verdict = appraisal::judge_fake(knowledge, &mut self.rng);
+        if verdict == FakeVerdict::Unnoticed {
+            return verdict;
+        }
+
+        let event = if item_gd.bind().get_known_fake() {
+            "SoldFake"
+        } else {
+            "PassedFake"
+        };
+        self.record_reputation(market.clone(), event.into());
+        verdict
+    }
+
+    fn trade(
+        &mut self,
+        market: &GString,
+        item: &GString,
+        quantity: i64,
+        is_buying: bool,
+        true_value: Option<u32>,
+        freshness: f32,
+    ) -> Result<u32, TradeError> {
+        let standing = self.standing(market);
+        let (town, is_black_market) = self
+            .simulation
+            .market(&market.to_string())
+            .map(|m| (m.town.clone(), m.black_market))
+            .ok_or(TradeError::UnknownItem)?;
+        let (category, contraband) = self
+            .find_item(&item.to_string())
+            .map(|item_gd| {
+                let item = item_gd.bind();
+                (item.get_category().to_string(), item.get_contraband())
+            })
+            .unwrap_or_default();
+        // Black markets ask for neither licences nor taxes
+        if !is_black_market && !self.taxes.may_trade(&town, &category) {
+            return Err(TradeError::Unlicensed);
+        }
+
+        let m = self
+            .simulation
+            .market_mut(&market.to_string())
+            .ok_or(TradeError::UnknownItem)?;
+
+        let quantity = quantity.max(0) as u32;
+        let total = if is_buying {
+            m.buy(&item.to_string(), quantity, standing)?
+        } else {
+            // Stale goods fetch less whatever the merchant makes of them
+            let total = m.sell(
+                &item.to_string(),
+                quantity,
+                standing,
+                true_value,
+                contraband,
+            )?;
+            freshness::price(total, freshness)
+        };
+
+        // Trading a handful of fish back and forth earns nobody's respect
+        if total >= reputation::FAIR_TRADE_MIN_VALUE {
+            self.reputation.record(m, ReputationEvent::FairTrade);
+        }
+
+        let (item, market, now) = (item.to_string(), market.to_string(), self.simulation.time());
+        if is_buying {
+            self.ledger.buy(&item, quantity, total, &market, now);
+            return Ok(total);
+        }
+
+        // The merchant pays the town's share straight to the tax collector
+        let tax = if is_black_market {
+            0
+        } else {
+            self.taxes.sales_tax(&town, total)
+        };
+        self.ledger.sell(&item, quantity, total, &market, now);
+        if tax > 0 {
+            self.ledger.fee("Sales tax", tax, &market, now);
+        }
+        Ok(total - tax)
+    }
+
+    // Perishables spoil in the player's bags, and slower in the cold stash
+    fn age_goods(&mut self, minutes: i64) {
+        if let Some(inventory_gd) = self.inventory_node.as_mut() {
+            inventory_gd.bind_mut().age_items(minutes);
+        }
+        if let Some(stash_gd) = self.stash_node.as_mut() {
+            stash_gd.bind_mut().age_items(minutes);
+        }
+    }
+
+    // Notes the set pieces the player has laid hands on, in their bags or the
+    // stash, in the collection log
+    fn log_pieces(&mut self) {
+        let mut items: Vec<String> = vec![];
+        let stores = [
+            self.inventory_node.as_ref().map(|i| i.bind().get_items()),
+            self.stash_node.as_ref().map(|s| s.bind().get_items()),
+        ];
+        for store in stores.iter().flatten() {
+            items.extend(
+                store
+                    .iter_shared()
+                    .flatten()
+                    .map(|item_gd| item_gd.bind().get_name().to_string()),
+            );
+        }
+
+        let found = self.collections.discover(items.iter().map(|i| i.as_str()));
+        for (piece, set) in found {
+            self.base_mut().emit_signal(
+                "on_piece_discovered".into(),
+                &[piece.to_variant(), set.to_variant()],
+            );
+        }
+    }
+
+    // Warehouses make room in the stash, losing one takes that room away
+    fn update_stash_capacity(&mut self) {
+        let extra = self.estate.stash_capacity() as i64;
+        if let Some(stash_gd) = self.stash_node.as_mut() {
+            stash_gd.bind_mut().set_extra_capacity(extra);
+        }
+    }
+
+    // A day of takings and upkeep. Upkeep the player can't pay costs the
+    // property a level
+    fn tick_properties(&mut self) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let now = self.simulation.time();
+        for event in self.estate.tick_day() {
+            match event {
+                PropertyEvent::Revenue { property, amount } => {
+                    inventory_gd.bind_mut().earn(amount as i64);
+                    let town = self.property_town(&property);
+                    self.ledger.income(&property, amount, &town, now);
+                }
+                PropertyEvent::Upkeep { property, amount } => {
+                    let unpaid = inventory_gd.bind_mut().charge(amount as i64);
+                    let town = self.property_town(&property);
+                    self.ledger.fee(
+                        &format!("{property} upkeep"),
+                        amount - unpaid as u32,
+                        &town,
+                        now,
+                    );
+                    if unpaid > 0 {
+                        if let Ok(level) = self.estate.neglect(&property) {
+                            self.base_mut().emit_signal(
+                                "on_property_changed".into(),
+                                &[property.to_variant(), (level as i64).to_variant()],
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        self.update_stash_capacity();
+    }
+
+    fn property_town(&self, property: &str) -> String {
+        self.estate
+            .property(property)
+            .map_or(String::new(), |p| p.town.clone())
+    }
+
+    // Charges the penalty of every accepted contract that ran out of time
+    fn expire_contracts(&mut self) {
+        let failed = self.contracts.expire(self.simulation.time());
+        if failed.is_empty() {
+            return;
+        }
+
+        for contract in failed.iter() {
+            if let Some(m) = self.simulation.market(&contract.client) {
+                self.reputation.record(m, ReputationEvent::FailedOrder);
+            }
+            if let Some(inventory_gd) = self.inventory_node.as_mut() {
+                let unpaid = inventory_gd.bind_mut().charge(contract.penalty as i64);
+                let paid = contract.penalty - unpaid as u32;
+                self.ledger.fee(
+                    "Contract penalty",
+                    paid,
+                    &contract.client,
+                    self.simulation.time(),
+                );
+            }
+            self.tell(format!("Contract failed: {contract}").into());
+        }
+
+        self.base_mut()
+            .emit_signal("on_contracts_updated".into(), &[]);
+    }
+
+    // Compounds a day of interest and deals with missed installments
+    fn tick_loans(&mut self) {
+        let events = self.loans.tick_day(self.simulation.time());
+        for event in events {
+            match event {
+                LoanEvent::Late { loan, penalty } => {
+                    self.tell(
+                        format!("Missed a payment on loan {loan}, {penalty} coins added").into(),
+                    );
+                }
+                LoanEvent::Defaulted { loan } => self.seize_goods(&loan),
+            }
+        }
+        self.emit_debt();
+    }
+
+    // The lender takes whole stacks from the inventory until the debt is
+    // covered, whatever they can't cover is lost to them
+    fn seize_goods(&mut self, loan: &Loan) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+
+        let mut owed = loan.balance as i64;
+        // A copy, taking a stack shifts the ones after it
+        let items: Vec<_> = inventory_gd
+            .bind()
+            .get_items()
+            .iter_shared()
+            .flatten()
+            .collect();
+        for item_gd in items {
+            if owed <= 0 {
+                break;
+            }
+            let (name, value, stacks) = {
+                let item = item_gd.bind();
+                (item.get_name(), item.value() as i64, item.get_stacks())
+            };
+            if inventory_gd.bind_mut().take_item(item_gd, stacks) {
+                self.tell(format!("{} seized {stacks} {name}", loan.lender).into());
+                owed -= value * stacks;
+                self.ledger.lose(
+                    &name.to_string(),
+                    stacks as u32,
+                    &loan.lender,
+                    self.simulation.time(),
+                );
+            }
+        }
+    }
+
+    fn start_events(&mut self, day: u64) {
+        for announcement in self.calendar.on_new_day(day, &mut self.simulation) {
+            let town = announcement.town.unwrap_or_default();
+            self.base_mut().emit_signal(
+                "on_event_announced".into(),
+                &[
+                    town.to_variant(),
+                    announcement.title.to_variant(),
+                    announcement.text.to_variant(),
+                ],
+            );
+        }
+    }
+
+    // Guards take every contraband stack the player carries
+    fn confiscate_contraband(&mut self, checkpoint: &str) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        // Picked out first, taking a stack shifts the ones after it
+        let contraband: Vec<_> = inventory_gd
+            .bind()
+            .get_items()
+            .iter_shared()
+            .flatten()
+            .filter(|item_gd| item_gd.bind().get_contraband())
+            .collect();
+        for item_gd in contraband {
+            let (name, stacks) = {
+                let item = item_gd.bind();
+                (item.get_name(), item.get_stacks())
+            };
+            if inventory_gd.bind_mut().take_item(item_gd, stacks) {
+                self.ledger.lose(
+                    &name.to_string(),
+                    stacks as u32,
+                    checkpoint,
+                    self.simulation.time(),
+                );
+            }
+        }
+    }
+
+    // Bandits and storms take their share of every stack the player carries
+    fn suffer_encounter(&mut self, encounter: Encounter, road: &str) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        // Picked out first, a stack taken whole shifts the ones after it
+        let items: Vec<_> = inventory_gd
+            .bind()
+            .get_items()
+            .iter_shared()
+            .flatten()
+            .collect();
+        for item_gd in items {
+            let (name, stacks) = {
+                let item = item_gd.bind();
+                (item.get_name(), item.get_stacks())
+            };
+            let lost = encounter.units_lost(stacks.max(0) as u32);
+            if lost > 0 && inventory_gd.bind_mut().take_item(item_gd, lost as i64) {
+                self.ledger
+                    .lose(&name.to_string(), lost, road, self.simulation.time());
+            }
+        }
+    }
+
+    // Settles goals against the player's net worth, ends the campaign and
+    // enters the high score table once the last goal is decided
+    fn check_goals(&mut self) {
+        if self.campaign.is_over() {
+            return;
+        }
+        let net_worth = self.net_worth().total();
+        let day = self.simulation.time().day();
+        for goal in self.campaign.check(net_worth, day) {
+            self.base_mut()
+                .emit_signal("on_goal_changed".into(), &[goal.to_string().to_variant()]);
+        }
+        if !self.campaign.is_over() {
+            return;
+        }
+
+        let results = self.campaign.results(net_worth, day);
+        let rank = self.high_scores.record(results.clone());
+        self.save_high_scores();
+        self.base_mut().emit_signal(
+            "on_campaign_over".into(),
+            &[
+                results.to_string().to_variant(),
+                rank.map_or(0, |r| r as i64).to_variant(),
+            ],
+        );
+    }
+
+    fn save_high_scores(&self) {
+        let mut writer = SaveWriter::new();
+        self.high_scores.save(&mut writer);
+        match FileAccess::open(self.high_score_path.clone(), ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(writer.finish().into());
+            }
+            None => godot_error!("Failed to open {} for writing", self.high_score_path),
+        }
+    }
+
+    fn load_high_scores(&mut self) {
+        if !FileAccess::file_exists(self.high_score_path.clone()) {
+            return;
+        }
+        let text = FileAccess::get_file_as_string(self.high_score_path.clone()).to_string();
+        let mut high_scores = HighScores::default();
+        let mut parts: [&mut dyn Persist; 1] = [&mut high_scores];
+        match save::load_all(&text, &mut parts) {
+            Ok(()) => self.high_scores = high_scores,
+            Err(e) => godot_error!("Failed to load {}: {e}", self.high_score_path),
+        }
+    }
+
+    fn emit_debt(&mut self) {
+        let debt = self.loans.total_debt() as i64;
+        self.base_mut()
+            .emit_signal("on_debt_changed".into(), &[debt.to_variant()]);
+    }
+
+    // Pays out and hands over whatever the auction house settled
+    // Hands the player the goods of a lot, as they were when listed
+    fn give_lot(&mut self, lot: &Lot) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let mut fakes = FakeShare::new(lot.quantity as i64, lot.fake_units as i64);
+        for mut item_gd in self.make_items(&lot.item, lot.quantity) {
+            {
+                let mut item = item_gd.bind_mut();
+                let stacks = item.get_stacks();
+                item.set_freshness(lot.freshness);
+                item.set_true_value(lot.true_value);
+                item.set_fake_units(fakes.take(stacks));
+                item.set_known_fake(lot.known_fake);
+                item.set_unit_cost(lot.unit_cost);
+                if let Some(estimate) = lot.estimate {
+                    item.set_estimate(estimate);
+                }
+            }
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+    }
+
+    fn settle_auction(&mut self, events: Vec<AuctionEvent>) {
+        if events.is_empty() {
+            return;
+        }
+
+        for event in events {
+            match event {
+                AuctionEvent::Refund {
+                    bidder: Party::Player,
+                    amount,
+                    ..
+                } => {
+                    if let Some(inventory_gd) = self.inventory_node.as_mut() {
+                        inventory_gd.bind_mut().earn(amount as i64);
+                    }
+                }
+                AuctionEvent::Sold { lot, buyer, price } => {
+                    let now = self.simulation.time();
+                    if lot.seller == Party::Player {
+                        if let Some(inventory_gd) = self.inventory_node.as_mut() {
+                            inventory_gd.bind_mut().earn(price as i64);
+                        }
+                        self.ledger
+                            .sell(&lot.item, lot.quantity, price, AUCTION_HOUSE, now);
+                    }
+                    if buyer == Party::Player {
+                        self.give_lot(&lot);
+                        self.ledger
+                            .buy(&lot.item, lot.quantity, price, AUCTION_HOUSE, now);
+                    }
+                }
+                AuctionEvent::Unsold { lot } if lot.seller == Party::Player => {
+                    self.give_lot(&lot);
+                }
+                _ => {}
+            }
+        }
+
+        self.base_mut()
+            .emit_signal("on_auction_updated".into(), &[]);
+    }
+}
+
+#[godot_api]
+impl World {
+    #[signal]
+    fn on_time_advanced(&mut self, day: i64, hour: i64);
+
+    // Why something the player tried didn't work, or what happened to them
+    #[signal]
+    fn on_message(&mut self, text: GString);
+
+    #[signal]
+    fn on_market_updated(&mut self);
+
+    #[signal]
+    fn on_reputation_changed(&mut self, market: GString, reputation: i64);
+
+    #[signal]
+    fn on_contracts_updated(&mut self);
+
+    #[signal]
+    fn on_auction_updated(&mut self);
+
+    #[signal]
+    fn on_debt_changed(&mut self, debt: i64);
+
+    // `town` is empty for news that reaches every town
+    #[signal]
+    fn on_event_announced(&mut self, town: GString, title: GString, text: GString);
+
+    #[signal]
+    fn on_inspected(&mut self, checkpoint: GString, outcome: GString);
+
+    // A merchant caught the player selling a fake
+    #[signal]
+    fn on_fake_detected(&mut self, market: GString, item: GString, confiscated: bool);
+
+    #[signal]
+    fn on_travelled(&mut self, town: GString, report: GString);
+
+    #[signal]
+    fn on_goal_changed(&mut self, goal: GString);
+
+    // `rank` in the scenario's high score table, 0 when it didn't make it
+    #[signal]
+    fn on_campaign_over(&mut self, results: GString, rank: i64);
+
+    #[signal]
+    fn on_recipe_discovered(&mut self, recipe: GString);
+
+    #[signal]
+    fn on_crafted(&mut self, recipe: GString, item: GString, quantity: i64);
+
+    #[signal]
+    fn on_piece_discovered(&mut self, piece: GString, set: GString);
+
+    // `level` 0 means the player lost the property
+    #[signal]
+    fn on_property_changed(&mut self, property: GString, level: i64);
+
+    // Shows the player a line on the HUD
+    #[func]
+    pub fn tell(&mut self, text: GString) {
+        self.base_mut()
+            .emit_signal("on_message".into(), &[text.to_variant()]);
+    }
+
+    #[func]
+    pub fn get_day(&self) -> i64 {
+        self.simulation.time().day() as i64
+    }
+
+    #[func]
+    pub fn get_hour(&self) -> i64 {
+        self.simulation.time().hour_of_day() as i64
+    }
+
+    #[func]
+    pub fn advance_minutes(&mut self, minutes: i64) {
+        if minutes <= 0 {
+            return;
+        }
+
+        let previous_day = self.get_day();
+
+        let auction = &mut self.auction;
+        let stalls = &mut self.stalls;
+        let rivals = &mut self.rivals;
+        let routes = &self.routes;
+        let mut auction_events = vec![];
+        let ticks = self
+            .simulation
+            .advance_with(GameTime::from_minutes(minutes as u64), |sim| {
+                auction_events.extend(auction.tick(sim.time(), sim));
+                for stall in stalls.iter_mut() {
+                    stall.tick(sim.time(), sim);
+                }
+                rivals.tick(sim, routes);
+            });
+        if ticks == 0 {
+            return;
+        }
+        // By whole ticks, the minutes short of one carry over to the next call
+        let elapsed = GameTime::from_minutes(ticks * TICK.minutes());
+        self.age_goods(elapsed.minutes() as i64);
+        self.buyback.age(elapsed);
+        self.log_pieces();
+        // The player sees the shelves of the town they're in
+        let now = self.simulation.time();
+        self.known_prices
+            .observe(&self.simulation, &self.routes.location, now);
+
+        let day = self.get_day();
+        let hour = self.get_hour();
+        self.base_mut().emit_signal(
+            "on_time_advanced".into(),
+            &[day.to_variant(), hour.to_variant()],
+        );
+        self.base_mut().emit_signal("on_market_updated".into(), &[]);
+
+        self.expire_contracts();
+        self.settle_auction(auction_events);
+        self.buyback.expire(self.simulation.time());
+        self.check_goals();
+
+        // Interest and events once for every day that passed
+        for day in previous_day + 1..=day {
+            self.tick_loans();
+            self.tick_properties();
+            self.start_events(day as u64);
+        }
+
+        // New contracts and an autosave at the start of every in-game day
+        if day != previous_day {
+            self.contracts.generate(&mut self.simulation);
+            self.base_mut()
+                .emit_signal("on_contracts_updated".into(), &[]);
+            self.save_game();
+        }
+    }
+
+    #[func]
+    fn get_price(&self, market: GString, item: GString) -> i64 {
+        let standing = self.standing(&market);
+        self.good(&market, &item)
+            .map_or(-1, |g| reputation::buy_price(g.price(), standing) as i64)
+    }
+
+    // Total for buying `quantity` in one order, or -1 when the market can't
+    #[func]
+    pub fn get_quote(&self, market: GString, item: GString, quantity: i64) -> i64 {
+        let standing = self.standing(&market);
+        self.simulation
+            .market(&market.to_string())
+            .and_then(|m| {
+                m.quote(&item.to_string(), quantity.max(0) as u32, standing)
+                    .ok()
+            })
+            .map_or(-1, |total| total as i64)
+    }
+
+    #[func]
+    pub fn get_affordable(&self, market: GString, item: GString, funds: i64) -> i64 {
+        let standing = self.standing(&market);
+        self.simulation.market(&market.to_string()).map_or(0, |m| {
+            m.affordable(&item.to_string(), standing, funds.max(0) as u32) as i64
+        })
+    }
+
+    #[func]
+    fn get_stock(&self, market: GString, item: GString) -> i64 {
+        self.good(&market, &item).map_or(-1, |g| g.stock as i64)
+    }
+
+    #[func]
+    fn get_reputation(&self, market: GString) -> i64 {
+        self.reputation.merchant(&market.to_string()) as i64
+    }
+
+    #[func]
+    fn get_town_reputation(&self, town: GString) -> i64 {
+        self.reputation.town(&town.to_string()) as i64
+    }
+
+    // Lets gameplay code report things like "FailedHaggle" or "SoldFake"
+    #[func]
+    pub fn record_reputation(&mut self, market: GString, event: GString) {
+        let event = match ReputationEvent::from_name(&event.to_string()) {
+            Some(event) => event,
+            None => {
+                godot_error!("Unknown reputation event {event}");
+                return;
+            }
+        };
+        let Some(m) = self.simulation.market(&market.to_string()) else {
+            godot_error!("Unknown market {market}");
+            return;
+        };
+
+        self.reputation.record(m, event);
+
+        let value = self.get_reputation(market.clone());
+        self.base_mut().emit_signal(
+            "on_reputation_changed".into(),
+            &[market.to_variant(), value.to_variant()],
+        );
+    }
+
+    // Returns the total cost, or -1 when the market can't sell
+    #[func]
+    pub fn buy(&mut self, market: GString, item: GString, quantity: i64) -> i64 {
+        match self.trade(&market, &item, quantity, true, None, FRESH) {
+            Ok(total) => {
+                self.base_mut().emit_signal("on_market_updated".into(), &[]);
+                total as i64
+            }
+            Err(e) => {
+                self.tell(format!("Can't buy {item} from {market}: {e}").into());
+                -1
+            }
+        }
+    }
+
+    // Returns the total paid out, or -1 when the market doesn't buy the item
+    #[func]
+    pub fn sell(&mut self, market: GString, item: GString, quantity: i64) -> i64 {
+        match self.trade(&market, &item, quantity, false, None, FRESH) {
+            Ok(total) => {
+                self.base_mut().emit_signal("on_market_updated".into(), &[]);
+                total as i64
+            }
+            Err(e) => {
+                self.tell(format!("Can't sell {item} to {market}: {e}").into());
+                -1
+            }
+        }
+    }
+
+    // Sells units of an inventory item, the merchant judges its hidden value
+    // as well as they can, returns the total paid out or -1
+    #[func]
+    pub fn sell_item(&mut self, market: GString, item_gd: Gd<Item>, quantity: i64) -> i64 {
+        let (item, mut true_value, fakes, freshness) = {
+            let item = item_gd.bind();
+            (
+                item.get_name(),
+                Some(item.genuine_value()),
+                item.fakes_in(quantity),
+                item.get_freshness(),
+            )
+        };
+
+        let is_black_market = self
+            .simulation
+            .market(&market.to_string())
+            .is_some_and(|m| m.black_market);
+        if !item_gd.bind().is_sellable() {
+            self.tell(format!("{item} isn't yours to sell").into());
+            return -1;
+        }
+        if item_gd.bind().get_contraband() && !is_black_market {
+            self.tell(format!("{market} won't touch contraband like {item}").into());
+            return -1;
+        }
+
+        // Any fake among the units on the counter can give the lot away
+        if fakes > 0 {
+            match self.inspect_fake(&market, &item_gd) {
+                // Taken for the real thing, and paid for like it
+                FakeVerdict::Unnoticed => true_value = None,
+                verdict => {
+                    let confiscated = verdict == FakeVerdict::Confiscated;
+                    if confiscated {
+                        if let Some(inventory_gd) = self.inventory_node.as_mut() {
+                            inventory_gd.bind_mut().take_item(item_gd.clone(), quantity);
+                        }
+                        self.ledger.lose(
+                            &item.to_string(),
+                            quantity.max(0) as u32,
+                            &market.to_string(),
+                            self.simulation.time(),
+                        );
+                    }
+                    self.base_mut().emit_signal(
+                        "on_fake_detected".into(),
+                        &[
+                            market.to_variant(),
+                            item.to_variant(),
+                            confiscated.to_variant(),
+                        ],
+                    );
+                    return -1;
+                }
+            }
+        }
+
+        match self.trade(&market, &item, quantity, false, true_value, freshness) {
+            Ok(total) => {
+                // The merchant keeps it aside for a while in case the
+                // player regrets the sale
+                let sold = {
+                    let i = item_gd.bind();
+                    SoldItem {
+                        id: 0,
+                        market: market.to_string(),
+                        item: item.to_string(),
+                        quantity: quantity as u32,
+                        price: total,
+                        true_value: i.value_of(quantity),
+                        estimate: i.estimate(),
+                        fake_units: fakes as u32,
+                        known_fake: i.get_known_fake(),
+                        freshness: i.get_freshness(),
+                        shelf_life: GameTime::from_hours(i.get_shelf_life_hours() as u64),
+                        expires_at: GameTime::ZERO,
+                    }
+                };
+                self.buyback.record(sold, self.simulation.time());
+
+                self.base_mut().emit_signal("on_market_updated".into(), &[]);
+                total as i64
+            }
+            Err(e) => {
+                self.tell(format!("Can't sell {item} to {market}: {e}").into());
+                -1
+            }
+        }
+    }
+
+    // Leaves a whole stack behind, it's gone for good
+    #[func]
+    pub fn drop_item(&mut self, item_gd: Gd<Item>) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let (name, stacks, is_droppable) = {
+            let item = item_gd.bind();
+            (item.get_name(), item.get_stacks(), item.is_droppable())
+        };
+        if !is_droppable {
+            self.tell(format!("You can't part with {name}").into());
+            return false;
+        }
+
+        if !inventory_gd.bind_mut().take_item(item_gd, stacks) {
+            return false;
+        }
+        let town = self.routes.location.clone();
+        self.ledger.lose(
+            &name.to_string(),
+            stacks as u32,
+            &town,
+            self.simulation.time(),
+        );
+        true
+    }
+
+    // Pays back what the merchant paid for a recent sale and returns the
+    // goods exactly as they were sold
+    #[func]
+    pub fn buy_back(&mut self, id: i64) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let now = self.simulation.time();
+        let Some(price) = self.buyback.get(id as u32).map(|s| s.price as i64) else {
+            return false;
+        };
+        if inventory_gd.bind().get_funds() < price {
+            return false;
+        }
+        let sold = match self.buyback.take(id as u32, now) {
+            Ok(sold) => sold,
+            Err(e) => {
+                self.tell(format!("Can't buy back {id}: {e}").into());
+                return false;
+            }
+        };
+        inventory_gd.bind_mut().spend(price);
+
+        if let Some(good) = self
+            .simulation
+            .market_mut(&sold.market)
+            .and_then(|m| m.good_mut(&sold.item))
+        {
+            good.stock = good.stock.saturating_sub(sold.quantity);
+        }
+        self.ledger
+            .buy(&sold.item, sold.quantity, sold.price, &sold.market, now);
+
+        let mut fakes = FakeShare::new(sold.quantity as i64, sold.fake_units as i64);
+        for mut item_gd in self.make_items(&sold.item, sold.quantity) {
+            {
+                let mut item = item_gd.bind_mut();
+                let stacks = item.get_stacks();
+                item.set_true_value(sold.true_value);
+                item.set_estimate(sold.estimate);
+                item.set_fake_units(fakes.take(stacks));
+                item.set_known_fake(sold.known_fake);
+                item.set_freshness(sold.freshness);
+                item.set_unit_cost(sold.price / sold.quantity.max(1));
+            }
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+
+        self.base_mut().emit_signal("on_market_updated".into(), &[]);
+        true
+    }
+
+    // Narrows the player's estimate of an item's value. `method` is "Skill"
+    // for the player's own eye, "Loupe" once they own one, or "Paid" for a
+    // professional who charges a fee. Returns false when it can't be done
+    #[func]
+    pub fn appraise(&mut self, mut item_gd: Gd<Item>, method: GString) -> bool {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return false,
+        };
+
+        let appraiser = match method.to_string().as_str() {
+            "Skill" => Appraiser::skill(inventory_gd.bind().get_appraisal_skill()),
+            "Loupe" if inventory_gd.bind().count_item(appraisal::LOUPE_ITEM.into()) == 0 => {
+                self.tell("You need a loupe for that".into());
+                return false;
+            }
+            "Loupe" => Appraiser::LOUPE,
+            "Paid" => Appraiser::PAID,
+            _ => {
+                godot_error!("Unknown appraisal method {method}");
+                return false;
+            }
+        };
+        let (name, appraised_level) = {
+            let item = item_gd.bind();
+            (item.get_name(), item.get_appraised_level())
+        };
+        let eye_level = appraiser.eye_level();
+        if eye_level.is_some_and(|level| appraised_level >= level) {
+            self.tell(
+                format!("You can't tell any more about {name} until your eye improves").into(),
+            );
+            return false;
+        }
+        if !inventory_gd.bind_mut().spend(appraiser.fee as i64) {
+            self.tell(format!("Can't afford to have {name} appraised").into());
+            return false;
+        }
+        self.ledger.fee(
+            "Appraisal",
+            appraiser.fee,
+            APPRAISER,
+            self.simulation.time(),
+        );
+
+        // Unnoticed fakes pass for the real thing, so the estimate is of a
+        // real unit
+        let (value, estimate, has_fakes) = {
+            let item = item_gd.bind();
+            if item.get_known_fake() {
+                (item.value(), item.estimate(), false)
+            } else {
+                (item.genuine_value(), item.estimate(), item.has_fakes())
+            }
+        };
+        let rng = &mut self.rng;
+        let estimate = estimate.narrow(value, appraiser, rng);
+        let caught = has_fakes && appraisal::detects_fake(appraiser, rng);
+        item_gd.bind_mut().set_estimate(estimate);
+        if let Some(level) = eye_level {
+            item_gd.bind_mut().set_appraised_level(level);
+        }
+        if caught {
+            inventory_gd.bind_mut().split_off_fakes(item_gd.clone());
+        }
+
+        // Practice makes the player's own eye sharper
+        if method.to_string() == "Skill" {
+            let skill = inventory_gd.bind().get_appraisal_skill();
+            inventory_gd
+                .bind_mut()
+                .set_appraisal_skill((skill + SKILL_GAIN).min(MAX_SKILL));
+        }
+        true
+    }
+
+    #[func]
+    pub fn accept_contract(&mut self, id: i64) -> bool {
+        match self.contracts.accept(id as u32) {
+            Ok(_) => {
+                self.base_mut()
+                    .emit_signal("on_contracts_updated".into(), &[]);
+                true
+            }
+            Err(e) => {
+                self.tell(format!("Can't accept contract {id}: {e}").into());
+                false
+            }
+        }
+    }
+
+    // Hands over the goods of every accepted contract in `town` that the
+    // player's inventory can cover, returns how many were completed
+    #[func]
+    pub fn deliver_contracts(&mut self, town: GString) -> i64 {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return 0,
+        };
+
+        let town = town.to_string();
+        let accepted: Vec<_> = self
+            .contracts
+            .in_town(&town)
+            .filter(|c| c.state == ContractState::Accepted)
+            .cloned()
+            .collect();
+
+        let mut completed = 0;
+        for contract in accepted {
+            let item: GString = contract.item.as_str().into();
+            let held = inventory_gd.bind().count_sellable(item.clone()).max(0) as u32;
+
+            let reward = match self
+                .contracts
+                .fulfil(contract.id, held, self.simulation.time())
+            {
+                Ok(reward) => reward,
+                Err(_) => continue,
+            };
+
+            inventory_gd
+                .bind_mut()
+                .remove_sellable(item, contract.quantity as i64);
+            inventory_gd.bind_mut().earn(reward as i64);
+            self.ledger.sell(
+                &contract.item,
+                contract.quantity,
+                reward,
+                &town,
+                self.simulation.time(),
+            );
+            if let Some(m) = self.simulation.market(&contract.client) {
+                self.reputation.record(m, ReputationEvent::CompletedOrder);
+            }
+            completed += 1;
+        }
+
+        if completed > 0 {
+            self.base_mut()
+                .emit_signal("on_contracts_updated".into(), &[]);
+        }
+        completed
+    }
+
+    // Takes the items out of the inventory and puts them up for `hours`,
+    // returns the lot id or -1
+    #[func]
+    pub fn list_on_auction(
+        &mut self,
+        item_gd: Gd<Item>,
+        quantity: i64,
+        reserve: i64,
+        hours: i64,
+    ) -> i64 {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return -1,
+        };
+        // Read before the units leave the stack, they take their fakes along
+        let (name, is_sellable, fakes, true_value) = {
+            let item = item_gd.bind();
+            (
+                item.get_name(),
+                item.is_sellable(),
+                item.fakes_in(quantity),
+                item.value_of(quantity),
+            )
+        };
+        let listed = item_gd.clone();
+        let fee = self.taxes.auction_listing_fee(reserve.max(0) as u32);
+        if hours <= 0 || !is_sellable || inventory_gd.bind().get_funds() < fee as i64 {
+            return -1;
+        }
+        // One stack at a time, so the lot is exactly what the player held
+        if !inventory_gd.bind_mut().take_item(item_gd, quantity) {
+            return -1;
+        }
+        inventory_gd.bind_mut().spend(fee as i64);
+        self.ledger
+            .fee("Auction fee", fee, AUCTION_HOUSE, self.simulation.time());
+
+        let ends_at = self.simulation.time() + GameTime::from_hours(hours as u64);
+        let id = self.auction.list(
+            &name.to_string(),
+            quantity as u32,
+            Party::Player,
+            reserve.max(0) as u32,
+            ends_at,
+        );
+        if let Some(lot) = self.auction.lot_mut(id) {
+            let item = listed.bind();
+            lot.freshness = item.get_freshness();
+            lot.shelf_life = GameTime::from_hours(item.get_shelf_life_hours() as u64);
+            lot.true_value = true_value;
+            lot.estimate = (item.get_estimate_high() > 0).then(|| item.estimate());
+            lot.fake_units = fakes as u32;
+            lot.known_fake = item.get_known_fake();
+            lot.unit_cost = item.get_unit_cost();
+        }
+
+        self.base_mut()
+            .emit_signal("on_auction_updated".into(), &[]);
+        id as i64
+    }
+
+    // The bid is taken from the player's funds and refunded if outbid
+    #[func]
+    pub fn bid_on_lot(&mut self, id: i64, amount: i64) -> bool {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return false,
+        };
+        if amount <= 0 || !inventory_gd.bind_mut().spend(amount) {
+            return false;
+        }
+
+        let now = self.simulation.time();
+        match self
+            .auction
+            .bid(id as u32, Party::Player, amount as u32, now)
+        {
+            Ok(events) => {
+                self.settle_auction(events);
+                self.base_mut()
+                    .emit_signal("on_auction_updated".into(), &[]);
+                true
+            }
+            Err(e) => {
+                inventory_gd.bind_mut().earn(amount);
+                self.tell(format!("Can't bid on lot {id}: {e}").into());
+                false
+            }
+        }
+    }
+
+    // Pays the borrowed coins into the player's funds, returns the loan id
+    // or -1 when the lender refuses
+    #[func]
+    pub fn take_loan(&mut self, lender: GString, amount: i64) -> i64 {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return -1,
+        };
+        let Some(terms) = self.lender(&lender.to_string()).cloned() else {
+            godot_error!("Unknown lender {lender}");
+            return -1;
+        };
+
+        match self
+            .loans
+            .take(&terms, amount.max(0) as u64, self.simulation.time())
+        {
+            Ok(id) => {
+                inventory_gd.bind_mut().earn(amount);
+                self.emit_debt();
+                id as i64
+            }
+            Err(e) => {
+                self.tell(format!("Can't borrow {amount} from {lender}: {e}").into());
+                -1
+            }
+        }
+    }
+
+    // Pays up to `amount` off a loan from the player's funds, returns what
+    // was paid
+    #[func]
+    pub fn repay_loan(&mut self, id: i64, amount: i64) -> i64 {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return 0,
+        };
+        let Some(balance) = self.loans.loan(id as u32).map(|l| l.balance as i64) else {
+            return 0;
+        };
+
+        let amount = amount.min(balance).min(inventory_gd.bind().get_funds());
+        if amount <= 0 || !inventory_gd.bind_mut().spend(amount) {
+            return 0;
+        }
+        let lender = self.loans.loan(id as u32).map(|l| l.lender.clone());
+        match self.loans.repay(id as u32, amount as u64) {
+            Ok(paid) => {
+                let now = self.simulation.time();
+                self.ledger
+                    .loan_payment(&lender.unwrap_or_default(), paid as u32, now);
+                self.emit_debt();
+                paid as i64
+            }
+            Err(e) => {
+                inventory_gd.bind_mut().earn(amount);
+                self.tell(format!("Can't repay loan {id}: {e}").into());
+                0
+            }
+        }
+    }
+
+    // Guards may search the player's bags, the more contraband the likelier.
+    // Returns true when they found any
+    #[func]
+    pub fn pass_checkpoint(&mut self, checkpoint: GString) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let Some(guards) = self
+            .checkpoints
+            .iter()
+            .find(|c| c.name == checkpoint.to_string())
+            .cloned()
+        else {
+            godot_error!("Unknown checkpoint {checkpoint}");
+            return false;
+        };
+
+        let weight = inventory_gd.bind().contraband_weight();
+        let inspection = guards.inspect(weight, &mut self.rng);
+        let now = self.simulation.time();
+        match inspection {
+            Inspection::Fined { fine } | Inspection::Confiscated { fine } => {
+                let unpaid = inventory_gd.bind_mut().charge(fine as i64);
+                self.ledger
+                    .fee("Smuggling fine", fine - unpaid as u32, &guards.name, now);
+                // Whoever can't pay the fine loses the goods either way
+                if unpaid > 0 || matches!(inspection, Inspection::Confiscated { .. }) {
+                    self.confiscate_contraband(&guards.name);
+                }
+            }
+            Inspection::Waved | Inspection::Searched => {}
+        }
+
+        let outcome = inspection.to_string();
+        self.base_mut().emit_signal(
+            "on_inspected".into(),
+            &[checkpoint.to_variant(), outcome.to_variant()],
+        );
+        matches!(
+            inspection,
+            Inspection::Fined { .. } | Inspection::Confiscated { .. }
+        )
+    }
+
+    #[func]
+    pub fn get_location(&self) -> GString {
+        self.routes.location.clone().into()
+    }
+
+    // Pays for the road, lets the time on it pass and brings the player to
+    // `town` with whatever the guards and the road left of their cargo
+    #[func]
+    pub fn travel(&mut self, town: GString) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let from = self.routes.location.clone();
+        let route = match self.routes.departure(&town.to_string()) {
+            Ok(route) => route,
+            Err(e) => {
+                self.tell(format!("Can't travel from {from} to {town}: {e}").into());
+                return false;
+            }
+        };
+        if !inventory_gd.bind_mut().spend(route.cost() as i64) {
+            self.tell(
+                format!(
+                    "Can't travel from {from} to {town}: {}",
+                    TravelError::CantAfford
+                )
+                .into(),
+            );
+            return false;
+        }
+
+        let now = self.simulation.time();
+        if route.toll > 0 {
+            self.ledger.fee("Toll", route.toll, &from, now);
+        }
+        self.ledger.fee("Food", route.food_cost(), &from, now);
+
+        // Arrived as far as saves go, so an autosave on the road doesn't
+        // send the player back after they paid
+        self.routes.location = town.to_string();
+        self.advance_minutes(route.duration.minutes() as i64);
+
+        let mut report = vec![format!(
+            "Arrived in {town} after {}h",
+            route.duration.hours()
+        )];
+        if let Some(checkpoint) = route.checkpoint.as_ref() {
+            self.pass_checkpoint(checkpoint.into());
+        }
+        if let Some(encounter) = route.encounter(&mut self.rng) {
+            self.suffer_encounter(encounter, &format!("Road to {town}"));
+            report.push(encounter.to_string());
+        }
+
+        let report = report.join(". ");
+        self.base_mut().emit_signal(
+            "on_travelled".into(),
+            &[town.to_variant(), report.to_variant()],
+        );
+        true
+    }
+
+    // Town a rival trader is in, empty while they are on the road
+    #[func]
+    pub fn get_rival_town(&self, rival: GString) -> GString {
+        self.rivals
+            .town_of(&rival.to_string(), &self.simulation)
+            .unwrap_or_default()
+            .into()
+    }
+
+    #[func]
+    pub fn buy_licence(&mut self, town: GString, category: GString) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let (town, category) = (town.to_string(), category.to_string());
+        let Some(price) = self.taxes.licence_price(&town, &category) else {
+            return false;
+        };
+        if inventory_gd.bind().get_funds() < price as i64 {
+            return false;
+        }
+        if let Err(e) = self.taxes.buy_licence(&town, &category) {
+            self.tell(format!("Can't buy a {category} licence in {town}: {e}").into());
+            return false;
+        }
+
+        inventory_gd.bind_mut().spend(price as i64);
+        self.ledger.fee(
+            &format!("{category} licence"),
+            price,
+            &town,
+            self.simulation.time(),
+        );
+        true
+    }
+
+    // Learns the recipes whose ingredients the player holds
+    #[func]
+    pub fn discover_recipes(&mut self) {
+        let Some(inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let found = self
+            .cookbook
+            .discover(|item| inventory_gd.bind().count_item(item.into()).max(0) as u32);
+        for name in found {
+            self.base_mut()
+                .emit_signal("on_recipe_discovered".into(), &[name.to_variant()]);
+        }
+    }
+
+    // The inputs go in at once, what they make comes out when the work is done
+    #[func]
+    pub fn craft(&mut self, workbench: GString, recipe: GString) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let checked = self
+            .cookbook
+            .check(&recipe.to_string(), &workbench.to_string(), |item| {
+                inventory_gd.bind().count_item(item.into()).max(0) as u32
+            });
+        let recipe = match checked {
+            Ok(recipe) => recipe.clone(),
+            Err(e) => {
+                self.tell(format!("Can't craft {recipe} at the {workbench}: {e}").into());
+                return false;
+            }
+        };
+        if self.find_item(&recipe.output).is_none() {
+            godot_error!(
+                "{} makes {}, which isn't in the catalogue",
+                recipe.name,
+                recipe.output
+            );
+            return false;
+        }
+
+        // A fake among the inputs makes a fake, and the result is only as
+        // fresh as the stalest perishable that went into it
+        let (mut is_fake, mut known_fake, mut stalest) = (false, false, FRESH);
+        for (item, quantity) in recipe.inputs.iter() {
+            let taken = inventory_gd
+                .bind_mut()
+                .take_units(item.into(), *quantity as i64)
+                .unwrap_or_default();
+            for (item_gd, _, fakes) in taken {
+                let item = item_gd.bind();
+                is_fake |= fakes > 0;
+                known_fake |= item.get_known_fake();
+                if item.is_perishable() {
+                    stalest = stalest.min(item.get_freshness());
+                }
+            }
+        }
+        let now = self.simulation.time();
+        let town = self.routes.location.clone();
+        let cost = self
+            .ledger
+            .craft(&recipe.inputs, &recipe.output, recipe.quantity, &town, now);
+        self.advance_minutes(recipe.duration.minutes() as i64);
+
+        for mut item_gd in self.make_items(&recipe.output, recipe.quantity) {
+            {
+                let mut item = item_gd.bind_mut();
+                if is_fake {
+                    let value = appraisal::fake_value(item.value());
+                    let stacks = item.get_stacks();
+                    item.set_true_value(value);
+                    item.set_fake_units(stacks);
+                }
+                item.set_known_fake(known_fake);
+                item.set_unit_cost((cost / recipe.quantity.max(1) as u64) as u32);
+                if item.is_perishable() {
+                    item.set_freshness(stalest);
+                }
+            }
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+
+        self.base_mut().emit_signal(
+            "on_crafted".into(),
+            &[
+                recipe.name.to_variant(),
+                recipe.output.to_variant(),
+                (recipe.quantity as i64).to_variant(),
+            ],
+        );
+        true
+    }
+
+    // Hands a complete set over to a collector in the player's town, returns
+    // what they paid or -1 when the deal is off
+    #[func]
+    pub fn sell_set(&mut self, collector: GString, set: GString) -> i64 {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return -1;
+        };
+        let (collector, set) = (collector.to_string(), set.to_string());
+        let town = self.routes.location.clone();
+        if self
+            .collections
+            .collector(&collector)
+            .is_some_and(|c| c.town != town)
+        {
+            self.tell(format!("{collector} doesn't deal in {town}").into());
+            return -1;
+        }
+
+        let pieces = self
+            .collections
+            .set(&set)
+            .map_or(vec![], |s| s.pieces.clone());
+
+        // Collectors know their pieces, fakes don't count towards a set
+        let offer = self
+            .collections
+            .offer(&collector, &set, &self.simulation, |item| {
+                inventory_gd.bind().count_genuine(item.into()).max(0) as u32
+            });
+        let total = match offer {
+            Ok(total) => total,
+            Err(e) => {
+                if e == CollectionError::Incomplete {
+                    self.expose_fakes(&collector, &pieces);
+                }
+                self.tell(format!("{collector} won't buy the {set}: {e}").into());
+                return -1;
+            }
+        };
+
+        let now = self.simulation.time();
+        let mut left = total;
+        for (i, piece) in pieces.iter().enumerate() {
+            inventory_gd.bind_mut().remove_genuine(piece.into(), 1);
+            // The takings are split evenly over the pieces, the last one
+            // gets the remainder
+            let share = if i + 1 == pieces.len() {
+                left
+            } else {
+                total / pieces.len() as u32
+            };
+            left -= share;
+            self.ledger.sell(piece, 1, share, &town, now);
+        }
+        inventory_gd.bind_mut().earn(total as i64);
+        total as i64
+    }
+
+    // A collector turns over every piece they are shown, the fakes among
+    // them stop fooling the player
+    fn expose_fakes(&mut self, collector: &str, pieces: &[String]) {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return;
+        };
+        let items: Vec<_> = inventory_gd
+            .bind()
+            .get_items()
+            .iter_shared()
+            .flatten()
+            .collect();
+        for item_gd in items {
+            let name = {
+                let item = item_gd.bind();
+                if !item.has_fakes() || item.get_known_fake() {
+                    continue;
+                }
+                item.get_name()
+            };
+            if !pieces.contains(&name.to_string()) {
+                continue;
+            }
+            inventory_gd.bind_mut().split_off_fakes(item_gd);
+            self.base_mut().emit_signal(
+                "on_fake_detected".into(),
+                &[
+                    collector.to_variant(),
+                    name.to_variant(),
+                    false.to_variant(),
+                ],
+            );
+        }
+    }
+
+    // Buys the property, or takes one the player owns up a level. Only
+    // possible in the property's town
+    #[func]
+    pub fn improve_property(&mut self, property: GString) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let property = property.to_string();
+        let Some((town, cost, level)) = self
+            .estate
+            .property(&property)
+            .map(|p| (p.town.clone(), p.next_cost(), p.level))
+        else {
+            godot_error!("There is no property called {property}");
+            return false;
+        };
+        if town != self.routes.location {
+            self.tell(format!("{property} can only be dealt with in {town}").into());
+            return false;
+        }
+        if cost.is_some_and(|cost| inventory_gd.bind().get_funds() < cost as i64) {
+            return false;
+        }
+        let cost = match self.estate.improve(&property) {
+            Ok(cost) => cost,
+            Err(e) => {
+                self.tell(format!("Can't improve {property}: {e}").into());
+                return false;
+            }
+        };
+
+        inventory_gd.bind_mut().spend(cost as i64);
+        let what = if level == 0 {
+            property.clone()
+        } else {
+            format!("{property} upgrade")
+        };
+        self.ledger.fee(&what, cost, &town, self.simulation.time());
+        self.update_stash_capacity();
+        self.base_mut().emit_signal(
+            "on_property_changed".into(),
+            &[property.to_variant(), (level as i64 + 1).to_variant()],
+        );
+        true
+    }
+
+    #[func]
+    pub fn get_net_worth(&self) -> i64 {
+        self.net_worth().total()
+    }
+
+    #[func]
+    pub fn get_debt(&self) -> i64 {
+        self.loans.total_debt() as i64
+    }
+
+    #[func]
+    pub fn rent_stall(&mut self, town: GString, days: i64) -> bool {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return false,
+        };
+        let now = self.simulation.time();
+        let Some(stall) = self.stall_mut(&town.to_string()) else {
+            godot_error!("There is no stall in {town}");
+            return false;
+        };
+
+        let rent = stall.rent_per_day as i64 * days;
+        if days <= 0 || !inventory_gd.bind_mut().spend(rent) {
+            return false;
+        }
+        stall.rent(days as u32, now);
+        self.ledger
+            .fee("Stall rent", rent as u32, &town.to_string(), now);
+        true
+    }
+
+    // Moves `quantity` out of an inventory stack onto a display slot
+    #[func]
+    pub fn display_item(
+        &mut self,
+        town: GString,
+        slot: i64,
+        item_gd: Gd<Item>,
+        quantity: i64,
+        asking: i64,
+        floor: i64,
+    ) -> bool {
+        let mut inventory_gd = match self.inventory_node.clone() {
+            Some(inventory_gd) => inventory_gd,
+            None => return false,
+        };
+        if !item_gd.bind().is_sellable() {
+            self.tell(format!("{} isn't yours to sell", item_gd.bind().get_name()).into());
+            return false;
+        }
+        // A stall is out in the open, the same rules as any shop in town apply
+        let (name, category, contraband) = {
+            let item = item_gd.bind();
+            (item.get_name(), item.get_category(), item.get_contraband())
+        };
+        if contraband {
+            self.tell(format!("{name} can't be sold in the open").into());
+            return false;
+        }
+        if !self
+            .taxes
+            .may_trade(&town.to_string(), &category.to_string())
+        {
+            self.tell(format!("Selling {name} in {town} takes a licence").into());
+            return false;
+        }
+        let display = {
+            let item = item_gd.bind();
+            Display {
+                item: item.get_name().to_string(),
+                quantity: quantity.max(0) as u32,
+                asking: asking.max(1) as u32,
+                floor: floor.clamp(1, asking.max(1)) as u32,
+                true_value: item.value_of(quantity),
+                fake_units: item.fakes_in(quantity) as u32,
+                estimate: (item.get_estimate_high() > 0).then(|| item.estimate()),
+                known_fake: item.get_known_fake(),
+                freshness: item.get_freshness(),
+                shelf_life: GameTime::from_hours(item.get_shelf_life_hours() as u64),
+                unit_cost: item.get_unit_cost(),
+            }
+        };
+
+        let now = self.simulation.time();
+        let fee = self.taxes.listing_fee(&town.to_string());
+        if inventory_gd.bind().get_funds() < fee as i64 {
+            return false;
+        }
+        let Some(stall) = self.stall_mut(&town.to_string()) else {
+            return false;
+        };
+        if let Err(e) = stall.place(slot.max(0) as usize, display.clone(), now) {
+            self.tell(format!("Can't display {} in {town}: {e}", display.item).into());
+            return false;
+        }
+
+        if !inventory_gd.bind_mut().take_item(item_gd, quantity) {
+            // Put it back the way it was, the slot was empty before
+            if let Some(stall) = self.stall_mut(&town.to_string()) {
+                let _ = stall.take_back(slot as usize);
+            }
+            return false;
+        }
+        if fee > 0 {
+            inventory_gd.bind_mut().spend(fee as i64);
+            self.ledger.fee("Listing fee", fee, &town.to_string(), now);
+        }
+        true
+    }
+
+    // Puts what is left on a display slot back into the inventory
+    #[func]
+    pub fn take_back_display(&mut self, town: GString, slot: i64) -> bool {
+        let Some(mut inventory_gd) = self.inventory_node.clone() else {
+            return false;
+        };
+        let Some(stall) = self.stall_mut(&town.to_string()) else {
+            return false;
+        };
+        let display = match stall.take_back(slot.max(0) as usize) {
+            Ok(display) => display,
+            Err(e) => {
+                self.tell(format!("Can't take back slot {slot} in {town}: {e}").into());
+                return false;
+            }
+        };
+
+        let mut fakes = FakeShare::new(display.quantity as i64, display.fake_units as i64);
+        for mut item_gd in self.make_items(&display.item, display.quantity) {
+            {
+                let mut item = item_gd.bind_mut();
+                let stacks = item.get_stacks();
+                item.set_true_value(display.true_value);
+                item.set_fake_units(fakes.take(stacks));
+                item.set_known_fake(display.known_fake);
+                item.set_freshness(display.freshness);
+                item.set_unit_cost(display.unit_cost);
+                if let Some(estimate) = display.estimate {
+                    item.set_estimate(estimate);
+                }
+            }
+            inventory_gd.bind_mut().add_item(item_gd);
+        }
+        true
+    }
+
+    #[func]
+    pub fn save_game(&mut self) -> bool {
+        let mut writer = SaveWriter::new();
+        // The clock goes first, everything after it may hold times
+        self.simulation.save(&mut writer);
+        self.calendar.save(&mut writer);
+        self.belongings().save(&mut writer);
+        self.reputation.save(&mut writer);
+        self.contracts.save(&mut writer);
+        self.auction.save(&mut writer);
+        self.loans.save(&mut writer);
+        self.ledger.save(&mut writer);
+        self.buyback.save(&mut writer);
+        self.routes.save(&mut writer);
+        self.rivals.save(&mut writer);
+        self.campaign.save(&mut writer);
+        self.taxes.save(&mut writer);
+        self.cookbook.save(&mut writer);
+        self.collections.save(&mut writer);
+        self.estate.save(&mut writer);
+        self.known_prices.save(&mut writer);
+        for stall in self.stalls.iter() {
+            stall.save(&mut writer);
+        }
+
+        match FileAccess::open(self.save_path.clone(), ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(writer.finish().into());
+                true
+            }
+            None => {
+                godot_error!("Failed to open {} for writing", self.save_path);
+                false
+            }
+        }
+    }
+
+    #[func]
+    pub fn load_game(&mut self) -> bool {
+        if !FileAccess::file_exists(self.save_path.clone()) {
+            return false;
+        }
+        let text = FileAccess::get_file_as_string(self.save_path.clone()).to_string();
+
+        let mut simulation = scenario::default_world(self.seed as u64);
+        let mut calendar = self.new_calendar();
+        let mut belongings = Belongings::default();
+        let mut reputation = Reputation::default();
+        let mut contracts = ContractBoard::default();
+        let mut auction = scenario::default_auction_house(self.seed as u64);
+        let mut loans = LoanBook::default();
+        let mut ledger = Ledger::default();
+        let mut buyback = BuybackList::default();
+        let mut stalls = scenario::default_stalls(self.seed as u64);
+        let mut routes = scenario::default_routes();
+        let mut rivals = scenario::default_rivals();
+        let mut campaign = scenario::default_campaign();
+        let mut taxes = scenario::default_taxes();
+        let mut cookbook = self.new_cookbook();
+        let mut collections = scenario::default_collections();
+        let mut estate = scenario::default_estate();
+        let mut known_prices = KnownPrices::default();
+
+        let mut parts: Vec<&mut dyn Persist> = vec![
+            &mut simulation,
+            &mut calendar,
+            &mut belongings,
+            &mut reputation,
+            &mut contracts,
+            &mut auction,
+            &mut loans,
+            &mut ledger,
+            &mut buyback,
+            &mut routes,
+            &mut rivals,
+            &mut campaign,
+            &mut taxes,
+            &mut cookbook,
+            &mut collections,
+            &mut estate,
+            &mut known_prices,
+        ];
+        parts.extend(stalls.iter_mut().map(|s| s as &mut dyn Persist));
+        match save::load_all(&text, &mut parts) {
+            Ok(()) => {
+                self.simulation = simulation;
+                self.calendar = calendar;
+                self.restore_belongings(&belongings);
+                self.reputation = reputation;
+                self.contracts = contracts;
+                self.auction = auction;
+                self.loans = loans;
+                self.ledger = ledger;
+                self.buyback = buyback;
+                self.stalls = stalls;
+                self.routes = routes;
+                self.rivals = rivals;
+                self.campaign = campaign;
+                self.taxes = taxes;
+                self.cookbook = cookbook;
+                self.collections = collections;
+                self.estate = estate;
+                self.known_prices = known_prices;
+                self.update_stash_capacity();
+                self.base_mut().emit_signal("on_market_updated".into(), &[]);
+                self.emit_debt();
+                true
+            }
+            Err(e) => {
+                godot_error!("Failed to load {}: {e}", self.save_path);
+                false
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl INode for World {
+    fn ready(&mut self) {
+        self.inventory_node = Some(
+            self.base_mut()
+                .get_node_as::<Inventory>("../Player/Inventory"),
+        );
+        self.stash_node = self.base().try_get_node_as::<Stash>("../Stash");
+
+        self.simulation = scenario::default_world(self.seed as u64);
+        self.rng = SimRng::new(self.seed as u64 ^ PLAYER_SEED_SALT);
+        self.auction = scenario::default_auction_house(self.seed as u64);
+        self.stalls = scenario::default_stalls(self.seed as u64);
+        self.calendar = self.new_calendar();
+        self.cookbook = self.new_cookbook();
+        self.load_high_scores();
+        if !self.load_game() {
+            self.contracts.generate(&mut self.simulation);
+        }
+        let now = self.simulation.time();
+        self.known_prices
+            .observe(&self.simulation, &self.routes.location, now);
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.elapsed += delta * self.time_scale;
+
+        let minutes = self.elapsed.floor();
+        self.elapsed -= minutes;
+        self.advance_minutes(minutes as i64);
+    }
+}